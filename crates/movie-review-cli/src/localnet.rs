@@ -0,0 +1,133 @@
+//! Subcommands for setting up scenarios against a localnet program built with
+//! the `localnet` feature. They only run with `--localnet` and a localhost
+//! URL, so a deployed program is never sent them by mistake.
+
+use crate::{load_payer, Error};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use movie_review_client::{mint_test_tokens, set_test_profile, with_compute_unit_limit};
+use solana_clap_utils::{
+    input_parsers::{pubkey_of, value_of},
+    input_validators::{is_parsable, is_pubkey, is_valid_signer},
+};
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+
+pub const SUBCOMMANDS: &[&str] = &["mint-test-tokens", "set-test-profile"];
+
+pub fn flag<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("localnet")
+        .long("localnet")
+        .global(true)
+        .help(
+            "Target a localnet program built with `localnet`, enabling the faucet and \
+             fixture subcommands",
+        )
+}
+
+pub fn subcommands<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+    app.subcommand(
+        SubCommand::with_name("mint-test-tokens")
+            .about("Mint reward tokens to any account [--localnet only]")
+            .arg(
+                Arg::with_name("recipient")
+                    .long("recipient")
+                    .value_name("PUBKEY")
+                    .takes_value(true)
+                    .required(true)
+                    .validator(is_pubkey),
+            )
+            .arg(
+                Arg::with_name("amount")
+                    .long("amount")
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .validator(is_parsable::<u64>),
+            )
+            .arg(keypair_arg()),
+    )
+    .subcommand(
+        SubCommand::with_name("set-test-profile")
+            .about("Create or overwrite a reviewer profile's counters [--localnet only]")
+            .arg(
+                Arg::with_name("owner")
+                    .long("owner")
+                    .value_name("PUBKEY")
+                    .takes_value(true)
+                    .required(true)
+                    .validator(is_pubkey),
+            )
+            .arg(count_arg("comments", "comments"))
+            .arg(count_arg("reviews", "reviews"))
+            .arg(count_arg("pending_rewards", "pending-rewards"))
+            .arg(keypair_arg()),
+    )
+}
+
+fn keypair_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("keypair")
+        .long("keypair")
+        .value_name("KEYPAIR")
+        .takes_value(true)
+        .validator(is_valid_signer)
+        .help("Payer [default: the Solana CLI config keypair]")
+}
+
+fn count_arg<'a, 'b>(name: &'a str, long: &'a str) -> Arg<'a, 'b> {
+    Arg::with_name(name)
+        .long(long)
+        .value_name("COUNT")
+        .takes_value(true)
+        .default_value("0")
+        .validator(is_parsable::<u64>)
+}
+
+/// Fails unless `--localnet` was passed and `url` points at this machine.
+pub fn require_localnet(matches: &ArgMatches, url: &str) -> Result<(), Error> {
+    if !matches.is_present("localnet") {
+        return Err("this subcommand requires --localnet".into());
+    }
+    if !(url.contains("localhost") || url.contains("127.0.0.1")) {
+        return Err(format!("--localnet requires a localhost URL, not {}", url).into());
+    }
+    Ok(())
+}
+
+pub fn run(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    subcommand: &str,
+    matches: &ArgMatches,
+) -> Result<(), Error> {
+    let payer = load_payer(matches)?;
+    let payer_pubkey = payer.pubkey();
+
+    let instruction = match subcommand {
+        "mint-test-tokens" => mint_test_tokens(
+            program_id,
+            &payer_pubkey,
+            &pubkey_of(matches, "recipient").unwrap(),
+            value_of(matches, "amount").unwrap(),
+        ),
+        "set-test-profile" => set_test_profile(
+            program_id,
+            &payer_pubkey,
+            &pubkey_of(matches, "owner").unwrap(),
+            value_of(matches, "comments").unwrap(),
+            value_of(matches, "reviews").unwrap(),
+            value_of(matches, "pending_rewards").unwrap(),
+        ),
+        _ => unreachable!(),
+    };
+
+    let transaction = Transaction::new_signed_with_payer(
+        &with_compute_unit_limit(program_id, &[instruction]),
+        Some(&payer_pubkey),
+        &[payer.as_ref()],
+        rpc_client.get_latest_blockhash()?,
+    );
+    let signature = rpc_client.send_and_confirm_transaction(&transaction)?;
+    println!("{}", signature);
+    Ok(())
+}
@@ -0,0 +1,421 @@
+use clap::{crate_description, crate_name, crate_version, App, AppSettings, Arg, SubCommand};
+use movie_review_client::{
+    accounts::parse_any,
+    client::MovieReviewClient,
+    decode_transaction, find_airdrop_address, find_config_address, find_handle_address,
+    find_mint_authority_address, find_profile_address, find_program_data_address,
+    find_review_address, find_token_mint_address, find_treasury_address, initialize_program,
+    initialize_reviewer_profile, mention,
+    state::{ConfigState, HandleState},
+    with_compute_unit_limit,
+};
+use serde_json::json;
+use solana_clap_utils::{
+    input_parsers::pubkey_of,
+    input_validators::{
+        is_pubkey, is_url_or_moniker, is_valid_signer, normalize_to_url_if_moniker,
+    },
+    keypair::signer_from_path,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_program::{
+    borsh::try_from_slice_unchecked, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+};
+use solana_sdk::{signature::Signer, transaction::Transaction};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use std::process::exit;
+
+#[cfg(feature = "localnet")]
+mod localnet;
+
+type Error = Box<dyn std::error::Error>;
+
+fn main() {
+    let app = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .short("u")
+                .long("url")
+                .value_name("URL_OR_MONIKER")
+                .takes_value(true)
+                .global(true)
+                .default_value("localhost")
+                .validator(is_url_or_moniker)
+                .help("JSON RPC URL for the cluster [mainnet-beta, testnet, devnet, localhost]"),
+        )
+        .arg(
+            Arg::with_name("program_id")
+                .long("program-id")
+                .value_name("PROGRAM_ID")
+                .takes_value(true)
+                .global(true)
+                .validator(is_pubkey)
+                .help("Address of the deployed movie review program"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .global(true)
+                .possible_values(&["display", "json"])
+                .default_value("display")
+                .help("Return information in the specified output format"),
+        )
+        .subcommand(
+            SubCommand::with_name("review-address")
+                .about("Derive the review account address for a reviewer and title")
+                .arg(
+                    Arg::with_name("reviewer")
+                        .long("reviewer")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_pubkey),
+                )
+                .arg(
+                    Arg::with_name("title")
+                        .long("title")
+                        .value_name("TITLE")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-review")
+                .about("Display a review account")
+                .arg(
+                    Arg::with_name("address")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey),
+                ),
+        )
+        .subcommand(SubCommand::with_name("show-config").about("Display the program config"))
+        .subcommand(
+            SubCommand::with_name("resolve-handle")
+                .about("Look up the owner of a registered handle")
+                .arg(Arg::with_name("handle").value_name("HANDLE").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect")
+                .about("Decode the movie review instructions in a transaction before signing it")
+                .arg(
+                    Arg::with_name("transaction")
+                        .value_name("BASE64_TRANSACTION")
+                        .required(true)
+                        .help("Serialized transaction, signed or not, encoded as base64"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect-account")
+                .about("Decode a program account, whatever its kind")
+                .arg(
+                    Arg::with_name("address")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("bootstrap")
+                .about(
+                    "Fund the payer, initialize the program and create the payer's profile and \
+                     token account, then print the derived addresses as JSON",
+                )
+                .arg(
+                    Arg::with_name("keypair")
+                        .long("keypair")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .validator(is_valid_signer)
+                        .help(
+                            "Payer and program upgrade authority [default: the Solana CLI \
+                             config keypair]",
+                        ),
+                ),
+        );
+    #[cfg(feature = "localnet")]
+    let app = localnet::subcommands(app.arg(localnet::flag()));
+    let matches = app.get_matches();
+
+    let program_id = pubkey_of(&matches, "program_id").unwrap_or_else(|| {
+        eprintln!("error: --program-id is required");
+        exit(1);
+    });
+    let url = normalize_to_url_if_moniker(matches.value_of("json_rpc_url").unwrap());
+    let client = MovieReviewClient::new(RpcClient::new(url.clone()), program_id);
+    let rpc_client = client.rpc_client();
+
+    let json = matches.value_of("output") == Some("json");
+
+    let result = match matches.subcommand() {
+        ("review-address", Some(arg_matches)) => {
+            let reviewer = pubkey_of(arg_matches, "reviewer").unwrap();
+            let title = arg_matches.value_of("title").unwrap();
+            let (address, _bump_seed) = find_review_address(&program_id, &reviewer, title);
+            println!("{}", address);
+            Ok(())
+        }
+        ("show-review", Some(arg_matches)) => {
+            let address = pubkey_of(arg_matches, "address").unwrap();
+            show_review(&client, &address, json)
+        }
+        ("show-config", _) => show_config(rpc_client, &program_id, json),
+        ("resolve-handle", Some(arg_matches)) => {
+            let handle = arg_matches.value_of("handle").unwrap();
+            resolve_handle(rpc_client, &program_id, handle)
+        }
+        ("inspect", Some(arg_matches)) => {
+            let transaction = arg_matches.value_of("transaction").unwrap();
+            inspect(&program_id, transaction, json)
+        }
+        ("inspect-account", Some(arg_matches)) => {
+            let address = pubkey_of(arg_matches, "address").unwrap();
+            inspect_account(rpc_client, &program_id, &address, json)
+        }
+        ("bootstrap", Some(arg_matches)) => bootstrap(rpc_client, &program_id, arg_matches),
+        #[cfg(feature = "localnet")]
+        (subcommand, Some(arg_matches)) if localnet::SUBCOMMANDS.contains(&subcommand) => {
+            localnet::require_localnet(arg_matches, &url)
+                .and_then(|()| localnet::run(rpc_client, &program_id, subcommand, arg_matches))
+        }
+        _ => unreachable!(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        exit(1);
+    }
+}
+
+fn account_exists(rpc_client: &RpcClient, address: &Pubkey) -> Result<bool, Error> {
+    Ok(rpc_client
+        .get_account_with_commitment(address, rpc_client.commitment())?
+        .value
+        .is_some())
+}
+
+/// The signer at `--keypair`, or the Solana CLI config keypair.
+fn load_payer(matches: &clap::ArgMatches) -> Result<Box<dyn Signer>, Error> {
+    let keypair_path = match matches.value_of("keypair") {
+        Some(path) => path.to_string(),
+        None => match solana_cli_config::CONFIG_FILE.as_ref() {
+            Some(config_file) => {
+                solana_cli_config::Config::load(config_file)
+                    .unwrap_or_default()
+                    .keypair_path
+            }
+            None => solana_cli_config::Config::default().keypair_path,
+        },
+    };
+    signer_from_path(matches, &keypair_path, "keypair", &mut None)
+}
+
+/// Runs every step a fresh deployment needs before its first review. Steps
+/// that are already done are skipped, so it is safe to re-run.
+fn bootstrap(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    matches: &clap::ArgMatches,
+) -> Result<(), Error> {
+    let payer = load_payer(matches)?;
+    let payer_pubkey = payer.pubkey();
+
+    if rpc_client.get_balance(&payer_pubkey)? < LAMPORTS_PER_SOL {
+        eprintln!("Requesting an airdrop for {}", payer_pubkey);
+        let signature = rpc_client.request_airdrop(&payer_pubkey, LAMPORTS_PER_SOL)?;
+        rpc_client.poll_for_signature(&signature)?;
+    }
+
+    let (config, _bump_seed) = find_config_address(program_id);
+    let (mint, _bump_seed) = find_token_mint_address(program_id);
+    let (profile, _bump_seed) = find_profile_address(program_id, &payer_pubkey);
+    let token_account = get_associated_token_address(&payer_pubkey, &mint);
+
+    let mut instructions = Vec::new();
+    if !account_exists(rpc_client, &config)? {
+        instructions.push(initialize_program(
+            program_id,
+            &payer_pubkey,
+            0,
+            0,
+            &Pubkey::default(),
+            0,
+        ));
+    }
+    if !account_exists(rpc_client, &profile)? {
+        instructions.push(initialize_reviewer_profile(program_id, &payer_pubkey));
+    }
+    instructions.push(create_associated_token_account_idempotent(
+        &payer_pubkey,
+        &payer_pubkey,
+        &mint,
+        &spl_token::ID,
+    ));
+
+    let transaction = Transaction::new_signed_with_payer(
+        &with_compute_unit_limit(program_id, &instructions),
+        Some(&payer_pubkey),
+        &[payer.as_ref()],
+        rpc_client.get_latest_blockhash()?,
+    );
+    let signature = rpc_client.send_and_confirm_transaction(&transaction)?;
+    eprintln!("Bootstrapped in {}", signature);
+
+    let addresses = json!({
+        "program_id": program_id.to_string(),
+        "program_data": find_program_data_address(program_id).0.to_string(),
+        "payer": payer_pubkey.to_string(),
+        "config": config.to_string(),
+        "treasury": find_treasury_address(program_id).0.to_string(),
+        "token_mint": mint.to_string(),
+        "mint_authority": find_mint_authority_address(program_id).0.to_string(),
+        "airdrop": find_airdrop_address(program_id).0.to_string(),
+        "profile": profile.to_string(),
+        "token_account": token_account.to_string(),
+    });
+    println!("{}", serde_json::to_string_pretty(&addresses)?);
+    Ok(())
+}
+
+fn show_review(client: &MovieReviewClient, address: &Pubkey, json: bool) -> Result<(), Error> {
+    let review = client.get_review(address)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&review)?);
+        return Ok(());
+    }
+
+    println!("Address: {}", address);
+    println!("Reviewer: {}", review.reviewer);
+    println!("Title: {}", review.title);
+    println!(
+        "Rating: {}.{}/5",
+        review.rating_tenths() / 10,
+        review.rating_tenths() % 10
+    );
+    println!("Description: {}", review.description);
+    println!("Visibility: {:?}", review.visibility);
+    println!("Status: {:?}", review.status);
+    Ok(())
+}
+
+fn resolve_handle(rpc_client: &RpcClient, program_id: &Pubkey, handle: &str) -> Result<(), Error> {
+    let handle = mention::normalize_handle(handle.trim_start_matches('@'))?;
+    let (address, _bump_seed) = find_handle_address(program_id, &handle);
+    let data = rpc_client.get_account_data(&address)?;
+    let handle_data = try_from_slice_unchecked::<HandleState>(&data)?;
+
+    println!("Handle: @{}", handle_data.handle);
+    println!("Owner: {}", handle_data.owner);
+    Ok(())
+}
+
+fn show_config(rpc_client: &RpcClient, program_id: &Pubkey, json: bool) -> Result<(), Error> {
+    let (address, _bump_seed) = find_config_address(program_id);
+    let data = rpc_client.get_account_data(&address)?;
+    let config = try_from_slice_unchecked::<ConfigState>(&data)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(());
+    }
+
+    println!("Address: {}", address);
+    println!("{}", config);
+    Ok(())
+}
+
+fn inspect_account(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    address: &Pubkey,
+    json: bool,
+) -> Result<(), Error> {
+    let account = rpc_client.get_account(address)?;
+    if account.owner != *program_id {
+        return Err(format!("{} is not owned by program {}", address, program_id).into());
+    }
+    let decoded = parse_any(&account.data)?;
+
+    if json {
+        let decoded = json!({
+            "address": address.to_string(),
+            "kind": format!("{:?}", decoded.kind()),
+            "account": format!("{:?}", decoded),
+        });
+        println!("{}", serde_json::to_string_pretty(&decoded)?);
+        return Ok(());
+    }
+
+    println!("Address: {}", address);
+    println!("Kind: {:?}", decoded.kind());
+    println!("{:#?}", decoded);
+    Ok(())
+}
+
+fn inspect(program_id: &Pubkey, encoded: &str, json: bool) -> Result<(), Error> {
+    let transaction: Transaction = bincode::deserialize(&base64::decode(encoded.trim())?)?;
+    let decoded = decode_transaction(program_id, &transaction.message);
+
+    if json {
+        let instructions: Vec<_> = decoded
+            .iter()
+            .map(|decoded| {
+                let accounts: Vec<_> = decoded
+                    .accounts
+                    .iter()
+                    .map(|account| {
+                        json!({
+                            "pubkey": account.pubkey.to_string(),
+                            "role": account.role,
+                            "signer": account.is_signer,
+                            "writable": account.is_writable,
+                        })
+                    })
+                    .collect();
+                json!({
+                    "index": decoded.index,
+                    "version": decoded.version,
+                    "instruction": decoded.instruction.as_ref().ok().map(|ix| format!("{:?}", ix)),
+                    "error": decoded.instruction.as_ref().err().map(|err| err.to_string()),
+                    "accounts": accounts,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&instructions)?);
+        return Ok(());
+    }
+
+    if decoded.is_empty() {
+        println!("No instructions for program {}", program_id);
+    }
+    for decoded in decoded {
+        match &decoded.instruction {
+            Ok(instruction) => println!("Instruction {}: {:?}", decoded.index, instruction),
+            Err(err) => println!("Instruction {}: invalid ({})", decoded.index, err),
+        }
+        println!("  Version: {}", decoded.version);
+        for (position, account) in decoded.accounts.iter().enumerate() {
+            let role = match account.role {
+                Some(role) => role.to_string(),
+                None => format!("account {}", position),
+            };
+            let mut flags = Vec::new();
+            if account.is_signer {
+                flags.push("signer");
+            }
+            if account.is_writable {
+                flags.push("writable");
+            }
+            println!("  {}: {} [{}]", role, account.pubkey, flags.join(", "));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,175 @@
+//! Blocking RPC client for scripts and bots that review movies, so they need
+//! not derive review addresses or encode instructions themselves. Enabled by
+//! the `client` feature.
+
+// Methods return `RpcClient`'s own error type, large as it is.
+#![allow(clippy::result_large_err)]
+
+use crate::{
+    add_movie_review, find_config_address, find_review_address,
+    flows::review_fee_accounts,
+    state::{ConfigState, MovieAccountState},
+    update_movie_review, with_compute_unit_limit,
+};
+use borsh::BorshSerialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_program::{
+    borsh::try_from_slice_unchecked, instruction::Instruction, program_pack::IsInitialized,
+    pubkey::Pubkey,
+};
+use solana_sdk::{
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+
+pub struct MovieReviewClient {
+    rpc_client: RpcClient,
+    program_id: Pubkey,
+}
+
+impl MovieReviewClient {
+    pub fn new(rpc_client: RpcClient, program_id: Pubkey) -> Self {
+        Self {
+            rpc_client,
+            program_id,
+        }
+    }
+
+    pub fn rpc_client(&self) -> &RpcClient {
+        &self.rpc_client
+    }
+
+    pub fn program_id(&self) -> &Pubkey {
+        &self.program_id
+    }
+
+    pub fn review_address(&self, reviewer: &Pubkey, title: &str) -> Pubkey {
+        find_review_address(&self.program_id, reviewer, title).0
+    }
+
+    /// Adds `reviewer`'s review of `title`, paying whatever fee the config
+    /// charges. Once the config pays rewards the reviewer needs a profile
+    /// first; `flows::first_review` creates one.
+    pub fn add_review(
+        &self,
+        reviewer: &dyn Signer,
+        title: &str,
+        rating: u8,
+        description: &str,
+    ) -> ClientResult<Signature> {
+        let config = self.get_config()?;
+        self.send(
+            &[add_movie_review(
+                &self.program_id,
+                &reviewer.pubkey(),
+                title,
+                rating,
+                description,
+                review_fee_accounts(config.as_ref()),
+            )],
+            reviewer,
+        )
+    }
+
+    pub fn update_review(
+        &self,
+        reviewer: &dyn Signer,
+        title: &str,
+        rating: u8,
+        description: &str,
+    ) -> ClientResult<Signature> {
+        self.send(
+            &[update_movie_review(
+                &self.program_id,
+                &reviewer.pubkey(),
+                title,
+                rating,
+                description,
+            )],
+            reviewer,
+        )
+    }
+
+    /// The review at `address`, of either layout version.
+    pub fn get_review(&self, address: &Pubkey) -> ClientResult<MovieAccountState> {
+        let data = self.rpc_client.get_account_data(address)?;
+        Ok(try_from_slice_unchecked::<MovieAccountState>(&data)?)
+    }
+
+    /// Every review by `reviewer` and its address, found with one program
+    /// account query per layout version since version 1 reviews hold their
+    /// reviewer one byte earlier.
+    pub fn get_reviews_by_reviewer(
+        &self,
+        reviewer: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, MovieAccountState)>> {
+        let discriminator = MovieAccountState::DISCRIMINATOR.to_string().try_to_vec()?;
+        let mut reviews = Vec::new();
+        for version_byte_len in [0, 1] {
+            // The discriminator, the version from version 2, `is_initialized`
+            let reviewer_offset = discriminator.len() + version_byte_len + 1;
+            let accounts = self.rpc_client.get_program_accounts_with_config(
+                &self.program_id,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, discriminator.clone())),
+                        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                            reviewer_offset,
+                            reviewer.to_bytes().to_vec(),
+                        )),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                },
+            )?;
+            for (address, account) in accounts {
+                let Ok(review) = try_from_slice_unchecked::<MovieAccountState>(&account.data)
+                else {
+                    continue;
+                };
+                // Rules out the other version's accounts matching by chance.
+                if review.is_initialized()
+                    && review.reviewer == *reviewer
+                    && !reviews.iter().any(|(found, _)| *found == address)
+                {
+                    reviews.push((address, review));
+                }
+            }
+        }
+        Ok(reviews)
+    }
+
+    /// The program's config, `None` before `InitializeProgram`.
+    fn get_config(&self) -> ClientResult<Option<ConfigState>> {
+        let (config, _) = find_config_address(&self.program_id);
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(&config, self.rpc_client.commitment())?
+            .value;
+        match account {
+            Some(account) if account.owner == self.program_id => Ok(Some(
+                try_from_slice_unchecked::<ConfigState>(&account.data)?,
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    fn send(&self, instructions: &[Instruction], payer: &dyn Signer) -> ClientResult<Signature> {
+        let transaction = Transaction::new_signed_with_payer(
+            &with_compute_unit_limit(&self.program_id, instructions),
+            Some(&payer.pubkey()),
+            &[payer],
+            self.rpc_client.get_latest_blockhash()?,
+        );
+        self.rpc_client.send_and_confirm_transaction(&transaction)
+    }
+}
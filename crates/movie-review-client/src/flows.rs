@@ -0,0 +1,107 @@
+//! Instruction sequences for common user journeys, in the order the program
+//! needs them. Flows take `exists`, which reports whether an account is
+//! already on chain, usually answered from an RPC lookup, and skip the steps
+//! it shows are done. Each sequence starts with a compute unit limit.
+
+use crate::{
+    add_comment, add_movie_review, find_inbox_address, find_profile_address,
+    find_token_mint_address, initialize_reviewer_profile_idempotent, state::ConfigState,
+    tip_reviewer, with_compute_unit_limit, ReviewFeeAccounts,
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+/// The optional `add_movie_review` accounts `config` requires, `None` before
+/// the config is initialized.
+pub fn review_fee_accounts(config: Option<&ConfigState>) -> ReviewFeeAccounts {
+    let Some(config) = config else {
+        return ReviewFeeAccounts::default();
+    };
+    let charges_fee = config.review_fee_lamports > 0 || config.review_fee_usd_cents > 0;
+
+    ReviewFeeAccounts {
+        treasury: charges_fee.then_some(config.treasury),
+        price_feed: (config.review_fee_usd_cents > 0).then_some(config.price_feed),
+        watch_attestation: config.require_watch_attestation,
+        reward: config.reward_base_amount > 0,
+        referrer: None,
+        watchlist_page: None,
+    }
+}
+
+/// A reviewer's first review: creates their profile and, once the reward mint
+/// exists, the token account rewards are minted to, then adds the review. A
+/// `referrer` without a profile is left out. The review's comment counter is
+/// created by its first comment.
+#[allow(clippy::too_many_arguments)]
+pub fn first_review(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    config: Option<&ConfigState>,
+    referrer: Option<Pubkey>,
+    exists: impl Fn(&Pubkey) -> bool,
+) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+
+    if !exists(&find_profile_address(program_id, reviewer).0) {
+        instructions.push(initialize_reviewer_profile_idempotent(program_id, reviewer));
+    }
+
+    let (mint, _) = find_token_mint_address(program_id);
+    if exists(&mint) {
+        instructions.push(create_associated_token_account_idempotent(
+            reviewer,
+            reviewer,
+            &mint,
+            &spl_token::ID,
+        ));
+    }
+
+    let mut fee_accounts = review_fee_accounts(config);
+    fee_accounts.referrer =
+        referrer.filter(|referrer| exists(&find_profile_address(program_id, referrer).0));
+    instructions.push(add_movie_review(
+        program_id,
+        reviewer,
+        title,
+        rating,
+        description,
+        fee_accounts,
+    ));
+
+    with_compute_unit_limit(program_id, &instructions)
+}
+
+/// Tips `reviewer` `lamports` and comments on their review at `review`, which
+/// must currently have `comment_count` comments. The reviewer is notified if
+/// they have an inbox.
+#[allow(clippy::too_many_arguments)]
+pub fn tip_and_comment(
+    program_id: &Pubkey,
+    commenter: &Pubkey,
+    reviewer: &Pubkey,
+    review: &Pubkey,
+    comment_count: u64,
+    lamports: u64,
+    comment: &str,
+    exists: impl Fn(&Pubkey) -> bool,
+) -> Vec<Instruction> {
+    let (inbox, _) = find_inbox_address(program_id, reviewer);
+
+    let instructions = [
+        tip_reviewer(program_id, commenter, review, reviewer, lamports),
+        add_comment(
+            program_id,
+            commenter,
+            review,
+            comment_count,
+            comment,
+            exists(&inbox).then_some(inbox),
+        ),
+    ];
+
+    with_compute_unit_limit(program_id, &instructions)
+}
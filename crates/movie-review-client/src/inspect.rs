@@ -0,0 +1,324 @@
+//! Decodes the movie review instructions in a transaction message, so users
+//! can check what a transaction does before signing it, and the events in its
+//! logs once it has run.
+
+use borsh::BorshDeserialize;
+use movie_review::{
+    events::Event,
+    instruction::{split_instruction_version, MovieInstruction},
+    state::ReviewRecord,
+};
+use solana_program::{
+    message::Message, program::MAX_RETURN_DATA, program_error::ProgramError, pubkey::Pubkey,
+};
+
+/// An account passed to a decoded instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedAccount {
+    pub pubkey: Pubkey,
+    /// What the program uses the account for, by its position. `None` for
+    /// accounts past the ones the instruction always takes, such as optional
+    /// fee accounts, mentioned users' inboxes or accounts to purge.
+    pub role: Option<&'static str>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DecodedMovieInstruction {
+    /// Position of the instruction in the message.
+    pub index: usize,
+    /// Version the instruction was tagged with, 0 if unversioned.
+    pub version: u16,
+    /// The error the program would reject the instruction data with, if it
+    /// does not decode.
+    pub instruction: Result<MovieInstruction, ProgramError>,
+    pub accounts: Vec<DecodedAccount>,
+}
+
+/// Decodes every instruction in `message` that targets `program_id`, in
+/// order. Other programs' instructions are skipped.
+pub fn decode_transaction(program_id: &Pubkey, message: &Message) -> Vec<DecodedMovieInstruction> {
+    message
+        .instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, compiled)| {
+            message.account_keys.get(compiled.program_id_index as usize) == Some(program_id)
+        })
+        .map(|(index, compiled)| {
+            let (version, instruction) = match split_instruction_version(&compiled.data) {
+                Ok((version, data)) => (version, MovieInstruction::unpack(data)),
+                Err(err) => (0, Err(err)),
+            };
+            let roles = instruction.as_ref().map(account_roles).unwrap_or(&[]);
+            let accounts = compiled
+                .accounts
+                .iter()
+                .enumerate()
+                .filter_map(|(position, &key_index)| {
+                    let key_index = key_index as usize;
+                    Some(DecodedAccount {
+                        pubkey: *message.account_keys.get(key_index)?,
+                        role: roles.get(position).copied(),
+                        is_signer: message.is_signer(key_index),
+                        is_writable: message.is_writable(key_index),
+                    })
+                })
+                .collect();
+
+            DecodedMovieInstruction {
+                index,
+                version,
+                instruction,
+                accounts,
+            }
+        })
+        .collect()
+}
+
+/// Decodes the events `program_id` logged in a transaction's `logs`, in order.
+/// Data logged by other programs, including ones it invokes, is skipped.
+pub fn decode_events(program_id: &Pubkey, logs: &[String]) -> Vec<Event> {
+    let program = program_id.to_string();
+    let mut invoked = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if invoked.last() == Some(&program.as_str()) {
+                events.extend(
+                    data.split(' ')
+                        .filter_map(|field| base64::decode(field).ok())
+                        .filter_map(|field| Event::decode(&field)),
+                );
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            // Skips `Program log:` lines and the like, which name no program.
+            let mut words = rest.split(' ');
+            let (Some(id), Some(status)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if id.parse::<Pubkey>().is_err() {
+                continue;
+            }
+            match status {
+                "invoke" => invoked.push(id),
+                "success" | "failed:" if invoked.last() == Some(&id) => {
+                    invoked.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// Decodes the return data of a simulated `ExportMyReviews`. The runtime
+/// trims trailing zero bytes off return data, so they are put back first.
+/// `None` if it is not a page of records.
+pub fn decode_review_records(return_data: &[u8]) -> Option<Vec<ReviewRecord>> {
+    let mut data = return_data.to_vec();
+    data.resize(data.len().max(4), 0);
+    let count = u32::from_le_bytes(data[..4].try_into().ok()?) as usize;
+    let len = count.checked_mul(ReviewRecord::SIZE)?.checked_add(4)?;
+    if len < data.len() || len > MAX_RETURN_DATA {
+        return None;
+    }
+    data.resize(len, 0);
+    Vec::try_from_slice(&data).ok()
+}
+
+/// Names of the accounts `instruction` always takes, in the order the
+/// instruction builders pass them.
+pub fn account_roles(instruction: &MovieInstruction) -> &'static [&'static str] {
+    use MovieInstruction::*;
+
+    match instruction {
+        AddMovieReview { .. }
+        | AddDraftReview { .. }
+        | AddEntityReview { .. }
+        | AddMovieReviewIdempotent { .. }
+        | UpdateMovieReview { .. } => {
+            &["reviewer", "review", "system_program", "config", "summary"]
+        }
+        AddComment { .. } => &[
+            "commenter",
+            "review",
+            "comment_counter",
+            "comment",
+            "commenter_profile",
+            "system_program",
+            "config",
+        ],
+        InitializeTokenMint => &[
+            "payer",
+            "token_mint",
+            "mint_authority",
+            "system_program",
+            "token_program",
+            "rent_sysvar",
+        ],
+        SetAirdropRoot { .. } => &[
+            "authority",
+            "airdrop",
+            "airdrop_claims",
+            "system_program",
+            "config",
+        ],
+        ClaimAirdrop { .. } => &[
+            "claimant",
+            "airdrop",
+            "airdrop_claims",
+            "config",
+            "system_program",
+            "vesting",
+            "token_mint",
+            "mint_authority",
+            "token_account",
+            "token_program",
+            "associated_token_program",
+        ],
+        InitializeConfig { .. } | InitializeConfigIdempotent { .. } => {
+            &["admin", "config", "system_program", "program_data"]
+        }
+        PurgeExpired => &["caller", "airdrop", "airdrop_authority"],
+        DeleteMovieReview => &["reviewer", "review", "summary"],
+        SetVisibility { .. } => &["reviewer", "review", "summary"],
+        MarkNotificationsRead { .. } => &["owner", "inbox", "system_program"],
+        Subscribe { .. } => &[
+            "subscriber",
+            "reviewer",
+            "subscription",
+            "system_program",
+            "config",
+            "treasury",
+        ],
+        SetPremiumContent { .. } => &["reviewer", "review", "premium_content", "system_program"],
+        GrantAccessKeyHint { .. } => &[
+            "reviewer",
+            "review",
+            "premium_content",
+            "subscriber",
+            "subscription",
+            "access_key_hint",
+            "system_program",
+        ],
+        GrantAccess { .. } => &["reviewer", "review", "acl", "system_program"],
+        RevokeAccess { .. } => &["reviewer", "review", "acl"],
+        PublishReview => &[
+            "reviewer",
+            "review",
+            "summary",
+            "system_program",
+            "config",
+            "profile",
+        ],
+        SchedulePublish { .. } | SetReviewExpiry { .. } => &["reviewer", "review"],
+        AttestWatched { .. } | AttestEntityWatched { .. } => {
+            &["viewer", "watch_attestation", "system_program"]
+        }
+        SetWatchRequirement { .. }
+        | SetCommentLimit { .. }
+        | SetCommentEditWindow { .. }
+        | SetRewardSchedule { .. }
+        | SetVestingPolicy { .. }
+        | SetReferralBonus { .. }
+        | SetEscrowPolicy { .. }
+        | SetDisabledFeatures { .. }
+        | SetReviewLengthLimits { .. }
+        | SetPaused { .. }
+        | SetSnapshotInterval { .. }
+        | SetCommentReward { .. } => &["admin", "config"],
+        SetCommentCapOverride { .. } => &[
+            "admin",
+            "config",
+            "review",
+            "comment_counter",
+            "system_program",
+        ],
+        UpdateComment { .. } => &["commenter", "comment", "system_program", "config"],
+        DeleteComment => &["commenter", "comment"],
+        RegisterHandle { .. } => &["owner", "profile", "handle", "system_program"],
+        ReleaseHandle => &["owner", "profile", "handle"],
+        InitializeProgram { .. } => &[
+            "admin",
+            "config",
+            "treasury",
+            "token_mint",
+            "mint_authority",
+            "system_program",
+            "token_program",
+            "rent_sysvar",
+            "program_data",
+        ],
+        ClaimRewards => &[
+            "claimer",
+            "profile",
+            "system_program",
+            "token_mint",
+            "mint_authority",
+            "token_account",
+            "token_program",
+            "associated_token_program",
+            "config",
+        ],
+        ClaimVested => &[
+            "beneficiary",
+            "vesting",
+            "system_program",
+            "token_mint",
+            "mint_authority",
+            "token_account",
+            "token_program",
+            "associated_token_program",
+            "config",
+        ],
+        #[cfg(feature = "test-utils")]
+        MintTestTokens { .. } => &[
+            "payer",
+            "recipient",
+            "system_program",
+            "token_mint",
+            "mint_authority",
+            "token_account",
+            "token_program",
+            "associated_token_program",
+        ],
+        #[cfg(feature = "test-utils")]
+        SetTestProfile { .. } => &["payer", "profile", "system_program"],
+        InitializeReviewerProfile | InitializeReviewerProfileIdempotent => {
+            &["owner", "profile", "system_program"]
+        }
+        SyncProgramVersion => &["payer", "version", "system_program"],
+        MigrateLegacyReview => &["review", "reviewer", "system_program"],
+        MigrateReview => &["review", "reviewer", "system_program", "summary"],
+        SnapshotAggregate => &["payer", "config", "summary", "snapshot", "system_program"],
+        ReclaimOrphans => &["admin", "config", "treasury", "review"],
+        FundEscrow { .. } => &["funder", "recipient", "escrow", "config", "system_program"],
+        DisputeEscrow => &["funder", "escrow", "config"],
+        ResolveEscrow { .. } => &["arbiter", "config", "escrow", "destination"],
+        SettleEscrow => &["escrow", "destination"],
+        CloseEscrow => &["funder", "escrow"],
+        ExpireReview { .. } => &["review", "reviewer", "summary"],
+        ArchiveReview => &["admin", "config", "review", "summary"],
+        CreateVersusPoll { .. } => &["creator", "poll", "system_program", "config"],
+        VoteVersus { .. } => &["voter", "poll", "receipt", "system_program", "config"],
+        FinalizeVersusPoll => &["poll", "payer", "summary_a", "summary_b", "system_program"],
+        VoteOnReview { .. } => &["voter", "review", "vote", "system_program", "config"],
+        TipReviewer { .. } => &["tipper", "review", "reviewer", "system_program", "config"],
+        TipReviewerToken { .. } => &[
+            "tipper",
+            "review",
+            "token_mint",
+            "tipper_token_account",
+            "reviewer_token_account",
+            "token_program",
+            "config",
+        ],
+        AddToWatchlist { .. } => &["owner", "watchlist", "system_program"],
+        RemoveFromWatchlist { .. } => &["owner", "watchlist"],
+        IndexReviews { .. } => &["reviewer", "review_index", "system_program"],
+        ExportMyReviews { .. } => &["reviewer", "review_index"],
+    }
+}
@@ -0,0 +1,1939 @@
+//! Off-chain helpers for building movie review program instructions and
+//! deriving its account addresses.
+
+pub use movie_review::{
+    accounts, compute_units, error, events, instruction, mention, merkle, state,
+};
+
+#[cfg(feature = "client")]
+pub mod client;
+pub mod flows;
+pub mod inspect;
+
+pub use inspect::{
+    decode_events, decode_review_records, decode_transaction, DecodedAccount,
+    DecodedMovieInstruction,
+};
+
+use borsh::BorshSerialize;
+use movie_review::{
+    instruction::{
+        split_instruction_version, MovieInstruction, INSTRUCTION_VERSION, VERSIONED_INSTRUCTION_TAG,
+    },
+    mention::Mention,
+    state::{EncryptionScheme, EntityKind, Feature, RewardHalvingUnit, VersusSide, Visibility},
+};
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use spl_associated_token_account::get_associated_token_address;
+
+pub fn find_review_address(program_id: &Pubkey, reviewer: &Pubkey, title: &str) -> (Pubkey, u8) {
+    find_entity_review_address(program_id, EntityKind::Movie, reviewer, title)
+}
+
+pub fn find_entity_review_address(
+    program_id: &Pubkey,
+    kind: EntityKind,
+    reviewer: &Pubkey,
+    title: &str,
+) -> (Pubkey, u8) {
+    state::derive_review_pda(program_id, kind, reviewer, title)
+}
+
+pub fn find_summary_address(program_id: &Pubkey, kind: EntityKind, title: &str) -> (Pubkey, u8) {
+    state::derive_summary_pda(program_id, kind, title)
+}
+
+/// The snapshot of `summary` for `epoch`, the current one being
+/// `ConfigState::snapshot_epoch`.
+pub fn find_snapshot_address(program_id: &Pubkey, summary: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    state::derive_snapshot_pda(program_id, summary, epoch)
+}
+
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], program_id)
+}
+
+pub fn find_token_mint_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"token_mint"], program_id)
+}
+
+pub fn find_mint_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"token_auth"], program_id)
+}
+
+pub fn find_airdrop_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"airdrop"], program_id)
+}
+
+pub fn find_airdrop_claims_address(program_id: &Pubkey, round: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"airdrop_claims", &round.to_le_bytes()], program_id)
+}
+
+/// Vesting account for the airdrop grant at `index` of a claims bitmap.
+pub fn find_vesting_address(program_id: &Pubkey, claims: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"vesting", claims.as_ref(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_program_data_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+pub fn find_treasury_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"treasury"], program_id)
+}
+
+pub fn find_inbox_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"inbox", owner.as_ref()], program_id)
+}
+
+pub fn find_version_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"version"], program_id)
+}
+
+pub fn find_profile_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"profile", owner.as_ref()], program_id)
+}
+
+/// `handle` must already be normalized, see `mention::normalize_handle`.
+pub fn find_handle_address(program_id: &Pubkey, handle: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"handle", handle.as_bytes()], program_id)
+}
+
+pub fn find_subscription_address(
+    program_id: &Pubkey,
+    subscriber: &Pubkey,
+    reviewer: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"sub", subscriber.as_ref(), reviewer.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_premium_content_address(program_id: &Pubkey, review: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"premium", review.as_ref()], program_id)
+}
+
+pub fn find_access_key_hint_address(
+    program_id: &Pubkey,
+    review: &Pubkey,
+    subscriber: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"key_hint", review.as_ref(), subscriber.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_acl_address(program_id: &Pubkey, review: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"acl", review.as_ref()], program_id)
+}
+
+pub fn find_watch_attestation_address(
+    program_id: &Pubkey,
+    viewer: &Pubkey,
+    movie_id: &str,
+) -> (Pubkey, u8) {
+    find_entity_watch_attestation_address(program_id, EntityKind::Movie, viewer, movie_id)
+}
+
+pub fn find_entity_watch_attestation_address(
+    program_id: &Pubkey,
+    kind: EntityKind,
+    viewer: &Pubkey,
+    entity_id: &str,
+) -> (Pubkey, u8) {
+    state::derive_watch_attestation_pda(program_id, kind, viewer, entity_id)
+}
+
+pub fn find_comment_counter_address(program_id: &Pubkey, review: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[review.as_ref(), b"comment"], program_id)
+}
+
+pub fn find_comment_address(program_id: &Pubkey, review: &Pubkey, count: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[review.as_ref(), count.to_be_bytes().as_ref()], program_id)
+}
+
+pub fn find_escrow_address(
+    program_id: &Pubkey,
+    funder: &Pubkey,
+    recipient: &Pubkey,
+    nonce: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"escrow",
+            funder.as_ref(),
+            recipient.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+pub fn find_versus_poll_address(program_id: &Pubkey, movie_a: &str, movie_b: &str) -> (Pubkey, u8) {
+    state::derive_versus_poll_pda(program_id, movie_a, movie_b)
+}
+
+pub fn find_review_index_address(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    page: u32,
+) -> (Pubkey, u8) {
+    state::derive_review_index_pda(program_id, reviewer, page)
+}
+
+pub fn find_watchlist_address(program_id: &Pubkey, owner: &Pubkey, page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"watchlist", owner.as_ref(), &page.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_versus_vote_address(
+    program_id: &Pubkey,
+    poll: &Pubkey,
+    voter: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"versus_vote", poll.as_ref(), voter.as_ref()], program_id)
+}
+
+pub fn find_review_vote_address(
+    program_id: &Pubkey,
+    review: &Pubkey,
+    voter: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[review.as_ref(), voter.as_ref(), b"vote"], program_id)
+}
+
+fn pack<T: BorshSerialize>(variant: u8, payload: &T) -> Vec<u8> {
+    let mut data = vec![variant];
+    data.extend(payload.try_to_vec().unwrap());
+    data
+}
+
+/// `pack` tagged with `INSTRUCTION_VERSION`, for instructions whose
+/// `MovieInstruction::min_version` unversioned data doesn't meet.
+fn pack_versioned<T: BorshSerialize>(variant: u8, payload: &T) -> Vec<u8> {
+    let mut data = vec![VERSIONED_INSTRUCTION_TAG];
+    data.extend(INSTRUCTION_VERSION.to_le_bytes());
+    data.extend(pack(variant, payload));
+    data
+}
+
+/// Tags `instruction` with the instruction version this client was built
+/// against, so a program that no longer supports it fails with
+/// `UnsupportedInstructionVersion` instead of misreading it. Instructions that
+/// are already tagged are returned as they are.
+pub fn versioned(mut instruction: Instruction) -> Instruction {
+    if instruction.data.first() == Some(&VERSIONED_INSTRUCTION_TAG) {
+        return instruction;
+    }
+    let mut data = vec![VERSIONED_INSTRUCTION_TAG];
+    data.extend(INSTRUCTION_VERSION.to_le_bytes());
+    data.append(&mut instruction.data);
+    instruction.data = data;
+    instruction
+}
+
+/// Units assumed for an instruction to another program, such as creating a
+/// token account.
+pub const OTHER_PROGRAM_UNITS: u32 = 30_000;
+
+/// Estimated compute units for `instruction`, see `compute_units`.
+pub fn estimate_compute_units(program_id: &Pubkey, instruction: &Instruction) -> u32 {
+    if instruction.program_id != *program_id {
+        return OTHER_PROGRAM_UNITS;
+    }
+    match split_instruction_version(&instruction.data)
+        .and_then(|(_version, data)| MovieInstruction::unpack(data))
+    {
+        Ok(decoded) => {
+            let roles = inspect::account_roles(&decoded);
+            compute_units::estimate(
+                &decoded,
+                instruction.accounts.len().saturating_sub(roles.len()),
+            )
+        }
+        // The program rejects it before doing any real work.
+        Err(_) => OTHER_PROGRAM_UNITS,
+    }
+}
+
+/// Prepends a `SetComputeUnitLimit` covering the estimates for
+/// `instructions`, so heavy transactions don't run out of units under the
+/// default limit. Instructions that already set a compute budget are returned
+/// as they are.
+pub fn with_compute_unit_limit(
+    program_id: &Pubkey,
+    instructions: &[Instruction],
+) -> Vec<Instruction> {
+    if instructions
+        .iter()
+        .any(|instruction| instruction.program_id == compute_budget::id())
+    {
+        return instructions.to_vec();
+    }
+
+    let units = instructions
+        .iter()
+        .fold(0u32, |units, instruction| {
+            units.saturating_add(estimate_compute_units(program_id, instruction))
+        })
+        .min(compute_units::MAX_TRANSACTION_UNITS);
+
+    let mut with_limit = vec![ComputeBudgetInstruction::set_compute_unit_limit(units)];
+    with_limit.extend_from_slice(instructions);
+    with_limit
+}
+
+/// Optional accounts required by `add_movie_review` once the config charges a
+/// review fee.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReviewFeeAccounts {
+    pub treasury: Option<Pubkey>,
+    pub price_feed: Option<Pubkey>,
+    /// Pass the reviewer's watch attestation, which the config may require.
+    pub watch_attestation: bool,
+    /// Pass the reviewer's profile, required once the config enables rewards.
+    pub reward: bool,
+    /// User who referred the reviewer, credited on the reviewer's first
+    /// review. The referrer must already have a profile.
+    pub referrer: Option<Pubkey>,
+    /// Page of the reviewer's watchlist to take a reviewed movie off.
+    pub watchlist_page: Option<u32>,
+}
+
+pub fn add_movie_review(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    fee_accounts: ReviewFeeAccounts,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: add_review_accounts(program_id, reviewer, EntityKind::Movie, title, fee_accounts),
+        data: pack_versioned(0, &(title.to_string(), rating, description.to_string())),
+    }
+}
+
+/// `add_movie_review` that succeeds without changes when the reviewer already
+/// published this exact review, so it is safe to retry after a timeout.
+pub fn add_movie_review_idempotent(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    fee_accounts: ReviewFeeAccounts,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: add_review_accounts(program_id, reviewer, EntityKind::Movie, title, fee_accounts),
+        data: pack_versioned(49, &(title.to_string(), rating, description.to_string())),
+    }
+}
+
+/// Adds a review of a `kind` entity. Movie reviews made this way live at the
+/// same address as ones from `add_movie_review`.
+pub fn add_entity_review(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    kind: EntityKind,
+    title: &str,
+    rating: u8,
+    description: &str,
+    fee_accounts: ReviewFeeAccounts,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: add_review_accounts(program_id, reviewer, kind, title, fee_accounts),
+        data: pack_versioned(
+            36,
+            &(kind, title.to_string(), rating, description.to_string()),
+        ),
+    }
+}
+
+fn add_review_accounts(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    kind: EntityKind,
+    title: &str,
+    fee_accounts: ReviewFeeAccounts,
+) -> Vec<AccountMeta> {
+    let (review, _) = find_entity_review_address(program_id, kind, reviewer, title);
+    let (config, _) = find_config_address(program_id);
+    let (summary, _) = find_summary_address(program_id, kind, title);
+
+    let mut accounts = vec![
+        AccountMeta::new(*reviewer, true),
+        AccountMeta::new(review, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(config, false),
+        AccountMeta::new(summary, false),
+    ];
+    if let Some(price_feed) = fee_accounts.price_feed {
+        accounts.push(AccountMeta::new_readonly(price_feed, false));
+    }
+    if let Some(treasury) = fee_accounts.treasury {
+        accounts.push(AccountMeta::new(treasury, false));
+    }
+    if fee_accounts.watch_attestation {
+        let (attestation, _) =
+            find_entity_watch_attestation_address(program_id, kind, reviewer, title);
+        accounts.push(AccountMeta::new_readonly(attestation, false));
+    }
+    if fee_accounts.reward || fee_accounts.referrer.is_some() {
+        let (profile, _) = find_profile_address(program_id, reviewer);
+        accounts.push(AccountMeta::new(profile, false));
+    }
+    if let Some(referrer) = fee_accounts.referrer {
+        let (referrer_profile, _) = find_profile_address(program_id, &referrer);
+        accounts.push(AccountMeta::new(referrer_profile, false));
+    }
+    if let Some(page) = fee_accounts.watchlist_page {
+        let (watchlist, _) = find_watchlist_address(program_id, reviewer, page);
+        accounts.push(AccountMeta::new(watchlist, false));
+    }
+
+    accounts
+}
+
+/// Accounts the program needs to mint a reward to `recipient`.
+pub fn reward_accounts(program_id: &Pubkey, recipient: &Pubkey) -> Vec<AccountMeta> {
+    let (mint, _) = find_token_mint_address(program_id);
+    let (mint_auth, _) = find_mint_authority_address(program_id);
+
+    vec![
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(mint_auth, false),
+        AccountMeta::new(get_associated_token_address(recipient, &mint), false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+    ]
+}
+
+pub fn update_movie_review(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (config, _) = find_config_address(program_id);
+    let (summary, _) = find_summary_address(program_id, EntityKind::Movie, title);
+
+    // The system program is only used when the review has to grow to fit a
+    // longer description, which the reviewer pays for.
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(summary, false),
+        ],
+        data: pack_versioned(1, &(title.to_string(), rating, description.to_string())),
+    }
+}
+
+pub fn delete_movie_review(program_id: &Pubkey, reviewer: &Pubkey, title: &str) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (summary, _) = find_summary_address(program_id, EntityKind::Movie, title);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new(summary, false),
+        ],
+        data: pack_versioned(8, &()),
+    }
+}
+
+pub fn initialize_token_mint(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    let (mint, _) = find_token_mint_address(program_id);
+    let (mint_auth, _) = find_mint_authority_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(mint_auth, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+        data: vec![3],
+    }
+}
+
+/// Creates the treasury, config and reward token mint of a fresh deployment.
+/// `admin` must be the program's upgrade authority.
+pub fn initialize_program(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: &Pubkey,
+    max_price_age_secs: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    let (treasury, _) = find_treasury_address(program_id);
+    let (mint, _) = find_token_mint_address(program_id);
+    let (mint_auth, _) = find_mint_authority_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(mint_auth, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(find_program_data_address(program_id).0, false),
+        ],
+        data: pack(
+            27,
+            &(
+                review_fee_lamports,
+                review_fee_usd_cents,
+                *price_feed,
+                max_price_age_secs,
+            ),
+        ),
+    }
+}
+
+pub fn set_airdrop_root(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    round: u64,
+    merkle_root: [u8; 32],
+    max_num_claims: u64,
+) -> Instruction {
+    let (airdrop, _) = find_airdrop_address(program_id);
+    let (claims, _) = find_airdrop_claims_address(program_id, round);
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(airdrop, false),
+            AccountMeta::new(claims, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: pack(4, &(merkle_root, max_num_claims)),
+    }
+}
+
+/// The claimant pays to create their token account if it doesn't exist yet.
+pub fn claim_airdrop(
+    program_id: &Pubkey,
+    claimant: &Pubkey,
+    round: u64,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (airdrop, _) = find_airdrop_address(program_id);
+    let (claims, _) = find_airdrop_claims_address(program_id, round);
+    let (config, _) = find_config_address(program_id);
+    let (vesting, _) = find_vesting_address(program_id, &claims, index);
+
+    let mut accounts = vec![
+        AccountMeta::new(*claimant, true),
+        AccountMeta::new_readonly(airdrop, false),
+        AccountMeta::new(claims, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(vesting, false),
+    ];
+    accounts.extend(reward_accounts(program_id, claimant));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: pack(5, &(index, amount, proof)),
+    }
+}
+
+/// `admin` must be the program's upgrade authority.
+pub fn initialize_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: &Pubkey,
+    max_price_age_secs: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_program_data_address(program_id).0, false),
+        ],
+        data: pack(
+            6,
+            &(
+                *treasury,
+                review_fee_lamports,
+                review_fee_usd_cents,
+                *price_feed,
+                max_price_age_secs,
+            ),
+        ),
+    }
+}
+
+/// `initialize_config` that succeeds without changes when `admin` already
+/// initialized the config with these settings.
+pub fn initialize_config_idempotent(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: &Pubkey,
+    max_price_age_secs: u64,
+) -> Instruction {
+    let mut instruction = initialize_config(
+        program_id,
+        admin,
+        treasury,
+        review_fee_lamports,
+        review_fee_usd_cents,
+        price_feed,
+        max_price_age_secs,
+    );
+    instruction.data[0] = 51;
+    instruction
+}
+
+/// Purges superseded airdrop claim bitmaps, paying the caller a share of the
+/// reclaimed rent and refunding the rest to the airdrop authority. The
+/// program takes them in ascending key order, so they are sorted and
+/// deduplicated here.
+pub fn purge_expired(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    airdrop_authority: &Pubkey,
+    purge_accounts: &[Pubkey],
+) -> Instruction {
+    let (airdrop, _) = find_airdrop_address(program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(*caller, true),
+        AccountMeta::new_readonly(airdrop, false),
+        AccountMeta::new(*airdrop_authority, false),
+    ];
+    let mut purge_accounts = purge_accounts.to_vec();
+    purge_accounts.sort();
+    purge_accounts.dedup();
+    accounts.extend(
+        purge_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: vec![7],
+    }
+}
+
+/// Marks `owner`'s notifications below `upto_seq` as read, creating the inbox
+/// on first use.
+pub fn mark_notifications_read(program_id: &Pubkey, owner: &Pubkey, upto_seq: u64) -> Instruction {
+    let (inbox, _) = find_inbox_address(program_id, owner);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(inbox, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(9, &upto_seq),
+    }
+}
+
+/// Buys or extends a subscription to `reviewer`. `treasury` must be the
+/// configured treasury once the config has been initialized.
+pub fn subscribe(
+    program_id: &Pubkey,
+    subscriber: &Pubkey,
+    reviewer: &Pubkey,
+    months: u16,
+    treasury: Option<Pubkey>,
+) -> Instruction {
+    let (subscription, _) = find_subscription_address(program_id, subscriber, reviewer);
+    let (config, _) = find_config_address(program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(*subscriber, true),
+        AccountMeta::new(*reviewer, false),
+        AccountMeta::new(subscription, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(config, false),
+    ];
+    if let Some(treasury) = treasury {
+        accounts.push(AccountMeta::new(treasury, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: pack(10, &months),
+    }
+}
+
+/// Declares that the description of `reviewer`'s review of `title` is
+/// ciphertext under `scheme`.
+pub fn set_premium_content(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    scheme: EncryptionScheme,
+    plaintext_hash: [u8; 32],
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (premium, _) = find_premium_content_address(program_id, &review);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new_readonly(review, false),
+            AccountMeta::new(premium, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(11, &(scheme, plaintext_hash)),
+    }
+}
+
+/// Leaves `subscriber` the sealed content key of a premium review. The
+/// subscriber must hold an active subscription to `reviewer`.
+pub fn grant_access_key_hint(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    subscriber: &Pubkey,
+    key_hint: Vec<u8>,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (premium, _) = find_premium_content_address(program_id, &review);
+    let (subscription, _) = find_subscription_address(program_id, subscriber, reviewer);
+    let (key_hint_address, _) = find_access_key_hint_address(program_id, &review, subscriber);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new_readonly(review, false),
+            AccountMeta::new_readonly(premium, false),
+            AccountMeta::new_readonly(*subscriber, false),
+            AccountMeta::new_readonly(subscription, false),
+            AccountMeta::new(key_hint_address, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(12, &key_hint),
+    }
+}
+
+pub fn set_visibility(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    visibility: Visibility,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (summary, _) = find_summary_address(program_id, EntityKind::Movie, title);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new(summary, false),
+        ],
+        data: pack_versioned(13, &visibility),
+    }
+}
+
+/// Adds `member` to the ACL of `reviewer`'s review of `title`, creating the
+/// ACL on first use.
+pub fn grant_access(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    member: &Pubkey,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (acl, _) = find_acl_address(program_id, &review);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new_readonly(review, false),
+            AccountMeta::new(acl, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(14, member),
+    }
+}
+
+pub fn revoke_access(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    member: &Pubkey,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (acl, _) = find_acl_address(program_id, &review);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new_readonly(review, false),
+            AccountMeta::new(acl, false),
+        ],
+        data: pack(15, member),
+    }
+}
+
+/// Saves a review as a draft. Drafts are excluded from indexes, aggregates and
+/// rewards until `publish_review`.
+pub fn add_draft_review(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: add_review_accounts(
+            program_id,
+            reviewer,
+            EntityKind::Movie,
+            title,
+            ReviewFeeAccounts::default(),
+        ),
+        data: pack_versioned(16, &(title.to_string(), rating, description.to_string())),
+    }
+}
+
+/// Publishes `reviewer`'s draft, accruing the publication reward to their
+/// profile when `reward` is set.
+pub fn publish_review(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    reward: bool,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+    let (config, _) = find_config_address(program_id);
+    let (summary, _) = find_summary_address(program_id, EntityKind::Movie, title);
+
+    let mut accounts = vec![
+        AccountMeta::new(*reviewer, true),
+        AccountMeta::new(review, false),
+        AccountMeta::new(summary, false),
+    ];
+    if reward {
+        accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+        accounts.push(AccountMeta::new(config, false));
+        accounts.push(AccountMeta::new(
+            find_profile_address(program_id, reviewer).0,
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: pack_versioned(17, &()),
+    }
+}
+
+pub fn set_reward_schedule(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    base_amount: u64,
+    halving_interval: u64,
+    halving_unit: RewardHalvingUnit,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(28, &(base_amount, halving_interval, halving_unit)),
+    }
+}
+
+/// Lets anyone publish the draft once `publish_at` has passed, or clears the
+/// schedule when `None`.
+pub fn schedule_publish(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    publish_at: Option<i64>,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*reviewer, true),
+            AccountMeta::new(review, false),
+        ],
+        data: pack(18, &publish_at),
+    }
+}
+
+/// Lets anyone expire the review once `valid_until` has passed, or clears the
+/// expiry when `None`.
+pub fn set_review_expiry(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    valid_until: Option<i64>,
+) -> Instruction {
+    let (review, _) = find_review_address(program_id, reviewer, title);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*reviewer, true),
+            AccountMeta::new(review, false),
+        ],
+        data: pack(47, &valid_until),
+    }
+}
+
+/// Flips `reviewer`'s review of the `kind` entity `title` to expired once it
+/// is past its `valid_until`, or with `purge` closes it and refunds its rent
+/// to `reviewer`.
+pub fn expire_review(
+    program_id: &Pubkey,
+    kind: EntityKind,
+    reviewer: &Pubkey,
+    title: &str,
+    purge: bool,
+) -> Instruction {
+    let (review, _) = find_entity_review_address(program_id, kind, reviewer, title);
+    let (summary, _) = find_summary_address(program_id, kind, title);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(review, false),
+            AccountMeta::new(*reviewer, false),
+            AccountMeta::new(summary, false),
+        ],
+        data: pack_versioned(48, &purge),
+    }
+}
+
+pub fn archive_review(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    kind: EntityKind,
+    reviewer: &Pubkey,
+    title: &str,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    let (review, _) = find_entity_review_address(program_id, kind, reviewer, title);
+    let (summary, _) = find_summary_address(program_id, kind, title);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(review, false),
+            AccountMeta::new(summary, false),
+        ],
+        data: pack_versioned(59, &()),
+    }
+}
+
+pub fn attest_watched(
+    program_id: &Pubkey,
+    viewer: &Pubkey,
+    movie_id: &str,
+    watched_at: i64,
+) -> Instruction {
+    let (attestation, _) = find_watch_attestation_address(program_id, viewer, movie_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*viewer, true),
+            AccountMeta::new(attestation, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(19, &(movie_id.to_string(), watched_at)),
+    }
+}
+
+pub fn attest_entity_watched(
+    program_id: &Pubkey,
+    viewer: &Pubkey,
+    kind: EntityKind,
+    entity_id: &str,
+    watched_at: i64,
+) -> Instruction {
+    let (attestation, _) =
+        find_entity_watch_attestation_address(program_id, kind, viewer, entity_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*viewer, true),
+            AccountMeta::new(attestation, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(37, &(kind, entity_id.to_string(), watched_at)),
+    }
+}
+
+/// Makes `add_movie_review` demand a watch attestation for the movie.
+pub fn set_watch_requirement(program_id: &Pubkey, admin: &Pubkey, required: bool) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(20, &required),
+    }
+}
+
+/// Comments on the review at `review`, which must currently have `count`
+/// comments. Passing `reviewer_inbox` notifies the reviewer of the reply.
+/// Inboxes for pubkeys mentioned in `comment` are always included, as are the
+/// accounts of mentioned handles; the inbox of each registered handle's owner
+/// must be added by the caller, since resolving it needs a lookup. The
+/// accounts after `config` go in ascending key order.
+pub fn add_comment(
+    program_id: &Pubkey,
+    commenter: &Pubkey,
+    review: &Pubkey,
+    count: u64,
+    comment: &str,
+    reviewer_inbox: Option<Pubkey>,
+) -> Instruction {
+    let (counter, _) = find_comment_counter_address(program_id, review);
+    let (comment_address, _) = find_comment_address(program_id, review, count);
+    let (profile, _) = find_profile_address(program_id, commenter);
+    let (config, _) = find_config_address(program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(*commenter, true),
+        AccountMeta::new_readonly(*review, false),
+        AccountMeta::new(counter, false),
+        AccountMeta::new(comment_address, false),
+        AccountMeta::new(profile, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(config, false),
+    ];
+    if let Some(inbox) = reviewer_inbox {
+        accounts.push(AccountMeta::new(inbox, false));
+    }
+    for mentioned in mention::parse_mentions(comment) {
+        let meta = match mentioned {
+            Mention::Key(key) => AccountMeta::new(find_inbox_address(program_id, &key).0, false),
+            Mention::Handle(handle) => {
+                AccountMeta::new_readonly(find_handle_address(program_id, &handle).0, false)
+            }
+        };
+        if !accounts.iter().any(|account| account.pubkey == meta.pubkey) {
+            accounts.push(meta);
+        }
+    }
+    accounts[7..].sort_by_key(|account| account.pubkey);
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: pack(2, &comment.to_string()),
+    }
+}
+
+pub fn set_comment_limit(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    max_comments_per_review: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(21, &max_comments_per_review),
+    }
+}
+
+/// Exempts the review at `review` from the comment cap, or lifts the
+/// exemption.
+pub fn set_comment_cap_override(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    review: &Pubkey,
+    uncapped: bool,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    let (counter, _) = find_comment_counter_address(program_id, review);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*review, false),
+            AccountMeta::new(counter, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(22, &uncapped),
+    }
+}
+
+pub fn update_comment(
+    program_id: &Pubkey,
+    commenter: &Pubkey,
+    comment_address: &Pubkey,
+    comment: &str,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*commenter, true),
+            AccountMeta::new(*comment_address, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: pack(23, &comment.to_string()),
+    }
+}
+
+/// Deletes the comment at `comment_address`, refunding its rent to the
+/// commenter.
+pub fn delete_comment(
+    program_id: &Pubkey,
+    commenter: &Pubkey,
+    comment_address: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*commenter, true),
+            AccountMeta::new(*comment_address, false),
+        ],
+        data: vec![58],
+    }
+}
+
+pub fn set_comment_edit_window(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    comment_edit_window_secs: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(24, &comment_edit_window_secs),
+    }
+}
+
+/// Registers `handle` for `owner`. The handle is normalized the same way the
+/// program does, so `find_handle_address` must be given the normalized form.
+pub fn register_handle(program_id: &Pubkey, owner: &Pubkey, handle: &str) -> Instruction {
+    let (profile, _) = find_profile_address(program_id, owner);
+    let (handle_address, _) = find_handle_address(program_id, &handle.to_ascii_lowercase());
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(profile, false),
+            AccountMeta::new(handle_address, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(25, &handle.to_string()),
+    }
+}
+
+pub fn release_handle(program_id: &Pubkey, owner: &Pubkey, handle: &str) -> Instruction {
+    let (profile, _) = find_profile_address(program_id, owner);
+    let (handle_address, _) = find_handle_address(program_id, handle);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(profile, false),
+            AccountMeta::new(handle_address, false),
+        ],
+        data: vec![26],
+    }
+}
+
+/// Mints the claimer's accrued rewards, creating their token account if
+/// needed.
+pub fn claim_rewards(program_id: &Pubkey, claimer: &Pubkey) -> Instruction {
+    let (profile, _) = find_profile_address(program_id, claimer);
+
+    let mut accounts = vec![
+        AccountMeta::new(*claimer, true),
+        AccountMeta::new(profile, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    accounts.extend(reward_accounts(program_id, claimer));
+    accounts.push(AccountMeta::new_readonly(
+        find_config_address(program_id).0,
+        false,
+    ));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: vec![29],
+    }
+}
+
+/// `vesting` is the account created when the airdrop claim exceeded the
+/// config's vesting threshold, see `find_vesting_address`.
+pub fn claim_vested(program_id: &Pubkey, beneficiary: &Pubkey, vesting: &Pubkey) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*beneficiary, true),
+        AccountMeta::new(*vesting, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    accounts.extend(reward_accounts(program_id, beneficiary));
+    accounts.push(AccountMeta::new_readonly(
+        find_config_address(program_id).0,
+        false,
+    ));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: vec![30],
+    }
+}
+
+pub fn set_vesting_policy(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    threshold: u64,
+    duration_secs: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(31, &(threshold, duration_secs)),
+    }
+}
+
+pub fn set_referral_bonus(program_id: &Pubkey, admin: &Pubkey, referral_bonus: u64) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(32, &referral_bonus),
+    }
+}
+
+pub fn initialize_reviewer_profile(program_id: &Pubkey, owner: &Pubkey) -> Instruction {
+    let (profile, _) = find_profile_address(program_id, owner);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(profile, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: vec![35],
+    }
+}
+
+/// `initialize_reviewer_profile` that succeeds without changes when `owner`
+/// already has a profile.
+pub fn initialize_reviewer_profile_idempotent(program_id: &Pubkey, owner: &Pubkey) -> Instruction {
+    let mut instruction = initialize_reviewer_profile(program_id, owner);
+    instruction.data[0] = 50;
+    instruction
+}
+
+/// Records the deployed build's versions in the version account. Run it after
+/// every upgrade.
+pub fn sync_program_version(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    let (version, _) = find_version_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(version, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: vec![38],
+    }
+}
+
+/// Shrinks a review created with the original 1000-byte allocation to its
+/// contents, refunding the freed rent to `reviewer`.
+pub fn migrate_legacy_review(
+    program_id: &Pubkey,
+    review: &Pubkey,
+    reviewer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*review, false),
+            AccountMeta::new(*reviewer, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: vec![39],
+    }
+}
+
+/// Upgrades `reviewer`'s version 1 review of the `kind` entity `title` to the
+/// current account layout, counting it in the title's summary; they sign and
+/// pay for the extra bytes.
+pub fn migrate_review(
+    program_id: &Pubkey,
+    kind: EntityKind,
+    reviewer: &Pubkey,
+    title: &str,
+) -> Instruction {
+    let (review, _) = find_entity_review_address(program_id, kind, reviewer, title);
+    let (summary, _) = find_summary_address(program_id, kind, title);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(review, false),
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(summary, false),
+        ],
+        data: pack_versioned(65, &()),
+    }
+}
+
+/// Snapshots the summary of the `kind` entity `title` for `epoch`, which must
+/// be the current one. Anyone can send it; `payer` pays for the snapshot.
+pub fn snapshot_aggregate(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    kind: EntityKind,
+    title: &str,
+    epoch: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    let (summary, _) = find_summary_address(program_id, kind, title);
+    let (snapshot, _) = find_snapshot_address(program_id, &summary, epoch);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(summary, false),
+            AccountMeta::new(snapshot, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: vec![67],
+    }
+}
+
+/// Config admin only: closes `orphans` left behind by the deleted `review`,
+/// sending their rent to `treasury`. Orphans are passed in ascending key
+/// order, and the review's comment counter, if listed, is followed by the
+/// addresses of the `comment_count` comments it numbered.
+pub fn reclaim_orphans(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    review: &Pubkey,
+    orphans: &[Pubkey],
+    comment_count: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    let (counter, _) = find_comment_counter_address(program_id, review);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(*treasury, false),
+        AccountMeta::new_readonly(*review, false),
+    ];
+    let mut orphans = orphans.to_vec();
+    orphans.sort();
+    orphans.dedup();
+    for orphan in orphans {
+        accounts.push(AccountMeta::new(orphan, false));
+        if orphan == counter {
+            accounts.extend((0..comment_count).map(|count| {
+                AccountMeta::new(find_comment_address(program_id, review, count).0, false)
+            }));
+        }
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: vec![40],
+    }
+}
+
+/// Config admin only: sets who resolves disputed escrows and the escrow
+/// timeouts. The default arbiter disables new escrows.
+pub fn set_escrow_policy(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    arbiter: &Pubkey,
+    dispute_window_secs: u64,
+    resolution_timeout_secs: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(
+            41,
+            &(*arbiter, dispute_window_secs, resolution_timeout_secs),
+        ),
+    }
+}
+
+/// Config admin only: switches off exactly the `disabled` features, turning
+/// any others back on.
+pub fn set_disabled_features(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    disabled: &[Feature],
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+    let disabled_features = disabled
+        .iter()
+        .fold(0u32, |bits, feature| bits | feature.bit());
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(52, &disabled_features),
+    }
+}
+
+/// Caps review title and description lengths, in bytes. Zero removes a cap.
+pub fn set_review_length_limits(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    max_title_len: u32,
+    max_description_len: u32,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(60, &(max_title_len, max_description_len)),
+    }
+}
+
+pub fn set_paused(program_id: &Pubkey, admin: &Pubkey, paused: bool) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(61, &paused),
+    }
+}
+
+/// Config admin only: sets the length of a snapshot epoch. Zero stops
+/// snapshots.
+pub fn set_snapshot_interval(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    snapshot_interval_secs: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(66, &snapshot_interval_secs),
+    }
+}
+
+/// Config admin only: sets the tokens each new comment accrues to its
+/// author. Zero disables comment rewards.
+pub fn set_comment_reward(program_id: &Pubkey, admin: &Pubkey, amount: u64) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+        ],
+        data: pack(68, &amount),
+    }
+}
+
+/// Escrows `amount` lamports from `funder` for `recipient`. `nonce` tells
+/// apart escrows between the same pair.
+pub fn fund_escrow(
+    program_id: &Pubkey,
+    funder: &Pubkey,
+    recipient: &Pubkey,
+    nonce: u64,
+    amount: u64,
+) -> Instruction {
+    let (escrow, _) = find_escrow_address(program_id, funder, recipient, nonce);
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*funder, true),
+            AccountMeta::new_readonly(*recipient, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(42, &(nonce, amount)),
+    }
+}
+
+/// Freezes `escrow` until the arbiter resolves it. Signed by its funder.
+pub fn dispute_escrow(program_id: &Pubkey, funder: &Pubkey, escrow: &Pubkey) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*funder, true),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: vec![43],
+    }
+}
+
+/// Arbiter only: pays a disputed escrow to `destination`, its recipient if
+/// `release` is set and its funder otherwise.
+pub fn resolve_escrow(
+    program_id: &Pubkey,
+    arbiter: &Pubkey,
+    escrow: &Pubkey,
+    destination: &Pubkey,
+    release: bool,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*arbiter, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(*destination, false),
+        ],
+        data: pack(44, &release),
+    }
+}
+
+/// Pays out an escrow whose deadline has passed: to its recipient if it was
+/// never disputed, or back to its funder if the arbiter did not resolve it.
+pub fn settle_escrow(program_id: &Pubkey, escrow: &Pubkey, destination: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(*destination, false),
+        ],
+        data: vec![45],
+    }
+}
+
+/// Returns the rent of a resolved escrow to its funder.
+pub fn close_escrow(program_id: &Pubkey, funder: &Pubkey, escrow: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*funder, true),
+            AccountMeta::new(*escrow, false),
+        ],
+        data: vec![46],
+    }
+}
+
+/// Opens a poll between the movies titled `movie_a` and `movie_b`, taking
+/// votes until `ends_at`.
+pub fn create_versus_poll(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    movie_a: &str,
+    movie_b: &str,
+    ends_at: i64,
+) -> Instruction {
+    let (poll, _) = find_versus_poll_address(program_id, movie_a, movie_b);
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*creator, true),
+            AccountMeta::new(poll, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: pack(53, &(movie_a.to_string(), movie_b.to_string(), ends_at)),
+    }
+}
+
+/// Votes for `side` of `poll`, once per voter.
+pub fn vote_versus(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    poll: &Pubkey,
+    side: VersusSide,
+) -> Instruction {
+    let (receipt, _) = find_versus_vote_address(program_id, poll, voter);
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new(*poll, false),
+            AccountMeta::new(receipt, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: pack(54, &side),
+    }
+}
+
+/// Up- or downvotes `review`, once per voter.
+pub fn vote_on_review(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    review: &Pubkey,
+    is_upvote: bool,
+) -> Instruction {
+    let (vote, _) = find_review_vote_address(program_id, review, voter);
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new(*review, false),
+            AccountMeta::new(vote, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: pack(62, &is_upvote),
+    }
+}
+
+/// Tips `reviewer` `lamports` for their review at `review`.
+pub fn tip_reviewer(
+    program_id: &Pubkey,
+    tipper: &Pubkey,
+    review: &Pubkey,
+    reviewer: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*tipper, true),
+            AccountMeta::new(*review, false),
+            AccountMeta::new(*reviewer, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: pack(63, &lamports),
+    }
+}
+
+/// Tips `reviewer` `amount` reward tokens for their review at `review`, from
+/// `tipper`'s token account to the reviewer's, which must already exist.
+pub fn tip_reviewer_token(
+    program_id: &Pubkey,
+    tipper: &Pubkey,
+    review: &Pubkey,
+    reviewer: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (mint, _) = find_token_mint_address(program_id);
+    let (config, _) = find_config_address(program_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*tipper, true),
+            AccountMeta::new_readonly(*review, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(get_associated_token_address(tipper, &mint), false),
+            AccountMeta::new(get_associated_token_address(reviewer, &mint), false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: pack(64, &amount),
+    }
+}
+
+/// Records the winner of the poll between `movie_a` and `movie_b` once it has
+/// ended, in the poll and in both movies' summaries. Anyone can send it;
+/// `payer` pays for a summary a movie doesn't have yet.
+pub fn finalize_versus_poll(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    movie_a: &str,
+    movie_b: &str,
+) -> Instruction {
+    let (poll, _) = find_versus_poll_address(program_id, movie_a, movie_b);
+    let (summary_a, _) = find_summary_address(program_id, EntityKind::Movie, movie_a);
+    let (summary_b, _) = find_summary_address(program_id, EntityKind::Movie, movie_b);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(poll, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(summary_a, false),
+            AccountMeta::new(summary_b, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack_versioned(55, &()),
+    }
+}
+
+/// Adds `movie_id` to `page` of `owner`'s watchlist, creating the page if
+/// needed. Reviewing the movie with `ReviewFeeAccounts::watchlist_page` set
+/// takes it off again.
+pub fn add_to_watchlist(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    page: u32,
+    movie_id: &str,
+) -> Instruction {
+    let (watchlist, _) = find_watchlist_address(program_id, owner, page);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(watchlist, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(56, &(page, movie_id.to_string())),
+    }
+}
+
+pub fn remove_from_watchlist(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    page: u32,
+    movie_id: &str,
+) -> Instruction {
+    let (watchlist, _) = find_watchlist_address(program_id, owner, page);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(watchlist, false),
+        ],
+        data: pack(57, &(page, movie_id.to_string())),
+    }
+}
+
+/// Adds `reviewer`'s `reviews` to `page` of their review index, creating the
+/// page if needed. A page holds `state::REVIEW_INDEX_PAGE_LEN` reviews.
+pub fn index_reviews(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    page: u32,
+    reviews: &[Pubkey],
+) -> Instruction {
+    let (index, _) = find_review_index_address(program_id, reviewer, page);
+
+    let mut accounts = vec![
+        AccountMeta::new(*reviewer, true),
+        AccountMeta::new(index, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    accounts.extend(
+        reviews
+            .iter()
+            .map(|review| AccountMeta::new_readonly(*review, false)),
+    );
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: pack(69, &page),
+    }
+}
+
+/// Returns `page` of `reviewer`'s review index in the return data when
+/// simulated, see `decode_review_records`. `reviews` are the addresses the
+/// page holds, in order.
+pub fn export_my_reviews(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    page: u32,
+    reviews: &[Pubkey],
+) -> Instruction {
+    let (index, _) = find_review_index_address(program_id, reviewer, page);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*reviewer, false),
+        AccountMeta::new_readonly(index, false),
+    ];
+    accounts.extend(
+        reviews
+            .iter()
+            .map(|review| AccountMeta::new_readonly(*review, false)),
+    );
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: pack(70, &page),
+    }
+}
+
+/// Localnet only: mints `amount` reward tokens to `recipient`. Requires a
+/// program built with the `test-utils` feature.
+#[cfg(feature = "test-utils")]
+pub fn mint_test_tokens(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*recipient, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    accounts.extend(reward_accounts(program_id, recipient));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: pack(33, &amount),
+    }
+}
+
+/// Localnet only: creates or overwrites `owner`'s profile counters. Requires
+/// a program built with the `test-utils` feature.
+#[cfg(feature = "test-utils")]
+pub fn set_test_profile(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    owner: &Pubkey,
+    comments_created: u64,
+    reviews_published: u64,
+    pending_rewards: u64,
+) -> Instruction {
+    let (profile, _) = find_profile_address(program_id, owner);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(profile, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: pack(
+            34,
+            &(*owner, comments_created, reviews_published, pending_rewards),
+        ),
+    }
+}
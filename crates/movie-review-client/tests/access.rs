@@ -0,0 +1,263 @@
+mod common;
+
+use {
+    common::{now, review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        error::ReviewError,
+        find_access_key_hint_address, find_acl_address, find_premium_content_address,
+        find_subscription_address, grant_access, grant_access_key_hint, initialize_config,
+        revoke_access, set_premium_content, set_visibility,
+        state::{
+            AccessKeyHintState, AclState, EncryptionScheme, PremiumContentState,
+            SubscriptionState, Visibility, SUBSCRIPTION_MONTH_SECS, SUBSCRIPTION_PRICE_LAMPORTS,
+            SUBSCRIPTION_PROTOCOL_CUT_BPS,
+        },
+        subscribe,
+    },
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_program_test::tokio,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn test_subscribe_pays_reviewer_and_extends_expiry() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let reviewer = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+
+    context
+        .process_audited(
+            &[
+                initialize_config(
+                    &program_id,
+                    &payer,
+                    &treasury,
+                    0,
+                    0,
+                    &Pubkey::default(),
+                    0,
+                ),
+                system_instruction::transfer(&payer, &reviewer, 1_000_000_000),
+                system_instruction::transfer(&payer, &treasury, 1_000_000_000),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    context
+        .process_audited(
+            &[subscribe(&program_id, &payer, &reviewer, 2, Some(treasury))],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let price = 2 * SUBSCRIPTION_PRICE_LAMPORTS;
+    let cut = price * SUBSCRIPTION_PROTOCOL_CUT_BPS / 10_000;
+    assert_eq!(
+        context.get_balance(reviewer).await,
+        1_000_000_000 + price - cut
+    );
+    assert_eq!(context.get_balance(treasury).await, 1_000_000_000 + cut);
+
+    let (subscription_pda, _bump_seed) = find_subscription_address(&program_id, &payer, &reviewer);
+    let subscription = context
+        .banks_client
+        .get_state::<SubscriptionState>(subscription_pda)
+        .await;
+    assert_eq!(subscription.subscriber, payer);
+    assert_eq!(subscription.reviewer, reviewer);
+    let first_expiry = subscription.expires_at;
+    assert!(subscription.is_active(first_expiry - 1));
+    assert!(!subscription.is_active(first_expiry));
+
+    // Renewing stacks on top of the remaining time
+    context
+        .process_audited(
+            &[subscribe(&program_id, &payer, &reviewer, 1, Some(treasury))],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let subscription = context
+        .banks_client
+        .get_state::<SubscriptionState>(subscription_pda)
+        .await;
+    assert_eq!(
+        subscription.expires_at,
+        first_expiry + SUBSCRIPTION_MONTH_SECS
+    );
+}
+
+#[tokio::test]
+async fn test_subscribe_rejects_zero_months() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    assert_eq!(
+        context
+            .process(
+                &[subscribe(
+                    &program_id,
+                    &payer,
+                    &Pubkey::new_unique(),
+                    0,
+                    None
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidSubscriptionLength)
+    );
+}
+
+#[tokio::test]
+async fn test_grant_access_key_hint_requires_subscription() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let subscriber = context.funded_keypair(1_000_000_000).await;
+    let outsider = Pubkey::new_unique();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "ciphertext").await;
+    context
+        .process(
+            &[set_premium_content(
+                &program_id,
+                &payer,
+                title,
+                EncryptionScheme::XChaCha20Poly1305,
+                [7; 32],
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (premium_pda, _bump_seed) = find_premium_content_address(&program_id, &review_pda);
+    let premium = context
+        .banks_client
+        .get_state::<PremiumContentState>(premium_pda)
+        .await;
+    assert_eq!(premium.scheme, EncryptionScheme::XChaCha20Poly1305);
+    assert_eq!(premium.plaintext_hash, [7; 32]);
+
+    context
+        .process(
+            &[
+                subscribe(&program_id, &subscriber.pubkey(), &payer, 1, None),
+                grant_access_key_hint(
+                    &program_id,
+                    &payer,
+                    title,
+                    &subscriber.pubkey(),
+                    vec![9; 72],
+                ),
+            ],
+            &[&subscriber],
+        )
+        .await
+        .unwrap();
+
+    let (key_hint_pda, _bump_seed) =
+        find_access_key_hint_address(&program_id, &review_pda, &subscriber.pubkey());
+    let key_hint = context
+        .banks_client
+        .get_state::<AccessKeyHintState>(key_hint_pda)
+        .await;
+    assert_eq!(key_hint.subscriber, subscriber.pubkey());
+    assert_eq!(key_hint.key_hint, vec![9; 72]);
+
+    // Readers without a subscription cannot be handed the key
+    assert_eq!(
+        context
+            .process(
+                &[grant_access_key_hint(
+                    &program_id,
+                    &payer,
+                    title,
+                    &outsider,
+                    vec![9; 72],
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::SubscriptionRequired)
+    );
+}
+
+#[tokio::test]
+async fn test_restricted_review_acl() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let member = Pubkey::new_unique();
+    let outsider = Pubkey::new_unique();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+    let grant_ix = grant_access(&program_id, &payer, title, &member);
+    context
+        .process(
+            &[
+                set_visibility(&program_id, &payer, title, Visibility::Restricted),
+                grant_ix.clone(),
+                grant_ix,
+                grant_access(&program_id, &payer, title, &outsider),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    context
+        .process(&[revoke_access(&program_id, &payer, title, &outsider)], &[])
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.visibility, Visibility::Restricted);
+    assert!(!review.is_listed(now()));
+
+    // Granting twice keeps a single entry, and revoking removes it
+    let (acl_pda, _bump_seed) = find_acl_address(&program_id, &review_pda);
+    let acl = context.banks_client.get_state::<AclState>(acl_pda).await;
+    assert_eq!(acl.members, vec![member]);
+    assert!(review.is_readable_by(&payer, Some(&acl)));
+    assert!(review.is_readable_by(&member, Some(&acl)));
+    assert!(!review.is_readable_by(&outsider, Some(&acl)));
+    assert!(!review.is_readable_by(&member, None));
+}
+
+#[tokio::test]
+async fn test_set_visibility_rejects_other_reviewer() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = Keypair::new();
+
+    let title = "Captain America";
+    context.add_review(title, 3, "Liked the movie").await;
+
+    // `other` signs for the payer's review
+    let mut set_visibility_ix = set_visibility(&program_id, &payer, title, Visibility::Unlisted);
+    set_visibility_ix.accounts[0].pubkey = other.pubkey();
+    assert_eq!(
+        context
+            .process(&[set_visibility_ix], &[&other])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::UnauthorizedReviewer)
+    );
+}
@@ -0,0 +1,565 @@
+mod common;
+
+use {
+    common::{review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        add_comment, add_movie_review, claim_rewards, delete_movie_review,
+        error::ReviewError,
+        find_comment_address, find_comment_counter_address, find_mint_authority_address,
+        find_review_address, find_token_mint_address, find_treasury_address, find_version_address,
+        initialize_config, initialize_program, initialize_reviewer_profile, initialize_token_mint,
+        instruction::{INSTRUCTION_VERSION, MIN_INSTRUCTION_VERSION, VERSIONED_INSTRUCTION_TAG},
+        reclaim_orphans, set_disabled_features, set_paused, set_review_length_limits,
+        state::{Feature, ProgramVersionState},
+        sync_program_version, update_movie_review, versioned, ReviewFeeAccounts,
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        system_instruction,
+    },
+    solana_program_test::tokio,
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+#[tokio::test]
+async fn test_rejects_fake_program_accounts() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let fake_program = Pubkey::new_unique();
+
+    let title = "Captain America";
+    context.add_review(title, 3, "Liked the movie").await;
+
+    let with_fake = |mut instruction: Instruction, index: usize| {
+        instruction.accounts[index].pubkey = fake_program;
+        instruction
+    };
+    let add_ix = add_movie_review(
+        &program_id,
+        &payer,
+        "Iron Man",
+        3,
+        "Fun",
+        ReviewFeeAccounts::default(),
+    );
+    let grow_ix = update_movie_review(
+        &program_id,
+        &payer,
+        title,
+        4,
+        "Liked the movie a lot more the second time around",
+    );
+    let init_mint_ix = initialize_token_mint(&program_id, &payer);
+    let claim_ix = claim_rewards(&program_id, &payer);
+
+    let cases = [
+        (with_fake(add_ix, 2), InstructionError::IncorrectProgramId),
+        (with_fake(grow_ix, 2), InstructionError::IncorrectProgramId),
+        (
+            with_fake(init_mint_ix.clone(), 3),
+            InstructionError::IncorrectProgramId,
+        ),
+        (
+            with_fake(init_mint_ix.clone(), 4),
+            InstructionError::Custom(ReviewError::IncorrectAccountError as u32),
+        ),
+        (
+            with_fake(init_mint_ix, 5),
+            InstructionError::InvalidArgument,
+        ),
+        (with_fake(claim_ix, 2), InstructionError::IncorrectProgramId),
+    ];
+    for (instruction, expected) in cases {
+        assert_eq!(
+            context
+                .process(&[instruction], &[])
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, expected)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_program_runs_once() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    context
+        .process(
+            &[initialize_program(
+                &program_id,
+                &payer,
+                1_000,
+                0,
+                &Pubkey::default(),
+                0,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (treasury_pda, _bump_seed) = find_treasury_address(&program_id);
+    let config = context
+        .banks_client
+        .get_config(context.config_address())
+        .await;
+    assert_eq!(config.admin, payer);
+    assert_eq!(config.treasury, treasury_pda);
+    assert_eq!(config.review_fee_lamports, 1_000);
+
+    let account = context.get_account(treasury_pda).await.unwrap();
+    assert_eq!(account.owner, program_id);
+
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let (mint_auth, _bump_seed) = find_mint_authority_address(&program_id);
+    let account = context.get_account(mint).await.unwrap();
+    let mint_data = spl_token::state::Mint::unpack(&account.data).unwrap();
+    assert_eq!(mint_data.mint_authority, COption::Some(mint_auth));
+
+    // A second bootstrap is rejected
+    assert_eq!(
+        context
+            .process(
+                &[initialize_program(
+                    &program_id,
+                    &payer,
+                    2_000,
+                    0,
+                    &Pubkey::default(),
+                    0,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_config_requires_upgrade_authority() {
+    let mut context = ReviewTestContext::new().await;
+    context.set_upgrade_authority(Some(Pubkey::new_unique()));
+
+    assert_eq!(
+        context
+            .process(&[context.initialize_free_config()], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAuthority)
+    );
+}
+
+#[tokio::test]
+async fn test_config_limits_and_pause() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = Keypair::new();
+
+    let title = "Hulk";
+    context
+        .process(
+            &[
+                context.initialize_free_config(),
+                set_review_length_limits(&program_id, &payer, 10, 20),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+    let review_pda = context.add_review(title, 3, "Liked it").await;
+
+    let add_review = |title: &str| {
+        add_movie_review(
+            &program_id,
+            &payer,
+            title,
+            3,
+            "Liked it",
+            ReviewFeeAccounts::default(),
+        )
+    };
+    let cases = [
+        (
+            vec![add_review("Captain America")],
+            vec![],
+            ReviewError::TitleTooLong,
+        ),
+        (
+            vec![update_movie_review(
+                &program_id,
+                &payer,
+                title,
+                4,
+                "Liked it a lot more the second time",
+            )],
+            vec![],
+            ReviewError::DescriptionTooLong,
+        ),
+        (
+            vec![set_paused(&program_id, &other.pubkey(), true)],
+            vec![&other],
+            ReviewError::InvalidAuthority,
+        ),
+        (
+            vec![set_paused(&program_id, &payer, true), add_review("Thor")],
+            vec![],
+            ReviewError::ProgramPaused,
+        ),
+        (
+            vec![
+                set_paused(&program_id, &payer, true),
+                update_movie_review(&program_id, &payer, title, 4, "Loved it"),
+            ],
+            vec![],
+            ReviewError::ProgramPaused,
+        ),
+        (
+            vec![
+                set_paused(&program_id, &payer, true),
+                add_comment(&program_id, &payer, &review_pda, 0, "Great review", None),
+            ],
+            vec![],
+            ReviewError::ProgramPaused,
+        ),
+        (
+            vec![
+                set_paused(&program_id, &payer, true),
+                claim_rewards(&program_id, &payer),
+            ],
+            vec![],
+            ReviewError::ProgramPaused,
+        ),
+    ];
+    for (instructions, signers, expected) in cases {
+        assert_eq!(
+            context
+                .process(&instructions, &signers)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            review_error(instructions.len() as u8 - 1, expected)
+        );
+    }
+
+    // Unpausing lets reviews through again.
+    context
+        .process(
+            &[
+                set_paused(&program_id, &payer, true),
+                set_paused(&program_id, &payer, false),
+                update_movie_review(&program_id, &payer, title, 4, "Loved it"),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // Reviews can still be deleted while paused.
+    context
+        .process(
+            &[
+                set_paused(&program_id, &payer, true),
+                delete_movie_review(&program_id, &payer, title),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+    context.assert_closed(review_pda).await;
+}
+
+#[tokio::test]
+async fn test_disabled_features_reject_instructions() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    context
+        .process(
+            &[
+                context.initialize_free_config(),
+                set_disabled_features(&program_id, &payer, &[Feature::Comments, Feature::Minting]),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+
+    let comment_ix = add_comment(&program_id, &payer, &review_pda, 0, "First", None);
+    assert_eq!(
+        context
+            .process(std::slice::from_ref(&comment_ix), &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::FeatureDisabled)
+    );
+
+    // Only the admin can switch features back on
+    let other = Keypair::new();
+    assert_eq!(
+        context
+            .process(
+                &[set_disabled_features(&program_id, &other.pubkey(), &[])],
+                &[&other],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAuthority)
+    );
+
+    context
+        .process(
+            &[
+                set_disabled_features(&program_id, &payer, &[Feature::Minting]),
+                comment_ix,
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let config = context
+        .banks_client
+        .get_config(context.config_address())
+        .await;
+    assert!(config.is_enabled(Feature::Comments));
+    assert!(!config.is_enabled(Feature::Minting));
+}
+
+#[tokio::test]
+async fn test_sync_program_version_and_reject_unsupported_versions() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    context
+        .process(
+            &[
+                sync_program_version(&program_id, &payer),
+                versioned(initialize_reviewer_profile(&program_id, &payer)),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // The client is released in lockstep with the program.
+    let (version_pda, _bump_seed) = find_version_address(&program_id);
+    let version = context
+        .banks_client
+        .get_state::<ProgramVersionState>(version_pda)
+        .await;
+    assert_eq!(
+        format!("{}.{}.{}", version.major, version.minor, version.patch),
+        env!("CARGO_PKG_VERSION")
+    );
+    assert_eq!(version.instruction_version, INSTRUCTION_VERSION);
+    assert_eq!(version.min_instruction_version, MIN_INSTRUCTION_VERSION);
+
+    let mut too_new_ix = initialize_reviewer_profile(&program_id, &payer);
+    let mut data = vec![VERSIONED_INSTRUCTION_TAG];
+    data.extend((INSTRUCTION_VERSION + 1).to_le_bytes());
+    data.append(&mut too_new_ix.data);
+    too_new_ix.data = data;
+    assert_eq!(
+        context
+            .process(&[too_new_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::UnsupportedInstructionVersion)
+    );
+
+    // Instructions whose accounts changed since are turned away unversioned.
+    let mut unversioned_ix =
+        update_movie_review(&program_id, &payer, "Captain America", 4, "Liked it");
+    unversioned_ix.data.drain(..3);
+    assert_eq!(
+        context
+            .process(&[unversioned_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::UnsupportedInstructionVersion)
+    );
+}
+
+#[tokio::test]
+async fn test_reclaim_orphans_requires_deleted_review() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let commenter = Keypair::new();
+    let treasury = Pubkey::new_unique();
+
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    context
+        .process_audited(
+            &[
+                initialize_config(&program_id, &payer, &treasury, 0, 0, &Pubkey::default(), 0),
+                system_instruction::transfer(&payer, &treasury, 1_000_000_000),
+                system_instruction::transfer(&payer, &commenter.pubkey(), 1_000_000_000),
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    3,
+                    "Liked the movie",
+                    ReviewFeeAccounts::default(),
+                ),
+                add_comment(
+                    &program_id,
+                    &commenter.pubkey(),
+                    &review_pda,
+                    0,
+                    "Agreed!",
+                    None,
+                ),
+                add_comment(
+                    &program_id,
+                    &commenter.pubkey(),
+                    &review_pda,
+                    1,
+                    "Me too",
+                    None,
+                ),
+            ],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+
+    let (counter_pda, _bump_seed) = find_comment_counter_address(&program_id, &review_pda);
+    let (first_comment, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+    let (second_comment, _bump_seed) = find_comment_address(&program_id, &review_pda, 1);
+    let orphans = [counter_pda, first_comment, second_comment];
+
+    // Nothing can be reclaimed while the review exists.
+    assert_eq!(
+        context
+            .process_audited(
+                &[reclaim_orphans(
+                    &program_id,
+                    &payer,
+                    &treasury,
+                    &review_pda,
+                    &orphans[1..],
+                    0,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AccountNotOrphaned)
+    );
+
+    context
+        .process_audited(&[delete_movie_review(&program_id, &payer, title)], &[])
+        .await
+        .unwrap();
+
+    // The counter can't go without all of its comments.
+    assert_eq!(
+        context
+            .process_audited(
+                &[reclaim_orphans(
+                    &program_id,
+                    &payer,
+                    &treasury,
+                    &review_pda,
+                    &[counter_pda],
+                    1,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    // Orphans are listed in ascending key order, so once each.
+    let mut twice_ix = reclaim_orphans(
+        &program_id,
+        &payer,
+        &treasury,
+        &review_pda,
+        &[counter_pda],
+        2,
+    );
+    twice_ix.accounts.extend(
+        orphans
+            .iter()
+            .map(|orphan| AccountMeta::new(*orphan, false)),
+    );
+    assert_eq!(
+        context
+            .process_audited(&[twice_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AccountsOutOfOrder)
+    );
+
+    // Only the config admin may reclaim.
+    let reclaim_ix = |admin: &Pubkey| {
+        reclaim_orphans(
+            &program_id,
+            admin,
+            &treasury,
+            &review_pda,
+            &[counter_pda],
+            2,
+        )
+    };
+    assert_eq!(
+        context
+            .process_audited(&[reclaim_ix(&commenter.pubkey())], &[&commenter])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAuthority)
+    );
+
+    let mut orphan_lamports = 0;
+    for orphan in orphans {
+        orphan_lamports += context.get_balance(orphan).await;
+    }
+    let treasury_lamports = context.get_balance(treasury).await;
+
+    context
+        .process_audited(&[reclaim_ix(&payer)], &[])
+        .await
+        .unwrap();
+
+    for orphan in orphans {
+        context.assert_closed(orphan).await;
+    }
+    assert_eq!(
+        context.get_balance(treasury).await,
+        treasury_lamports + orphan_lamports
+    );
+}
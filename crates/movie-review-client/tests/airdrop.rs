@@ -0,0 +1,345 @@
+mod common;
+
+use {
+    assert_matches::*,
+    borsh::BorshSerialize,
+    common::{review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        claim_airdrop, claim_vested,
+        error::ReviewError,
+        find_airdrop_address, find_airdrop_claims_address, find_token_mint_address,
+        find_vesting_address, initialize_token_mint, merkle, purge_expired, set_airdrop_root,
+        set_vesting_policy,
+        state::{self, AirdropState, VestingState, PURGE_BOUNTY_BPS},
+    },
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
+    solana_program_test::tokio,
+    solana_sdk::{account::Account, signature::Signer},
+    spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account,
+    },
+};
+
+#[tokio::test]
+async fn test_claim_airdrop_instruction() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+
+    // Two-leaf tree: the payer's claim and someone else's
+    const AMOUNT: u64 = 100;
+    let payer_leaf = merkle::airdrop_leaf(0, &payer, AMOUNT);
+    let other_leaf = merkle::airdrop_leaf(1, &Pubkey::new_unique(), 50);
+    let root = merkle::hash_pair(&payer_leaf, &other_leaf);
+
+    assert_matches!(
+        context
+            .process(
+                &[
+                    initialize_token_mint(&program_id, &payer),
+                    create_associated_token_account(&payer, &payer, &mint, &spl_token::ID),
+                    context.initialize_free_config(),
+                    set_airdrop_root(&program_id, &payer, 1, root, 2),
+                    claim_airdrop(&program_id, &payer, 1, 0, AMOUNT, vec![other_leaf]),
+                ],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let user_ata = get_associated_token_address(&payer, &mint);
+    assert_eq!(
+        context.banks_client.get_token_amount(user_ata).await,
+        AMOUNT
+    );
+
+    // A second claim against the same bitmap entry must be rejected
+    let claim_ix = claim_airdrop(&program_id, &payer, 1, 0, AMOUNT, vec![other_leaf]);
+    assert_eq!(
+        context
+            .process(&[claim_ix.clone(), claim_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AirdropAlreadyClaimed)
+    );
+}
+
+#[tokio::test]
+async fn test_claim_airdrop_creates_token_account() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    const AMOUNT: u64 = 100;
+    let payer_leaf = merkle::airdrop_leaf(0, &payer, AMOUNT);
+    let other_leaf = merkle::airdrop_leaf(1, &Pubkey::new_unique(), 50);
+    let root = merkle::hash_pair(&payer_leaf, &other_leaf);
+
+    // No ATA setup: the claim creates it
+    context
+        .process(
+            &[
+                initialize_token_mint(&program_id, &payer),
+                context.initialize_free_config(),
+                set_airdrop_root(&program_id, &payer, 1, root, 2),
+                claim_airdrop(&program_id, &payer, 1, 0, AMOUNT, vec![other_leaf]),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let user_ata = get_associated_token_address(&payer, &mint);
+    let token_account = context.banks_client.get_token_account(user_ata).await;
+    assert_eq!(token_account.owner, payer);
+    assert_eq!(token_account.amount, AMOUNT);
+}
+
+#[tokio::test]
+async fn test_claim_airdrop_rejects_invalid_proof() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let payer_leaf = merkle::airdrop_leaf(0, &payer, 100);
+    let other_leaf = merkle::airdrop_leaf(1, &Pubkey::new_unique(), 50);
+    let root = merkle::hash_pair(&payer_leaf, &other_leaf);
+
+    // Claiming more than the committed amount produces a different leaf
+    assert_eq!(
+        context
+            .process(
+                &[
+                    initialize_token_mint(&program_id, &payer),
+                    create_associated_token_account(&payer, &payer, &mint, &spl_token::ID),
+                    context.initialize_free_config(),
+                    set_airdrop_root(&program_id, &payer, 1, root, 2),
+                    claim_airdrop(&program_id, &payer, 1, 0, 1_000, vec![other_leaf]),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(4, ReviewError::InvalidMerkleProof)
+    );
+}
+
+#[tokio::test]
+async fn test_claim_airdrop_vests_large_grants() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    const AMOUNT: u64 = 100;
+    let payer_leaf = merkle::airdrop_leaf(0, &payer, AMOUNT);
+    let other_leaf = merkle::airdrop_leaf(1, &Pubkey::new_unique(), 50);
+    let root = merkle::hash_pair(&payer_leaf, &other_leaf);
+
+    context
+        .process(
+            &[
+                initialize_token_mint(&program_id, &payer),
+                context.initialize_free_config(),
+                set_vesting_policy(&program_id, &payer, 50, 30 * 24 * 60 * 60),
+                set_airdrop_root(&program_id, &payer, 1, root, 2),
+                claim_airdrop(&program_id, &payer, 1, 0, AMOUNT, vec![other_leaf]),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // Nothing is minted upfront; the grant is locked in a vesting account
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let user_ata = get_associated_token_address(&payer, &mint);
+    assert!(context.get_account(user_ata).await.is_none());
+
+    let (claims_pda, _bump_seed) = find_airdrop_claims_address(&program_id, 1);
+    let (vesting_pda, _bump_seed) = find_vesting_address(&program_id, &claims_pda, 0);
+    let vesting = context
+        .banks_client
+        .get_state::<VestingState>(vesting_pda)
+        .await;
+    assert_eq!(vesting.beneficiary, payer);
+    assert_eq!(vesting.total, AMOUNT);
+    assert_eq!(vesting.duration_secs, 30 * 24 * 60 * 60);
+    assert_eq!(vesting.claimed, 0);
+
+    // Claiming right away only releases what has unlocked so far
+    context
+        .process(&[claim_vested(&program_id, &payer, &vesting_pda)], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(context.banks_client.get_token_amount(user_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_set_airdrop_root_requires_config_admin() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let attacker = context.funded_keypair(1_000_000_000).await;
+
+    // Without a config there is no admin to publish a root
+    let set_root_ix = set_airdrop_root(&program_id, &attacker.pubkey(), 1, [1; 32], 8);
+    assert_eq!(
+        context
+            .process(std::slice::from_ref(&set_root_ix), &[&attacker])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::UninitializedAccount)
+    );
+
+    // Nor can anyone but the admin once there is one
+    assert_eq!(
+        context
+            .process(
+                &[context.initialize_free_config(), set_root_ix],
+                &[&attacker],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::InvalidAuthority)
+    );
+
+    let (airdrop_pda, _bump_seed) = find_airdrop_address(&program_id);
+    assert!(context.get_account(airdrop_pda).await.is_none());
+}
+
+#[tokio::test]
+async fn test_set_airdrop_root_rejects_round_overflow() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // An airdrop that has already used up every round number
+    let airdrop = AirdropState {
+        discriminator: AirdropState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        authority: payer,
+        merkle_root: [0; 32],
+        round: u64::MAX,
+        max_num_claims: 1,
+    };
+    let (airdrop_pda, _bump_seed) = find_airdrop_address(&program_id);
+    context.set_account(
+        airdrop_pda,
+        Account {
+            lamports: 100_000_000,
+            data: airdrop.try_to_vec().unwrap(),
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    context
+        .process(&[context.initialize_free_config()], &[])
+        .await
+        .unwrap();
+
+    // There is no next round to derive a claims address for
+    let mut ix = set_airdrop_root(&program_id, &payer, 0, [1; 32], 8);
+    ix.accounts[2].pubkey = Pubkey::new_unique();
+    assert_eq!(
+        context.process(&[ix], &[]).await.unwrap_err().unwrap(),
+        review_error(0, ReviewError::ArithmeticOverflow)
+    );
+}
+
+#[tokio::test]
+async fn test_purge_expired_airdrop_claims() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // Anyone can crank the purge, not just the authority
+    let caller = context.funded_keypair(1_000_000_000).await;
+    assert_matches!(
+        context
+            .process_audited(
+                &[
+                    context.initialize_free_config(),
+                    set_airdrop_root(&program_id, &payer, 1, [1; 32], 64),
+                    set_airdrop_root(&program_id, &payer, 2, [2; 32], 64),
+                ],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let (stale_claims, _bump_seed) = find_airdrop_claims_address(&program_id, 1);
+    let (live_claims, _bump_seed) = find_airdrop_claims_address(&program_id, 2);
+    let stale_lamports = context.get_balance(stale_claims).await;
+
+    // The current round's bitmap is still needed
+    assert_eq!(
+        context
+            .process_audited(
+                &[purge_expired(
+                    &program_id,
+                    &caller.pubkey(),
+                    &payer,
+                    &[live_claims]
+                )],
+                &[&caller],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AccountNotPurgeable)
+    );
+
+    // Listing an account twice breaks the ascending key order
+    let mut twice = purge_expired(&program_id, &caller.pubkey(), &payer, &[stale_claims]);
+    twice.accounts.push(AccountMeta::new(stale_claims, false));
+    assert_eq!(
+        context
+            .process_audited(&[twice], &[&caller])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AccountsOutOfOrder)
+    );
+
+    // The payer, as airdrop authority, gets back the rent less the caller's
+    // bounty, and pays the fee for both signatures.
+    let caller_lamports = context.get_balance(caller.pubkey()).await;
+    let payer_lamports = context.get_balance(payer).await;
+    assert_matches!(
+        context
+            .process_audited(
+                &[purge_expired(
+                    &program_id,
+                    &caller.pubkey(),
+                    &payer,
+                    &[stale_claims]
+                )],
+                &[&caller],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let bounty = stale_lamports * PURGE_BOUNTY_BPS / 10_000;
+    context.assert_closed(stale_claims).await;
+    assert_eq!(
+        context.get_balance(payer).await,
+        payer_lamports + stale_lamports - bounty - 10_000
+    );
+    assert_eq!(
+        context.get_balance(caller.pubkey()).await,
+        caller_lamports + bounty
+    );
+}
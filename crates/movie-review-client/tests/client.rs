@@ -0,0 +1,133 @@
+//! `MovieReviewClient` against a mock RPC server, which checks how it reads
+//! and decodes accounts without a running validator.
+
+#![cfg(feature = "client")]
+
+use {
+    borsh::BorshSerialize,
+    movie_review_client::{
+        client::MovieReviewClient,
+        find_review_address,
+        state::{self, EntityKind, MovieAccountState, ReviewStatus, Visibility},
+    },
+    serde_json::{json, Value},
+    solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signature::Keypair,
+};
+
+fn review(reviewer: Pubkey, title: &str) -> MovieAccountState {
+    MovieAccountState {
+        discriminator: MovieAccountState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        reviewer,
+        rating: 40,
+        title: title.to_string(),
+        description: "Liked the movie".to_string(),
+        visibility: Visibility::Public,
+        status: ReviewStatus::Published,
+        published_at: 0,
+        publish_at: None,
+        kind: EntityKind::Movie,
+        valid_until: None,
+        upvotes: 0,
+        downvotes: 0,
+        tips_received: 0,
+        created_at: 0,
+        updated_at: 0,
+        bump: 0,
+    }
+}
+
+/// `review` as the RPC server encodes an account owned by `program_id`.
+fn ui_account(program_id: &Pubkey, review: &MovieAccountState) -> Value {
+    json!({
+        "lamports": 1_000_000,
+        "data": [base64::encode(review.try_to_vec().unwrap()), "base64"],
+        "owner": program_id.to_string(),
+        "executable": false,
+        "rentEpoch": 0,
+    })
+}
+
+fn mock_client(program_id: Pubkey, mocks: Mocks) -> MovieReviewClient {
+    MovieReviewClient::new(
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks),
+        program_id,
+    )
+}
+
+#[test]
+fn test_get_review_decodes_the_account() {
+    let program_id = Pubkey::new_unique();
+    let reviewer = Pubkey::new_unique();
+    let title = "Captain America";
+    let expected = review(reviewer, title);
+
+    let mut mocks = Mocks::new();
+    mocks.insert(
+        RpcRequest::GetAccountInfo,
+        json!({
+            "context": { "slot": 1 },
+            "value": ui_account(&program_id, &expected),
+        }),
+    );
+    let client = mock_client(program_id, mocks);
+
+    let address = client.review_address(&reviewer, title);
+    assert_eq!(
+        address,
+        find_review_address(&program_id, &reviewer, title).0
+    );
+    let review = client.get_review(&address).unwrap();
+    assert_eq!(review.reviewer, reviewer);
+    assert_eq!(review.title, title);
+    assert_eq!(review.rating_tenths(), 40);
+}
+
+#[test]
+fn test_get_reviews_by_reviewer_skips_other_reviewers() {
+    let program_id = Pubkey::new_unique();
+    let reviewer = Pubkey::new_unique();
+    let own_review = Pubkey::new_unique();
+
+    // An account the filters let through by chance is dropped after decoding.
+    let mut mocks = Mocks::new();
+    mocks.insert(
+        RpcRequest::GetProgramAccounts,
+        json!([
+            {
+                "pubkey": own_review.to_string(),
+                "account": ui_account(&program_id, &review(reviewer, "Captain America")),
+            },
+            {
+                "pubkey": Pubkey::new_unique().to_string(),
+                "account": ui_account(&program_id, &review(Pubkey::new_unique(), "Iron Man")),
+            },
+        ]),
+    );
+    let client = mock_client(program_id, mocks);
+
+    let reviews = client.get_reviews_by_reviewer(&reviewer).unwrap();
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0].0, own_review);
+    assert_eq!(reviews[0].1.title, "Captain America");
+}
+
+#[test]
+fn test_add_and_update_review_send_transactions() {
+    let client = mock_client(Pubkey::new_unique(), Mocks::new());
+    let reviewer = Keypair::new();
+
+    // The mock server has no config, so the review is free.
+    client
+        .add_review(&reviewer, "Captain America", 4, "Liked the movie")
+        .unwrap();
+    client
+        .update_review(&reviewer, "Captain America", 5, "Loved the movie")
+        .unwrap();
+}
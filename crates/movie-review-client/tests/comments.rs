@@ -0,0 +1,584 @@
+mod common;
+
+use {
+    borsh::BorshSerialize,
+    common::{now, review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        add_comment, delete_comment, delete_movie_review,
+        error::ReviewError,
+        find_comment_address, find_comment_counter_address, find_config_address,
+        find_inbox_address, find_profile_address, mark_notifications_read, register_handle,
+        set_comment_cap_override, set_comment_limit,
+        state::{
+            self, ConfigState, InboxState, MovieComment, MovieCommentCounter, NotificationKind,
+            RewardHalvingUnit,
+        },
+        update_comment,
+    },
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
+    solana_program_test::tokio,
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+#[tokio::test]
+async fn test_add_comment_notifies_reviewer() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let commenter = context.funded_keypair(1_000_000_000).await;
+
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+    let (inbox_pda, _bump_seed) = find_inbox_address(&program_id, &payer);
+    context
+        .process(
+            &[add_comment(
+                &program_id,
+                &commenter.pubkey(),
+                &review_pda,
+                0,
+                "Agreed!",
+                Some(inbox_pda),
+            )],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+
+    let (comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+    let comment = context.banks_client.get_comment(comment_pda).await;
+    assert_eq!(comment.review, review_pda);
+    assert_eq!(comment.commenter, commenter.pubkey());
+    assert_eq!(comment.comment, "Agreed!");
+    assert_eq!(comment.count, 0);
+
+    let inbox = context
+        .banks_client
+        .get_state::<InboxState>(inbox_pda)
+        .await;
+    let unread = inbox.unread();
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0].kind, NotificationKind::Reply);
+    assert_eq!(unread[0].source, comment_pda);
+}
+
+#[tokio::test]
+async fn test_mark_notifications_read_opens_inbox() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // The first call opens an empty inbox and cannot move the cursor
+    context
+        .process(&[mark_notifications_read(&program_id, &payer, 10)], &[])
+        .await
+        .unwrap();
+
+    let (inbox_pda, _bump_seed) = find_inbox_address(&program_id, &payer);
+    let account = context.get_account(inbox_pda).await.unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.data.len(), InboxState::SIZE);
+    let inbox = context
+        .banks_client
+        .get_state::<InboxState>(inbox_pda)
+        .await;
+    assert_eq!(inbox.owner, payer);
+    assert_eq!(inbox.next_seq, 0);
+    assert_eq!(inbox.read_seq, 0);
+}
+
+#[tokio::test]
+async fn test_mark_notifications_read() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // An inbox holding three notifications, none read yet
+    let mut inbox = InboxState {
+        discriminator: InboxState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        owner: payer,
+        next_seq: 0,
+        read_seq: 0,
+        notifications: Vec::new(),
+    };
+    for slot in 0..3 {
+        inbox
+            .push(NotificationKind::Mention, Pubkey::new_unique(), slot)
+            .unwrap();
+    }
+    let mut data = vec![0; InboxState::SIZE];
+    inbox.serialize(&mut &mut data[..]).unwrap();
+    let (inbox_pda, _bump_seed) = find_inbox_address(&program_id, &payer);
+    context.set_account(
+        inbox_pda,
+        Account {
+            lamports: 100_000_000,
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    context
+        .process(&[mark_notifications_read(&program_id, &payer, 2)], &[])
+        .await
+        .unwrap();
+
+    let inbox = context
+        .banks_client
+        .get_state::<InboxState>(inbox_pda)
+        .await;
+    assert_eq!(inbox.read_seq, 2);
+    let unread = inbox.unread();
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0].seq, 2);
+    assert_eq!(unread[0].kind, NotificationKind::Mention);
+}
+
+#[tokio::test]
+async fn test_add_comment_resolves_handle_mentions() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let fan = context.funded_keypair(1_000_000_000).await;
+
+    context
+        .process(
+            &[register_handle(&program_id, &fan.pubkey(), "cinephile")],
+            &[&fan],
+        )
+        .await
+        .unwrap();
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+
+    // The handle's account comes from the text; its owner's inbox is looked
+    // up and added by the caller.
+    let (inbox_pda, _bump_seed) = find_inbox_address(&program_id, &fan.pubkey());
+    let mut add_comment_ix = add_comment(
+        &program_id,
+        &payer,
+        &review_pda,
+        0,
+        "Thoughts, @Cinephile? Not you @nobody",
+        None,
+    );
+    add_comment_ix
+        .accounts
+        .push(AccountMeta::new(inbox_pda, false));
+    add_comment_ix.accounts[7..].sort_by_key(|account| account.pubkey);
+
+    // Extra accounts must come in ascending key order.
+    let mut out_of_order_ix = add_comment_ix.clone();
+    out_of_order_ix.accounts[7..].reverse();
+    assert_eq!(
+        context
+            .process(&[out_of_order_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AccountsOutOfOrder)
+    );
+
+    context.process(&[add_comment_ix], &[]).await.unwrap();
+
+    let (comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+    let inbox = context
+        .banks_client
+        .get_state::<InboxState>(inbox_pda)
+        .await;
+    let unread = inbox.unread();
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0].kind, NotificationKind::Mention);
+    assert_eq!(unread[0].source, comment_pda);
+}
+
+#[tokio::test]
+async fn test_add_comment_notifies_mentions() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let mentioned = Pubkey::new_unique();
+    let text = format!("You should see this @{}!", mentioned);
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+
+    // Without the mentioned user's inbox the comment is rejected
+    let add_comment_ix = add_comment(&program_id, &payer, &review_pda, 0, &text, None);
+    let mut without_inbox_ix = add_comment_ix.clone();
+    without_inbox_ix.accounts.truncate(7);
+    assert_eq!(
+        context
+            .process(&[without_inbox_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    context.process(&[add_comment_ix], &[]).await.unwrap();
+
+    let (comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+    let (inbox_pda, _bump_seed) = find_inbox_address(&program_id, &mentioned);
+    let inbox = context
+        .banks_client
+        .get_state::<InboxState>(inbox_pda)
+        .await;
+    assert_eq!(inbox.owner, mentioned);
+    let unread = inbox.unread();
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0].kind, NotificationKind::Mention);
+    assert_eq!(unread[0].source, comment_pda);
+}
+
+#[tokio::test]
+async fn test_add_comment_enforces_comment_limit() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    context
+        .process(
+            &[
+                context.initialize_free_config(),
+                set_comment_limit(&program_id, &payer, 1),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+    context.add_comment(review_pda, 0, "First").await;
+
+    let second_comment_ix = add_comment(&program_id, &payer, &review_pda, 1, "Second", None);
+    assert_eq!(
+        context
+            .process(std::slice::from_ref(&second_comment_ix), &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::CommentLimitReached)
+    );
+
+    // The admin can lift the cap for a special thread
+    context
+        .process(
+            &[
+                set_comment_cap_override(&program_id, &payer, &review_pda, true),
+                second_comment_ix,
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (counter_pda, _bump_seed) = find_comment_counter_address(&program_id, &review_pda);
+    let counter = context
+        .banks_client
+        .get_state::<MovieCommentCounter>(counter_pda)
+        .await;
+    assert_eq!(counter.counter, 2);
+    assert!(counter.uncapped);
+}
+
+#[tokio::test]
+async fn test_update_comment_resizes_account() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+    let comment_pda = context.add_comment(review_pda, 0, "Short").await;
+    let edited = "A much longer comment than the original one";
+    context
+        .process(
+            &[update_comment(&program_id, &payer, &comment_pda, edited)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let account = context.get_account(comment_pda).await.unwrap();
+    assert_eq!(
+        account.data.len(),
+        MovieComment::get_account_size(edited.to_string()).unwrap()
+    );
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let comment = context.banks_client.get_comment(comment_pda).await;
+    assert_eq!(comment.comment, edited);
+    assert!(comment.created_at > 0);
+}
+
+#[tokio::test]
+async fn test_update_comment_checks_typed_account() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+    let comment_pda = context.add_comment(review_pda, 0, "Short").await;
+
+    let mut read_only_ix = update_comment(&program_id, &payer, &comment_pda, "Edited");
+    read_only_ix.accounts[1].is_writable = false;
+    assert_eq!(
+        context
+            .process(&[read_only_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AccountNotWritable)
+    );
+
+    // A review is program-owned and initialized, but not a comment.
+    assert_eq!(
+        context
+            .process(
+                &[update_comment(&program_id, &payer, &review_pda, "Edited")],
+                &[]
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAccountType)
+    );
+
+    // Nor is a comment a review, even where the review is read directly.
+    let mut delete_ix = delete_movie_review(&program_id, &payer, title);
+    delete_ix.accounts[1].pubkey = comment_pda;
+    assert_eq!(
+        context
+            .process(&[delete_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAccountType)
+    );
+}
+
+#[tokio::test]
+async fn test_delete_comment_refunds_rent() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let commenter = context.funded_keypair(100_000_000).await;
+    let other = Keypair::new();
+
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+    context
+        .process(
+            &[add_comment(
+                &program_id,
+                &commenter.pubkey(),
+                &review_pda,
+                0,
+                "Great review",
+                None,
+            )],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+    let (comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+
+    // Only the commenter can delete the comment.
+    assert_eq!(
+        context
+            .process(
+                &[delete_comment(&program_id, &other.pubkey(), &comment_pda)],
+                &[&other],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAuthority)
+    );
+
+    let comment_lamports = context.get_balance(comment_pda).await;
+    let commenter_lamports = context.get_balance(commenter.pubkey()).await;
+
+    context
+        .process(
+            &[delete_comment(
+                &program_id,
+                &commenter.pubkey(),
+                &comment_pda,
+            )],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+
+    context.assert_closed(comment_pda).await;
+    assert_eq!(
+        context.get_balance(commenter.pubkey()).await,
+        commenter_lamports + comment_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_first_comment_opens_the_comment_counter() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let commenter = context.funded_keypair(1_000_000_000).await;
+
+    // Adding the review leaves the counter to the first comment
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+    let (counter_pda, _bump_seed) = find_comment_counter_address(&program_id, &review_pda);
+    assert!(context.get_account(counter_pda).await.is_none());
+
+    let commenter_lamports = context.get_balance(commenter.pubkey()).await;
+    context
+        .process(
+            &[add_comment(
+                &program_id,
+                &commenter.pubkey(),
+                &review_pda,
+                0,
+                "First",
+                None,
+            )],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+
+    let counter = context
+        .banks_client
+        .get_state::<MovieCommentCounter>(counter_pda)
+        .await;
+    assert_eq!(counter.counter, 1);
+    assert!(!counter.uncapped);
+
+    // The commenter pays the counter's rent along with their comment's and
+    // profile's; the transaction fee is the payer's
+    let (comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &commenter.pubkey());
+    let rent = context.get_balance(counter_pda).await
+        + context.get_balance(comment_pda).await
+        + context.get_balance(profile_pda).await;
+    assert_eq!(
+        context.get_balance(commenter.pubkey()).await,
+        commenter_lamports - rent
+    );
+
+    context.add_comment(review_pda, 1, "Second").await;
+    let counter = context
+        .banks_client
+        .get_state::<MovieCommentCounter>(counter_pda)
+        .await;
+    assert_eq!(counter.counter, 2);
+}
+
+#[tokio::test]
+async fn test_add_comment_updates_commenter_profile() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let review_pda = context
+        .add_review("Captain America", 3, "Liked the movie")
+        .await;
+    let first_comment_pda = context.add_comment(review_pda, 0, "First").await;
+    context.add_comment(review_pda, 1, "Second").await;
+
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &payer);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.owner, payer);
+    assert_eq!(profile.comments_created, 2);
+
+    let comment = context.banks_client.get_comment(first_comment_pda).await;
+    assert_eq!(comment.commenter_profile, profile_pda);
+}
+
+#[tokio::test]
+async fn test_update_comment_rejects_after_edit_window() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let review_pda = Pubkey::new_unique();
+
+    // A 15 minute edit window and a comment posted an hour ago
+    let config = ConfigState {
+        discriminator: ConfigState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        admin: payer,
+        treasury: Pubkey::new_unique(),
+        review_fee_lamports: 0,
+        review_fee_usd_cents: 0,
+        price_feed: Pubkey::default(),
+        max_price_age_secs: 0,
+        require_watch_attestation: false,
+        max_comments_per_review: 0,
+        comment_edit_window_secs: 15 * 60,
+        reward_base_amount: 0,
+        reward_halving_interval: 0,
+        reward_halving_unit: RewardHalvingUnit::Reviews,
+        reward_start_slot: 0,
+        rewarded_reviews: 0,
+        vesting_threshold: 0,
+        vesting_duration_secs: 0,
+        referral_bonus: 0,
+        escrow_arbiter: Pubkey::default(),
+        escrow_dispute_window_secs: 0,
+        escrow_resolution_timeout_secs: 0,
+        disabled_features: 0,
+        max_title_len: 0,
+        max_description_len: 0,
+        paused: false,
+        snapshot_interval_secs: 0,
+        comment_reward_amount: 0,
+    };
+    let comment = MovieComment {
+        discriminator: MovieComment::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        review: review_pda,
+        commenter: payer,
+        comment: "Posted an hour ago".to_string(),
+        count: 0,
+        created_at: now() - 3600,
+        commenter_profile: Pubkey::new_unique(),
+    };
+    let (comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+    context.set_state(find_config_address(&program_id).0, &config);
+    context.set_state(comment_pda, &comment);
+
+    assert_eq!(
+        context
+            .process(
+                &[update_comment(
+                    &program_id,
+                    &payer,
+                    &comment_pda,
+                    "Too late"
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::CommentLocked)
+    );
+}
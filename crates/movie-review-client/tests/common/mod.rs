@@ -0,0 +1,465 @@
+//! Setup shared by the integration tests: a local deployment of the program
+//! alongside the SPL programs it invokes, and helpers for the steps most
+//! tests start with.
+
+// Each test binary uses its own subset of these.
+#![allow(dead_code)]
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    movie_review::processor::process_instruction,
+    movie_review_client::{
+        add_comment, add_movie_review,
+        error::ReviewError,
+        find_comment_address, find_config_address, find_program_data_address, find_review_address,
+        find_token_mint_address, initialize_config, initialize_token_mint,
+        state::{
+            self, ConfigState, EntityKind, MovieAccountState, MovieComment, MovieSummary,
+            ReviewStatus, ReviewerProfile, Visibility,
+        },
+        ReviewFeeAccounts,
+    },
+    pyth_sdk_solana::state::{AccountType, PriceAccount, PriceStatus, MAGIC, VERSION_2},
+    solana_program::{
+        borsh::try_from_slice_unchecked,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        hash::Hash,
+        instruction::{Instruction, InstructionError},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        commitment_config::CommitmentLevel,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    std::time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Typed reads of program accounts, so tests assert on state rather than raw
+/// bytes. Each panics if the account does not exist or does not decode.
+pub trait BanksClientExt {
+    async fn get_state<T: BorshDeserialize>(&mut self, address: Pubkey) -> T;
+
+    async fn get_review(&mut self, address: Pubkey) -> MovieAccountState {
+        self.get_state(address).await
+    }
+
+    async fn get_comment(&mut self, address: Pubkey) -> MovieComment {
+        self.get_state(address).await
+    }
+
+    async fn get_profile(&mut self, address: Pubkey) -> ReviewerProfile {
+        self.get_state(address).await
+    }
+
+    async fn get_config(&mut self, address: Pubkey) -> ConfigState {
+        self.get_state(address).await
+    }
+
+    async fn get_summary(&mut self, address: Pubkey) -> MovieSummary {
+        self.get_state(address).await
+    }
+
+    async fn get_token_account(&mut self, address: Pubkey) -> spl_token::state::Account;
+
+    async fn get_token_amount(&mut self, address: Pubkey) -> u64 {
+        self.get_token_account(address).await.amount
+    }
+}
+
+impl BanksClientExt for BanksClient {
+    async fn get_state<T: BorshDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.get_account(address).await.unwrap().unwrap();
+        try_from_slice_unchecked::<T>(&account.data).unwrap()
+    }
+
+    async fn get_token_account(&mut self, address: Pubkey) -> spl_token::state::Account {
+        let account = self.get_account(address).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap()
+    }
+}
+
+/// The error a transaction fails with when its instruction at `index` returns
+/// `error`.
+pub fn review_error(index: u8, error: ReviewError) -> TransactionError {
+    TransactionError::InstructionError(index, InstructionError::Custom(error as u32))
+}
+
+/// A ProgramData account naming `upgrade_authority_address`, as the upgradeable
+/// loader keeps for a deployed program.
+pub fn program_data_account(upgrade_authority_address: Option<Pubkey>) -> Account {
+    Account {
+        lamports: 100_000_000,
+        data: bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address,
+        })
+        .unwrap(),
+        owner: bpf_loader_upgradeable::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A Pyth SOL/USD price account with the given price at exponent -8.
+pub fn pyth_price_account(price: i64, timestamp: i64) -> Account {
+    let mut price_account: PriceAccount = bytemuck::Zeroable::zeroed();
+    price_account.magic = MAGIC;
+    price_account.ver = VERSION_2;
+    price_account.atype = AccountType::Price as u32;
+    price_account.expo = -8;
+    price_account.timestamp = timestamp;
+    price_account.agg.price = price;
+    price_account.agg.status = PriceStatus::Trading;
+
+    Account {
+        lamports: 1_000_000_000,
+        data: bytemuck::bytes_of(&price_account).to_vec(),
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A published, public movie review by `reviewer` in the current layout, for
+/// tests that write review state directly.
+pub fn review_state(
+    reviewer: Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+) -> MovieAccountState {
+    MovieAccountState {
+        discriminator: MovieAccountState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        reviewer,
+        rating,
+        title: title.to_string(),
+        description: description.to_string(),
+        visibility: Visibility::Public,
+        status: ReviewStatus::Published,
+        published_at: 0,
+        publish_at: None,
+        kind: EntityKind::Movie,
+        valid_until: None,
+        upvotes: 0,
+        downvotes: 0,
+        tips_received: 0,
+        created_at: 0,
+        updated_at: 0,
+        bump: 0,
+    }
+}
+
+/// Wall-clock unix time, which the test validator's clock starts from.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+pub struct ReviewTestContext {
+    pub program_id: Pubkey,
+    pub banks_client: BanksClient,
+    /// Pays for every transaction `process` sends.
+    pub payer: Keypair,
+    pub recent_blockhash: Hash,
+    context: ProgramTestContext,
+}
+
+impl ReviewTestContext {
+    /// The program and the SPL programs it invokes, all run natively. Tests
+    /// that need accounts in place at genesis add them before `start`.
+    pub fn program_test(program_id: Pubkey) -> ProgramTest {
+        let mut program_test =
+            ProgramTest::new("movie_review", program_id, processor!(process_instruction));
+        program_test.add_program(
+            "spl_token",
+            spl_token::ID,
+            processor!(spl_token::processor::Processor::process),
+        );
+        program_test.add_program(
+            "spl_associated_token_account",
+            spl_associated_token_account::ID,
+            processor!(spl_associated_token_account::processor::process_instruction),
+        );
+        program_test
+    }
+
+    pub async fn start(program_id: Pubkey, program_test: ProgramTest) -> Self {
+        let context = program_test.start_with_context().await;
+        Self {
+            program_id,
+            banks_client: context.banks_client.clone(),
+            payer: Keypair::from_bytes(&context.payer.to_bytes()).unwrap(),
+            recent_blockhash: context.last_blockhash,
+            context,
+        }
+    }
+
+    /// A fresh deployment with no config, so reviews are free.
+    pub async fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        Self::start(program_id, Self::program_test(program_id)).await
+    }
+
+    /// A fresh deployment upgradeable by `payer`, as a real one would be, so
+    /// it can send `InitializeProgram`.
+    pub async fn with_upgrade_authority() -> Self {
+        let mut context = Self::new().await;
+        context.set_upgrade_authority(Some(context.payer.pubkey()));
+        context
+    }
+
+    /// Replaces the program's ProgramData account with one naming
+    /// `upgrade_authority`.
+    pub fn set_upgrade_authority(&mut self, upgrade_authority: Option<Pubkey>) {
+        let address = find_program_data_address(&self.program_id).0;
+        self.set_account(address, program_data_account(upgrade_authority));
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.context.set_account(&address, &account.into());
+    }
+
+    /// Writes `state` to a program-owned account at `address`, for tests that
+    /// start from state the instructions would not produce.
+    pub fn set_state<T: BorshSerialize>(&mut self, address: Pubkey, state: &T) {
+        self.set_account(
+            address,
+            Account {
+                lamports: 100_000_000,
+                data: state.try_to_vec().unwrap(),
+                owner: self.program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    /// Writes raw `data` to a program-owned account at `address` holding the
+    /// rent-exempt minimum for its size.
+    pub fn set_account_data(&mut self, address: Pubkey, data: Vec<u8>) {
+        self.set_account(
+            address,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: self.program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.context.warp_to_slot(slot).unwrap();
+    }
+
+    /// Moves on to a new blockhash, so a transaction identical to one already
+    /// sent is processed again rather than deduplicated.
+    pub async fn refresh_blockhash(&mut self) {
+        self.recent_blockhash = self
+            .banks_client
+            .get_new_latest_blockhash(&self.recent_blockhash)
+            .await
+            .unwrap();
+    }
+
+    /// `initialize_config` by `payer` with a throwaway treasury and no fees.
+    pub fn initialize_free_config(&self) -> Instruction {
+        initialize_config(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &Pubkey::new_unique(),
+            0,
+            0,
+            &Pubkey::default(),
+            0,
+        )
+    }
+
+    pub fn config_address(&self) -> Pubkey {
+        find_config_address(&self.program_id).0
+    }
+
+    /// Sends `instructions` in one transaction paid for by `payer`, signed
+    /// by `signers` as well.
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers);
+        self.banks_client.process_transaction(transaction).await
+    }
+
+    /// `instructions` in one transaction paid for by `payer`, signed by
+    /// `signers` as well.
+    pub fn transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&self.payer.pubkey()));
+        let mut keypairs = vec![&self.payer];
+        keypairs.extend(signers);
+        transaction.sign(&keypairs, self.recent_blockhash);
+        transaction
+    }
+
+    /// `process`, checking the transaction's lamport flows: across every
+    /// account it names, balances drop by exactly the transaction fee, so
+    /// fees, refunds and closes only move lamports between those accounts.
+    /// Accounts it leaves holding data, other than programs, must stay rent
+    /// exempt.
+    pub async fn process_audited(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers);
+        let keys = transaction.message.account_keys.clone();
+        let fee = self
+            .banks_client
+            .get_fee_for_message_with_commitment_and_context(
+                tarpc::context::current(),
+                CommitmentLevel::Processed,
+                transaction.message.clone(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let before = self.total_lamports(&keys).await;
+
+        let result = self.banks_client.process_transaction(transaction).await;
+
+        // A failed transaction's error can be reported before its fee is
+        // charged, so give the fee a moment to land.
+        let mut after = self.total_lamports(&keys).await;
+        for _ in 0..100 {
+            if before - after >= fee {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            after = self.total_lamports(&keys).await;
+        }
+        assert_eq!(before - after, fee, "lamports were created or destroyed");
+
+        let rent = self.banks_client.get_rent().await.unwrap();
+        for key in &keys {
+            if let Some(account) = self.banks_client.get_account(*key).await.unwrap() {
+                assert!(
+                    account.executable
+                        || account.data.is_empty()
+                        || rent.is_exempt(account.lamports, account.data.len()),
+                    "{} is not rent exempt",
+                    key
+                );
+            }
+        }
+        result
+    }
+
+    async fn total_lamports(&mut self, keys: &[Pubkey]) -> u64 {
+        let mut total = 0;
+        for key in keys {
+            total += self
+                .banks_client
+                .get_balance_with_commitment(*key, CommitmentLevel::Processed)
+                .await
+                .unwrap();
+        }
+        total
+    }
+
+    /// A new keypair holding `lamports` from `payer`.
+    pub async fn funded_keypair(&mut self, lamports: u64) -> Keypair {
+        let keypair = Keypair::new();
+        self.process(
+            &[system_instruction::transfer(
+                &self.payer.pubkey(),
+                &keypair.pubkey(),
+                lamports,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        keypair
+    }
+
+    /// Creates the reward token mint without the rest of `InitializeProgram`,
+    /// returning its address.
+    pub async fn create_mint(&mut self) -> Pubkey {
+        self.process(
+            &[initialize_token_mint(
+                &self.program_id,
+                &self.payer.pubkey(),
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        find_token_mint_address(&self.program_id).0
+    }
+
+    /// Adds `payer`'s review of `title` with no fee accounts, returning the
+    /// review's address.
+    pub async fn add_review(&mut self, title: &str, rating: u8, description: &str) -> Pubkey {
+        self.process(
+            &[add_movie_review(
+                &self.program_id,
+                &self.payer.pubkey(),
+                title,
+                rating,
+                description,
+                ReviewFeeAccounts::default(),
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        find_review_address(&self.program_id, &self.payer.pubkey(), title).0
+    }
+
+    /// Adds `payer`'s comment on the review at `review`, which must have
+    /// `count` comments so far, returning the comment's address.
+    pub async fn add_comment(&mut self, review: Pubkey, count: u64, comment: &str) -> Pubkey {
+        self.process(
+            &[add_comment(
+                &self.program_id,
+                &self.payer.pubkey(),
+                &review,
+                count,
+                comment,
+                None,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        find_comment_address(&self.program_id, &review, count).0
+    }
+
+    pub async fn get_balance(&mut self, address: Pubkey) -> u64 {
+        self.banks_client.get_balance(address).await.unwrap()
+    }
+
+    pub async fn get_account(&mut self, address: Pubkey) -> Option<Account> {
+        self.banks_client.get_account(address).await.unwrap()
+    }
+
+    /// Asserts the account at `address` was closed and removed by the
+    /// runtime.
+    pub async fn assert_closed(&mut self, address: Pubkey) {
+        assert!(
+            self.get_account(address).await.is_none(),
+            "{} is still open",
+            address
+        );
+    }
+}
@@ -0,0 +1,225 @@
+mod common;
+
+use {
+    common::{review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        close_escrow, dispute_escrow,
+        error::ReviewError,
+        find_escrow_address, fund_escrow, resolve_escrow, set_escrow_policy, settle_escrow,
+        state::{EscrowState, EscrowStatus},
+    },
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_program_test::tokio,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+/// Starts a program with an initialized config whose admin is the payer.
+async fn start_with_config() -> ReviewTestContext {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    context
+        .process(&[context.initialize_free_config()], &[])
+        .await
+        .unwrap();
+    context
+}
+
+#[tokio::test]
+async fn test_disputed_escrow_is_resolved_by_arbiter() {
+    let mut context = start_with_config().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let arbiter = Keypair::new();
+    let recipient = Pubkey::new_unique();
+    let amount = 500_000_000;
+
+    // No escrows until the admin names an arbiter.
+    let (escrow_pda, _bump_seed) = find_escrow_address(&program_id, &payer, &recipient, 0);
+    let fund_ix = fund_escrow(&program_id, &payer, &recipient, 0, amount);
+    assert_eq!(
+        context
+            .process_audited(std::slice::from_ref(&fund_ix), &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::EscrowNotConfigured)
+    );
+
+    context
+        .process_audited(
+            &[
+                set_escrow_policy(&program_id, &payer, &arbiter.pubkey(), 3600, 3600),
+                fund_ix,
+                system_instruction::transfer(&payer, &arbiter.pubkey(), 1_000_000_000),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let account = context.get_account(escrow_pda).await.unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        account.lamports,
+        rent.minimum_balance(EscrowState::SIZE) + amount
+    );
+    let escrow = context
+        .banks_client
+        .get_state::<EscrowState>(escrow_pda)
+        .await;
+    assert_eq!(escrow.status, EscrowStatus::Funded);
+    assert_eq!(escrow.amount, amount);
+
+    // Funds stay put during the dispute window.
+    assert_eq!(
+        context
+            .process_audited(&[settle_escrow(&program_id, &escrow_pda, &recipient)], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::EscrowLocked)
+    );
+
+    // Only the funder can dispute, and only the arbiter can resolve.
+    assert_eq!(
+        context
+            .process_audited(
+                &[dispute_escrow(&program_id, &arbiter.pubkey(), &escrow_pda)],
+                &[&arbiter],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAuthority)
+    );
+
+    assert_eq!(
+        context
+            .process_audited(
+                &[
+                    dispute_escrow(&program_id, &payer, &escrow_pda),
+                    resolve_escrow(&program_id, &payer, &escrow_pda, &recipient, true),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::InvalidAuthority)
+    );
+
+    context
+        .process_audited(
+            &[
+                dispute_escrow(&program_id, &payer, &escrow_pda),
+                resolve_escrow(
+                    &program_id,
+                    &arbiter.pubkey(),
+                    &escrow_pda,
+                    &recipient,
+                    true,
+                ),
+            ],
+            &[&arbiter],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(context.get_balance(recipient).await, amount);
+    let account = context.get_account(escrow_pda).await.unwrap();
+    assert_eq!(account.lamports, rent.minimum_balance(EscrowState::SIZE));
+    let escrow = context
+        .banks_client
+        .get_state::<EscrowState>(escrow_pda)
+        .await;
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.paid_to, recipient);
+
+    // A resolved escrow can't pay out twice, only be closed.
+    assert_eq!(
+        context
+            .process_audited(&[settle_escrow(&program_id, &escrow_pda, &payer)], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidEscrowStatus)
+    );
+
+    context
+        .process_audited(&[close_escrow(&program_id, &payer, &escrow_pda)], &[])
+        .await
+        .unwrap();
+    context.assert_closed(escrow_pda).await;
+}
+
+#[tokio::test]
+async fn test_escrow_timeouts() {
+    let mut context = start_with_config().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let arbiter = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let amount = 500_000_000;
+
+    // With no dispute window the escrow can't be disputed and settles to
+    // the recipient straight away.
+    let (escrow_pda, _bump_seed) = find_escrow_address(&program_id, &payer, &recipient, 0);
+    assert_eq!(
+        context
+            .process_audited(
+                &[
+                    set_escrow_policy(&program_id, &payer, &arbiter, 0, 0),
+                    fund_escrow(&program_id, &payer, &recipient, 0, amount),
+                    dispute_escrow(&program_id, &payer, &escrow_pda),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(2, ReviewError::DisputeWindowClosed)
+    );
+
+    context
+        .process_audited(
+            &[
+                set_escrow_policy(&program_id, &payer, &arbiter, 0, 0),
+                fund_escrow(&program_id, &payer, &recipient, 0, amount),
+                settle_escrow(&program_id, &escrow_pda, &recipient),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(context.get_balance(recipient).await, amount);
+
+    // A dispute the arbiter lets time out is refunded to the funder.
+    let (escrow_pda, _bump_seed) = find_escrow_address(&program_id, &payer, &recipient, 1);
+    context
+        .process_audited(
+            &[
+                set_escrow_policy(&program_id, &payer, &arbiter, 3600, 0),
+                fund_escrow(&program_id, &payer, &recipient, 1, amount),
+                dispute_escrow(&program_id, &payer, &escrow_pda),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let payer_lamports = context.get_balance(payer).await;
+    context
+        .process_audited(&[settle_escrow(&program_id, &escrow_pda, &payer)], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context.get_balance(payer).await,
+        payer_lamports + amount - 5000
+    );
+    let escrow = context
+        .banks_client
+        .get_state::<EscrowState>(escrow_pda)
+        .await;
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.paid_to, payer);
+}
@@ -0,0 +1,581 @@
+mod common;
+
+use {
+    assert_matches::*,
+    borsh::BorshSerialize,
+    common::{BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        add_comment, add_movie_review, claim_rewards, compute_units, decode_events,
+        decode_transaction, delete_movie_review,
+        events::{Event, ReviewAdded, ReviewUpdated},
+        find_comment_address, find_comment_counter_address, find_config_address,
+        find_mint_authority_address, find_profile_address, find_review_address,
+        find_summary_address, find_token_mint_address, flows, initialize_program,
+        initialize_reviewer_profile,
+        instruction::{MovieInstruction, INSTRUCTION_VERSION},
+        purge_expired, reclaim_orphans, set_reward_schedule,
+        state::{ConfigState, EntityKind, MovieCommentCounter, ReviewStatus, RewardHalvingUnit},
+        update_movie_review, versioned, with_compute_unit_limit, ReviewFeeAccounts,
+        OTHER_PROGRAM_UNITS,
+    },
+    solana_program::{
+        instruction::Instruction, message::Message, program_error::ProgramError,
+        program_option::COption, program_pack::Pack, pubkey::Pubkey, system_instruction,
+    },
+    solana_program_test::tokio,
+    solana_sdk::{compute_budget::ComputeBudgetInstruction, signature::Signer},
+    spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account_idempotent,
+    },
+};
+
+#[tokio::test]
+async fn test_add_and_update_review_with_client_builders() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let reviewer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+    assert_matches!(
+        context
+            .process(
+                &[update_movie_review(
+                    &program_id,
+                    &reviewer,
+                    title,
+                    5,
+                    "Loved the movie"
+                )],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    assert_eq!(
+        review_pda,
+        find_review_address(&program_id, &reviewer, title).0
+    );
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.reviewer, reviewer);
+    assert_eq!(review.title, title);
+    assert_eq!(review.rating, 50);
+    assert_eq!(review.description, "Loved the movie");
+}
+
+#[tokio::test]
+async fn test_handlers_log_progress() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let reviewer = context.payer.pubkey();
+
+    let transaction = context.transaction(
+        &[add_movie_review(
+            &program_id,
+            &reviewer,
+            "Captain America",
+            3,
+            "Liked the movie",
+            ReviewFeeAccounts::default(),
+        )],
+        &[],
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_matches!(simulation.result, Some(Ok(())));
+    let logs = simulation.simulation_details.unwrap().logs;
+    assert!(logs.contains(&"Program log: Adding movie review...".to_string()));
+}
+
+#[test]
+fn test_decode_transaction_resolves_account_roles() {
+    let program_id = Pubkey::new_unique();
+    let reviewer = Pubkey::new_unique();
+    let title = "Captain America";
+    let message = Message::new(
+        &[
+            system_instruction::transfer(&reviewer, &Pubkey::new_unique(), 1),
+            versioned(add_movie_review(
+                &program_id,
+                &reviewer,
+                title,
+                3,
+                "Liked the movie",
+                ReviewFeeAccounts {
+                    reward: true,
+                    ..ReviewFeeAccounts::default()
+                },
+            )),
+            Instruction::new_with_bytes(program_id, &[200], vec![]),
+        ],
+        Some(&reviewer),
+    );
+
+    let decoded = decode_transaction(&program_id, &message);
+    assert_eq!(decoded.len(), 2);
+
+    let review = &decoded[0];
+    assert_eq!(review.index, 1);
+    assert_eq!(review.version, INSTRUCTION_VERSION);
+    assert_eq!(
+        review.instruction,
+        Ok(MovieInstruction::AddMovieReview {
+            title: title.to_string(),
+            rating: 3,
+            description: "Liked the movie".to_string(),
+        })
+    );
+    let roles: Vec<_> = review.accounts.iter().map(|account| account.role).collect();
+    assert_eq!(
+        roles,
+        [
+            Some("reviewer"),
+            Some("review"),
+            Some("system_program"),
+            Some("config"),
+            Some("summary"),
+            None
+        ]
+    );
+    assert_eq!(review.accounts[0].pubkey, reviewer);
+    assert!(review.accounts[0].is_signer && review.accounts[0].is_writable);
+    assert_eq!(
+        review.accounts[1].pubkey,
+        find_review_address(&program_id, &reviewer, title).0
+    );
+    assert!(!review.accounts[1].is_signer && review.accounts[1].is_writable);
+    assert_eq!(
+        review.accounts[4].pubkey,
+        find_summary_address(&program_id, EntityKind::Movie, title).0
+    );
+    assert_eq!(
+        review.accounts[5].pubkey,
+        find_profile_address(&program_id, &reviewer).0
+    );
+
+    assert_eq!(decoded[1].index, 2);
+    assert_eq!(
+        decoded[1].instruction,
+        Err(ProgramError::InvalidInstructionData)
+    );
+    assert!(decoded[1].accounts.is_empty());
+}
+
+#[test]
+fn test_decode_events_skips_other_programs() {
+    let program_id = Pubkey::new_unique();
+    let other_program = Pubkey::new_unique();
+    let reviewer = Pubkey::new_unique();
+    let review = find_review_address(&program_id, &reviewer, "Captain America").0;
+    let added = Event::ReviewAdded(ReviewAdded {
+        review,
+        reviewer,
+        kind: EntityKind::Movie,
+        status: ReviewStatus::Published,
+        title: "Captain America".to_string(),
+        rating: 3,
+        description: "Liked the movie".to_string(),
+    });
+    let updated = Event::ReviewUpdated(ReviewUpdated {
+        review,
+        reviewer,
+        rating: 5,
+        description: "Loved the movie".to_string(),
+    });
+    let data = |event: &Event| {
+        format!(
+            "Program data: {}",
+            base64::encode(event.try_to_vec().unwrap())
+        )
+    };
+
+    let logs = [
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Adding movie review...".to_string(),
+        data(&added),
+        format!("Program {} invoke [2]", other_program),
+        data(&updated),
+        format!("Program {} success", other_program),
+        "Program data: bm90IGFuIGV2ZW50".to_string(),
+        format!(
+            "Program {} consumed 1000 of 200000 compute units",
+            program_id
+        ),
+        format!("Program {} success", program_id),
+        format!("Program {} invoke [1]", other_program),
+        data(&updated),
+        format!("Program {} success", other_program),
+        format!("Program {} invoke [1]", program_id),
+        data(&updated),
+        format!("Program {} failed: custom program error: 0x0", program_id),
+    ];
+
+    assert_eq!(decode_events(&program_id, &logs), [added, updated]);
+}
+
+#[test]
+fn test_compute_unit_limit_covers_every_instruction() {
+    let program_id = Pubkey::new_unique();
+    let reviewer = Pubkey::new_unique();
+    let (review, _bump_seed) = find_review_address(&program_id, &reviewer, "Captain America");
+    let instructions = [
+        system_instruction::transfer(&reviewer, &Pubkey::new_unique(), 1),
+        add_movie_review(
+            &program_id,
+            &reviewer,
+            "Captain America",
+            3,
+            "Liked the movie",
+            ReviewFeeAccounts {
+                reward: true,
+                ..ReviewFeeAccounts::default()
+            },
+        ),
+        add_comment(&program_id, &reviewer, &review, 0, "First!", None),
+    ];
+
+    let with_limit = with_compute_unit_limit(&program_id, &instructions);
+    assert_eq!(with_limit[1..], instructions);
+    assert_eq!(
+        with_limit[0],
+        ComputeBudgetInstruction::set_compute_unit_limit(
+            OTHER_PROGRAM_UNITS
+                + compute_units::ADD_MOVIE_REVIEW
+                + compute_units::PER_EXTRA_ACCOUNT
+                + compute_units::ADD_COMMENT
+                + compute_units::FEATURE_CHECK
+        )
+    );
+
+    // An explicit budget is left alone.
+    assert_eq!(
+        with_compute_unit_limit(&program_id, &with_limit),
+        with_limit
+    );
+}
+
+#[test]
+fn test_variable_accounts_are_passed_in_key_order() {
+    let program_id = Pubkey::new_unique();
+    let caller = Pubkey::new_unique();
+    let keys = |instruction: &Instruction, skip: usize| -> Vec<Pubkey> {
+        instruction.accounts[skip..]
+            .iter()
+            .map(|account| account.pubkey)
+            .collect()
+    };
+
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let purge = purge_expired(&program_id, &caller, &caller, &[second, first, second]);
+    assert_eq!(keys(&purge, 3), [first, second]);
+
+    let mentioned = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let comment = format!("@{} @{}", mentioned[0], mentioned[1]);
+    let comment_ix = add_comment(&program_id, &caller, &first, 0, &comment, None);
+    let extras = keys(&comment_ix, 7);
+    assert_eq!(extras.len(), 2);
+    assert!(extras[0] < extras[1]);
+
+    // Comments follow their counter in index order, whatever its position.
+    let (counter, _) = find_comment_counter_address(&program_id, &first);
+    let orphans = [Pubkey::new_unique(), counter, Pubkey::new_unique()];
+    let reclaim = reclaim_orphans(&program_id, &caller, &caller, &first, &orphans, 2);
+    let mut expected = orphans.to_vec();
+    expected.sort();
+    let at = expected.iter().position(|key| *key == counter).unwrap() + 1;
+    expected.splice(
+        at..at,
+        (0..2).map(|count| find_comment_address(&program_id, &first, count).0),
+    );
+    assert_eq!(keys(&reclaim, 4), expected);
+}
+
+#[tokio::test]
+async fn test_first_review_and_tip_and_comment_flows() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let title = "Captain America";
+
+    // A fresh deployment: no profile, no reward mint, free reviews.
+    let instructions = flows::first_review(
+        &program_id,
+        &payer,
+        title,
+        4,
+        "Liked the movie",
+        None,
+        Some(Pubkey::new_unique()),
+        |_| false,
+    );
+    assert_eq!(instructions.len(), 3);
+    assert_matches!(context.process(&instructions, &[]).await, Ok(_));
+
+    let (profile, _bump_seed) = find_profile_address(&program_id, &payer);
+    assert!(context
+        .banks_client
+        .get_account(profile)
+        .await
+        .unwrap()
+        .is_some());
+    let (review, _bump_seed) = find_review_address(&program_id, &payer, title);
+    assert!(context
+        .banks_client
+        .get_account(review)
+        .await
+        .unwrap()
+        .is_some());
+
+    let fan = context.funded_keypair(100_000_000).await;
+    let instructions = flows::tip_and_comment(
+        &program_id,
+        &fan.pubkey(),
+        &payer,
+        &review,
+        0,
+        1_000_000,
+        "Great review!",
+        |_| false,
+    );
+    assert_matches!(context.process(&instructions, &[&fan]).await, Ok(_));
+
+    let (comment, _bump_seed) = find_comment_address(&program_id, &review, 0);
+    let comment = context.banks_client.get_comment(comment).await;
+    assert_eq!(comment.commenter, fan.pubkey());
+    assert_eq!(comment.comment, "Great review!");
+    let review = context.banks_client.get_review(review).await;
+    assert_eq!(review.tips_received, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_initialize_token_mint_with_client_builder() {
+    let mut context = ReviewTestContext::new().await;
+
+    let mint = context.create_mint().await;
+    let account = context
+        .banks_client
+        .get_account(mint)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, spl_token::ID);
+    let mint = spl_token::state::Mint::unpack(&account.data).unwrap();
+    assert_eq!(
+        mint.mint_authority,
+        COption::Some(find_mint_authority_address(&context.program_id).0)
+    );
+    assert_eq!(mint.supply, 0);
+}
+
+/// Walks one review through its whole life on a single deployment, checking
+/// state, lamports and token balances after each step. Deployment-wide
+/// changes should keep this passing.
+#[tokio::test]
+async fn test_review_lifecycle() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let reviewer = context.funded_keypair(100_000_000).await;
+    let fan = context.funded_keypair(100_000_000).await;
+    let title = "Captain America";
+
+    // Bootstrap: free reviews, 10 reward tokens each.
+    assert_matches!(
+        context
+            .process(
+                &[
+                    initialize_program(&program_id, &payer, 0, 0, &Pubkey::default(), 0),
+                    set_reward_schedule(&program_id, &payer, 10, 0, RewardHalvingUnit::Slots),
+                ],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let (config_pda, _bump_seed) = find_config_address(&program_id);
+    let config = context
+        .banks_client
+        .get_state::<ConfigState>(config_pda)
+        .await;
+    assert_eq!(config.admin, payer);
+    assert_eq!(config.reward_base_amount, 10);
+
+    // Profile and reward token account.
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    assert_matches!(
+        context
+            .process(
+                &[
+                    initialize_reviewer_profile(&program_id, &reviewer.pubkey()),
+                    create_associated_token_account_idempotent(
+                        &payer,
+                        &reviewer.pubkey(),
+                        &mint,
+                        &spl_token::ID,
+                    ),
+                ],
+                &[&reviewer],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &reviewer.pubkey());
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.owner, reviewer.pubkey());
+    assert_eq!(profile.reviews_published, 0);
+
+    // Add the review; the reviewer pays its rent and the title summary's, and
+    // earns the reward.
+    let reviewer_lamports = context.get_balance(reviewer.pubkey()).await;
+    assert_matches!(
+        context
+            .process(
+                &[add_movie_review(
+                    &program_id,
+                    &reviewer.pubkey(),
+                    title,
+                    3,
+                    "Liked the movie",
+                    flows::review_fee_accounts(Some(&config)),
+                )],
+                &[&reviewer],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &reviewer.pubkey(), title);
+    let review_lamports = context.get_balance(review_pda).await;
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+    let summary_lamports = context.get_balance(summary_pda).await;
+    assert_eq!(
+        context.get_balance(reviewer.pubkey()).await,
+        reviewer_lamports - review_lamports - summary_lamports
+    );
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.reviewer, reviewer.pubkey());
+    assert_eq!(review.rating, 30);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.reviews_published, 1);
+    assert_eq!(profile.pending_rewards, 10);
+
+    // Claim the reward.
+    assert_matches!(
+        context
+            .process(
+                &[claim_rewards(&program_id, &reviewer.pubkey())],
+                &[&reviewer]
+            )
+            .await,
+        Ok(_)
+    );
+
+    let token_account = context
+        .banks_client
+        .get_account(get_associated_token_address(&reviewer.pubkey(), &mint))
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&token_account.data).unwrap();
+    assert_eq!(token_account.amount, 10);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.pending_rewards, 0);
+    assert_eq!(profile.rewards_claimed, 10);
+
+    // A fan tips the reviewer and comments, then the reviewer replies.
+    let reviewer_lamports = context.get_balance(reviewer.pubkey()).await;
+    let mut instructions = flows::tip_and_comment(
+        &program_id,
+        &fan.pubkey(),
+        &reviewer.pubkey(),
+        &review_pda,
+        0,
+        1_000_000,
+        "Great review!",
+        |_| false,
+    );
+    instructions.push(add_comment(
+        &program_id,
+        &reviewer.pubkey(),
+        &review_pda,
+        1,
+        "Thanks!",
+        None,
+    ));
+    assert_matches!(
+        context.process(&instructions, &[&fan, &reviewer]).await,
+        Ok(_)
+    );
+
+    let (second_comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 1);
+    let second_comment_lamports = context.get_balance(second_comment_pda).await;
+    assert_eq!(
+        context.get_balance(reviewer.pubkey()).await,
+        reviewer_lamports + 1_000_000 - second_comment_lamports
+    );
+    let (counter_pda, _bump_seed) = find_comment_counter_address(&program_id, &review_pda);
+    let counter = context
+        .banks_client
+        .get_state::<MovieCommentCounter>(counter_pda)
+        .await;
+    assert_eq!(counter.counter, 2);
+    let (first_comment_pda, _bump_seed) = find_comment_address(&program_id, &review_pda, 0);
+    let comment = context.banks_client.get_comment(first_comment_pda).await;
+    assert_eq!(comment.commenter, fan.pubkey());
+    assert_eq!(comment.comment, "Great review!");
+    let comment = context.banks_client.get_comment(second_comment_pda).await;
+    assert_eq!(comment.commenter, reviewer.pubkey());
+
+    // Update the review.
+    assert_matches!(
+        context
+            .process(
+                &[update_movie_review(
+                    &program_id,
+                    &reviewer.pubkey(),
+                    title,
+                    5,
+                    "Loved the movie",
+                )],
+                &[&reviewer],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.rating, 50);
+    assert_eq!(review.description, "Loved the movie");
+
+    // Delete it; the rent goes back to the reviewer.
+    let reviewer_lamports = context.get_balance(reviewer.pubkey()).await;
+    let review_lamports = context.get_balance(review_pda).await;
+    assert_matches!(
+        context
+            .process(
+                &[delete_movie_review(&program_id, &reviewer.pubkey(), title)],
+                &[&reviewer],
+            )
+            .await,
+        Ok(_)
+    );
+
+    assert!(context
+        .banks_client
+        .get_account(review_pda)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        context.get_balance(reviewer.pubkey()).await,
+        reviewer_lamports + review_lamports
+    );
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.rewards_claimed, 10);
+}
@@ -0,0 +1,471 @@
+mod common;
+
+use {
+    common::{now, review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        add_comment, add_draft_review, add_movie_review, archive_review, attest_watched,
+        error::ReviewError,
+        expire_review, find_profile_address, find_review_address, find_watch_attestation_address,
+        publish_review, schedule_publish, set_review_expiry, set_reward_schedule,
+        set_watch_requirement,
+        state::{EntityKind, ReviewStatus, RewardHalvingUnit, WatchAttestation},
+        update_movie_review, ReviewFeeAccounts,
+    },
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_instruction},
+    solana_program_test::tokio,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn test_draft_review_publish() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    context
+        .process(
+            &[add_draft_review(
+                &program_id,
+                &payer,
+                title,
+                3,
+                "Work in progress",
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.status, ReviewStatus::Draft);
+    assert_eq!(review.published_at, 0);
+    assert!(!review.is_listed(now()));
+
+    context
+        .process(&[publish_review(&program_id, &payer, title, false)], &[])
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.status, ReviewStatus::Published);
+    assert!(review.published_at > 0);
+    assert!(review.is_listed(now()));
+
+    // Publishing again must not restamp published_at
+    assert_eq!(
+        context
+            .process(
+                &[
+                    publish_review(&program_id, &payer, title, false),
+                    system_instruction::transfer(&payer, &Pubkey::new_unique(), 1_000_000),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AlreadyPublished)
+    );
+}
+
+#[tokio::test]
+async fn test_scheduled_publish_is_permissionless_after_publish_at() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let keeper = Keypair::new();
+
+    let title = "Captain America";
+    context
+        .process(
+            &[add_draft_review(&program_id, &payer, title, 3, "Embargoed")],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let mut keeper_publish_ix = publish_review(&program_id, &payer, title, false);
+    keeper_publish_ix.accounts[0] = AccountMeta::new_readonly(keeper.pubkey(), true);
+
+    // Unscheduled drafts can only be published by the reviewer
+    assert_eq!(
+        context
+            .process(&[keeper_publish_ix.clone()], &[&keeper])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::UnauthorizedReviewer)
+    );
+
+    // Before the embargo lifts the keeper is turned away
+    let future = now() + 3600;
+    assert_eq!(
+        context
+            .process(
+                &[
+                    schedule_publish(&program_id, &payer, title, Some(future)),
+                    keeper_publish_ix.clone(),
+                ],
+                &[&keeper],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::PublishTimeNotReached)
+    );
+
+    // Once it has passed anyone can release the review
+    context
+        .process(
+            &[
+                schedule_publish(&program_id, &payer, title, Some(1)),
+                keeper_publish_ix,
+            ],
+            &[&keeper],
+        )
+        .await
+        .unwrap();
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.status, ReviewStatus::Published);
+    assert_eq!(review.reviewer, payer);
+    assert_eq!(review.publish_at, None);
+    assert!(review.published_at > 0);
+}
+
+#[tokio::test]
+async fn test_keeper_publish_rewards_the_reviewer() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let keeper = context.funded_keypair(1_000_000_000).await;
+
+    let title = "Captain America";
+    context
+        .process(
+            &[
+                context.initialize_free_config(),
+                set_reward_schedule(&program_id, &payer, 100, 0, RewardHalvingUnit::Reviews),
+                add_draft_review(&program_id, &payer, title, 3, "Embargoed"),
+                schedule_publish(&program_id, &payer, title, Some(1)),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let mut keeper_publish_ix = publish_review(&program_id, &payer, title, true);
+    keeper_publish_ix.accounts[0] = AccountMeta::new(keeper.pubkey(), true);
+
+    // The keeper can't redirect the reward to their own profile.
+    let (keeper_profile_pda, _bump_seed) = find_profile_address(&program_id, &keeper.pubkey());
+    let mut redirected_ix = keeper_publish_ix.clone();
+    redirected_ix.accounts[5] = AccountMeta::new(keeper_profile_pda, false);
+    assert_eq!(
+        context
+            .process(&[redirected_ix], &[&keeper])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidPDA)
+    );
+
+    context
+        .process(&[keeper_publish_ix], &[&keeper])
+        .await
+        .unwrap();
+
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &payer);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.owner, payer);
+    assert_eq!(profile.pending_rewards, 100);
+    assert_eq!(profile.reviews_published, 1);
+    assert!(context.get_account(keeper_profile_pda).await.is_none());
+}
+
+#[tokio::test]
+async fn test_expire_review_after_valid_until() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let add_review_ix = add_movie_review(
+        &program_id,
+        &payer,
+        title,
+        3,
+        "Premiere",
+        ReviewFeeAccounts::default(),
+    );
+    let expire_ix = expire_review(&program_id, EntityKind::Movie, &payer, title, false);
+    assert_eq!(
+        context
+            .process_audited(
+                &[
+                    add_review_ix.clone(),
+                    set_review_expiry(&program_id, &payer, title, Some(now() + 3600)),
+                    expire_ix.clone(),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(2, ReviewError::ReviewNotExpired)
+    );
+
+    context
+        .process_audited(
+            &[
+                add_review_ix,
+                set_review_expiry(&program_id, &payer, title, Some(1)),
+                expire_ix,
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let review_lamports = context.get_balance(review_pda).await;
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.status, ReviewStatus::Expired);
+    assert_eq!(review.valid_until, Some(1));
+    assert!(!review.is_listed(now()));
+
+    // Expired reviews can't be published again, only purged.
+    assert_eq!(
+        context
+            .process_audited(&[publish_review(&program_id, &payer, title, false)], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::ReviewExpired)
+    );
+
+    let payer_lamports = context.get_balance(payer).await;
+    context
+        .process_audited(
+            &[expire_review(
+                &program_id,
+                EntityKind::Movie,
+                &payer,
+                title,
+                true,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    context.assert_closed(review_pda).await;
+    assert_eq!(
+        context.get_balance(payer).await,
+        payer_lamports + review_lamports - 5000
+    );
+}
+
+#[tokio::test]
+async fn test_archive_review_freezes_it() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = Keypair::new();
+
+    let title = "Captain America";
+    context
+        .process(&[context.initialize_free_config()], &[])
+        .await
+        .unwrap();
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+
+    // Only the config admin can archive.
+    assert_eq!(
+        context
+            .process(
+                &[archive_review(
+                    &program_id,
+                    &other.pubkey(),
+                    EntityKind::Movie,
+                    &payer,
+                    title,
+                )],
+                &[&other],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidAuthority)
+    );
+
+    context
+        .process(
+            &[archive_review(
+                &program_id,
+                &payer,
+                EntityKind::Movie,
+                &payer,
+                title,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.status, ReviewStatus::Archived);
+    assert_eq!(review.description, "Liked the movie");
+
+    for instruction in [
+        update_movie_review(&program_id, &payer, title, 4, "Liked it"),
+        add_comment(&program_id, &payer, &review_pda, 0, "Great review", None),
+    ] {
+        assert_eq!(
+            context
+                .process(&[instruction], &[])
+                .await
+                .unwrap_err()
+                .unwrap(),
+            review_error(0, ReviewError::ReviewArchived)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_add_movie_review_requires_watch_attestation() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    context
+        .process(
+            &[
+                context.initialize_free_config(),
+                set_watch_requirement(&program_id, &payer, true),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let title = "Captain America";
+    let add_review_ix = add_movie_review(
+        &program_id,
+        &payer,
+        title,
+        3,
+        "Liked the movie",
+        ReviewFeeAccounts {
+            watch_attestation: true,
+            ..ReviewFeeAccounts::default()
+        },
+    );
+
+    // The attestation account is passed but was never created
+    assert_eq!(
+        context
+            .process(std::slice::from_ref(&add_review_ix), &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::WatchAttestationRequired)
+    );
+
+    context
+        .process(
+            &[
+                attest_watched(&program_id, &payer, title, now() - 3600),
+                add_review_ix,
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (attestation_pda, _bump_seed) = find_watch_attestation_address(&program_id, &payer, title);
+    let attestation = context
+        .banks_client
+        .get_state::<WatchAttestation>(attestation_pda)
+        .await;
+    assert_eq!(attestation.viewer, payer);
+    assert_eq!(attestation.movie_id, title);
+}
+
+#[tokio::test]
+async fn test_watch_attestation_for_a_title_longer_than_a_seed() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    context
+        .process(
+            &[
+                context.initialize_free_config(),
+                set_watch_requirement(&program_id, &payer, true),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let title = "The Lord of the Rings: The Fellowship of the Ring (Extended)";
+    context
+        .process(
+            &[
+                attest_watched(&program_id, &payer, title, now() - 3600),
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    5,
+                    "Worth every minute",
+                    ReviewFeeAccounts {
+                        watch_attestation: true,
+                        ..ReviewFeeAccounts::default()
+                    },
+                ),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (attestation_pda, _bump_seed) = find_watch_attestation_address(&program_id, &payer, title);
+    let attestation = context
+        .banks_client
+        .get_state::<WatchAttestation>(attestation_pda)
+        .await;
+    assert_eq!(attestation.movie_id, title);
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.title, title);
+}
+
+#[tokio::test]
+async fn test_attest_watched_rejects_future_timestamp() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    assert_eq!(
+        context
+            .process(
+                &[attest_watched(
+                    &program_id,
+                    &payer,
+                    "Captain America",
+                    now() + 86_400,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidTimestamp)
+    );
+}
@@ -0,0 +1,230 @@
+mod common;
+
+use {
+    borsh::BorshSerialize,
+    common::{review_error, review_state, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        delete_movie_review,
+        error::ReviewError,
+        find_review_address, find_summary_address, migrate_legacy_review, migrate_review,
+        state::{self, EntityKind, MovieAccountState},
+        update_movie_review, vote_on_review,
+    },
+    solana_program::instruction::AccountMeta,
+    solana_program_test::tokio,
+    solana_sdk::signature::Signer,
+};
+
+#[tokio::test]
+async fn test_migrate_legacy_review_shrinks_and_refunds() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+
+    // Reviews used to be created with a fixed 1000 bytes
+    let legacy = MovieAccountState {
+        version: 1,
+        ..review_state(payer, title, 3, "Liked the movie")
+    };
+    let mut data = legacy.try_to_vec().unwrap();
+    data.resize(1000, 0);
+    context.set_account_data(review_pda, data);
+
+    let review_lamports = context.get_balance(review_pda).await;
+    let payer_lamports = context.get_balance(payer).await;
+
+    context
+        .process_audited(
+            &[migrate_legacy_review(&program_id, &review_pda, &payer)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let size =
+        MovieAccountState::get_account_size(title.to_string(), "Liked the movie".to_string())
+            .unwrap();
+    let account = context.get_account(review_pda).await.unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.data.len(), size);
+    assert_eq!(account.lamports, rent.minimum_balance(size));
+    assert_eq!(
+        context.get_balance(payer).await,
+        payer_lamports + review_lamports - account.lamports - 5000
+    );
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.title, title);
+    assert_eq!(review.description, "Liked the movie");
+    assert_eq!(review.rating_tenths(), 30);
+
+    // A longer description grows the migrated account again.
+    let description = "Liked the movie a lot more the second time around";
+    let update_ix = update_movie_review(&program_id, &payer, title, 4, description);
+    assert_eq!(
+        context
+            .process_audited(
+                &[
+                    update_ix.clone(),
+                    migrate_legacy_review(&program_id, &review_pda, &payer),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::AlreadyMigrated)
+    );
+
+    context.process_audited(&[update_ix], &[]).await.unwrap();
+
+    let account = context.get_account(review_pda).await.unwrap();
+    assert_eq!(
+        account.data.len(),
+        MovieAccountState::get_account_size(title.to_string(), description.to_string()).unwrap()
+    );
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.description, description);
+    // The legacy whole-star rating is replaced by one in tenths.
+    assert_eq!(review.rating, 40);
+}
+
+#[tokio::test]
+async fn test_migrate_review_upgrades_version_1() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = context.funded_keypair(1_000_000_000).await;
+    let title = "Captain America";
+    let (review_pda, bump_seed) = find_review_address(&program_id, &payer, title);
+
+    // Written before state versions, sized to its contents.
+    let review = MovieAccountState {
+        version: 1,
+        ..review_state(payer, title, 30, "Liked the movie")
+    };
+    let size =
+        MovieAccountState::get_account_size(title.to_string(), "Liked the movie".to_string())
+            .unwrap();
+    let mut data = review.try_to_vec().unwrap();
+    data.resize(size - 2, 0);
+    assert_eq!(state::state_version(&data), Some(1));
+    context.set_account_data(review_pda, data);
+
+    // Other instructions keep the review in its own layout.
+    context
+        .process_audited(
+            &[vote_on_review(
+                &program_id,
+                &other.pubkey(),
+                &review_pda,
+                true,
+            )],
+            &[&other],
+        )
+        .await
+        .unwrap();
+
+    let account = context.get_account(review_pda).await.unwrap();
+    assert_eq!(account.data.len(), size - 2);
+    assert_eq!(state::state_version(&account.data), Some(1));
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!((review.version, review.upvotes), (1, 1));
+
+    // Only the reviewer can migrate it.
+    let migrate_ix = migrate_review(&program_id, EntityKind::Movie, &payer, title);
+    let mut other_migrate_ix = migrate_ix.clone();
+    other_migrate_ix.accounts[1] = AccountMeta::new(other.pubkey(), true);
+    assert_eq!(
+        context
+            .process(&[other_migrate_ix], &[&other])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::UnauthorizedReviewer)
+    );
+
+    context
+        .process_audited(std::slice::from_ref(&migrate_ix), &[])
+        .await
+        .unwrap();
+
+    // Migrating counts the review in its title's summary, creating it.
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (1, 30));
+
+    let account = context.get_account(review_pda).await.unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.data.len(), size);
+    assert_eq!(account.lamports, rent.minimum_balance(size));
+    assert_eq!(
+        state::state_version(&account.data),
+        Some(state::STATE_VERSION)
+    );
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.version, state::STATE_VERSION);
+    assert_eq!(review.bump, bump_seed);
+    assert_eq!(review.upvotes, 1);
+    assert_eq!(review.description, "Liked the movie");
+
+    // A new blockhash keeps it from being deduplicated with the first.
+    context.refresh_blockhash().await;
+    assert_eq!(
+        context
+            .process(&[migrate_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AlreadyCurrentVersion)
+    );
+}
+
+#[tokio::test]
+async fn test_version_1_review_is_left_out_of_summary() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+
+    // Written before summaries, so its title has none.
+    let review = MovieAccountState {
+        version: 1,
+        ..review_state(payer, title, 30, "Liked the movie")
+    };
+    let size =
+        MovieAccountState::get_account_size(title.to_string(), "Liked the movie".to_string())
+            .unwrap();
+    let mut data = review.try_to_vec().unwrap();
+    data.resize(size - 2, 0);
+    context.set_account_data(review_pda, data);
+
+    context
+        .process_audited(
+            &[update_movie_review(
+                &program_id,
+                &payer,
+                title,
+                4,
+                "Loved the movie",
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.rating, 40);
+    assert!(context.get_account(summary_pda).await.is_none());
+
+    context
+        .process_audited(&[delete_movie_review(&program_id, &payer, title)], &[])
+        .await
+        .unwrap();
+
+    context.assert_closed(review_pda).await;
+    assert!(context.get_account(summary_pda).await.is_none());
+}
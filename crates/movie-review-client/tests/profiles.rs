@@ -0,0 +1,282 @@
+mod common;
+
+use {
+    assert_matches::*,
+    borsh::BorshSerialize,
+    common::{review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        add_movie_review, decode_review_records, delete_movie_review,
+        error::ReviewError,
+        export_my_reviews, find_handle_address, find_profile_address, find_review_address,
+        find_review_index_address, index_reviews, initialize_reviewer_profile, register_handle,
+        release_handle,
+        state::{title_hash, HandleState, ReviewIndexState, ReviewRecord, REVIEW_INDEX_PAGE_LEN},
+        ReviewFeeAccounts,
+    },
+    solana_program::system_instruction,
+    solana_program_test::tokio,
+    solana_sdk::{instruction::InstructionError, signature::Signer, transaction::TransactionError},
+};
+
+#[tokio::test]
+async fn test_initialize_reviewer_profile_once() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    context
+        .process(&[initialize_reviewer_profile(&program_id, &payer)], &[])
+        .await
+        .unwrap();
+
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &payer);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.owner, payer);
+    assert_eq!(profile.reviews_published, 0);
+
+    // The transfer keeps the retry from being deduplicated
+    assert_eq!(
+        context
+            .process(
+                &[
+                    system_instruction::transfer(&payer, &profile_pda, 1),
+                    initialize_reviewer_profile(&program_id, &payer),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn test_register_handle_is_unique() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = context.funded_keypair(1_000_000_000).await;
+
+    context
+        .process(&[register_handle(&program_id, &payer, "PopCorn")], &[])
+        .await
+        .unwrap();
+
+    let (handle_pda, _bump_seed) = find_handle_address(&program_id, "popcorn");
+    let handle = context
+        .banks_client
+        .get_state::<HandleState>(handle_pda)
+        .await;
+    assert_eq!(handle.owner, payer);
+    assert_eq!(handle.handle, "popcorn");
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &payer);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.handle, "popcorn");
+
+    assert_eq!(
+        context
+            .process(
+                &[register_handle(&program_id, &other.pubkey(), "popcorn")],
+                &[&other],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::HandleTaken)
+    );
+
+    // Once released, the handle can be claimed by someone else
+    context
+        .process(
+            &[
+                release_handle(&program_id, &payer, "popcorn"),
+                register_handle(&program_id, &other.pubkey(), "popcorn"),
+            ],
+            &[&other],
+        )
+        .await
+        .unwrap();
+
+    let handle = context
+        .banks_client
+        .get_state::<HandleState>(handle_pda)
+        .await;
+    assert_eq!(handle.owner, other.pubkey());
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.handle, "");
+}
+
+#[tokio::test]
+async fn test_register_handle_rejects_invalid_handles() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    for handle in ["ab", "has space", "emoji🍿", &"x".repeat(33)] {
+        let mut ix = register_handle(&program_id, &payer, "valid");
+        ix.data = vec![25];
+        ix.data.extend(handle.to_string().try_to_vec().unwrap());
+        assert_eq!(
+            context.process(&[ix], &[]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidHandle)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_export_my_reviews_pages_the_review_index() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let captain_america = context.add_review("Captain America", 3, "Liked it").await;
+    let iron_man = context.add_review("Iron Man", 4, "Liked it more").await;
+    let thor = context.add_review("Thor", 5, "Loved it").await;
+
+    // Reviews already on the page are skipped
+    context
+        .process(
+            &[
+                index_reviews(&program_id, &payer, 0, &[captain_america, iron_man]),
+                index_reviews(&program_id, &payer, 0, &[iron_man, thor]),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (index_pda, _bump_seed) = find_review_index_address(&program_id, &payer, 0);
+    let index = context
+        .banks_client
+        .get_state::<ReviewIndexState>(index_pda)
+        .await;
+    assert_eq!(index.reviewer, payer);
+    assert_eq!(index.page, 0);
+    assert_eq!(index.reviews, [captain_america, iron_man, thor]);
+
+    // Only the reviewer's own reviews can be indexed
+    let other = context.funded_keypair(1_000_000_000).await;
+    context
+        .process(
+            &[add_movie_review(
+                &program_id,
+                &other.pubkey(),
+                "Hulk",
+                2,
+                "Not for me",
+                ReviewFeeAccounts::default(),
+            )],
+            &[&other],
+        )
+        .await
+        .unwrap();
+    let (hulk, _bump_seed) = find_review_address(&program_id, &other.pubkey(), "Hulk");
+    assert_eq!(
+        context
+            .process(&[index_reviews(&program_id, &payer, 0, &[hulk])], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::UnauthorizedReviewer)
+    );
+
+    // Deleted reviews drop out of the export
+    context
+        .process(&[delete_movie_review(&program_id, &payer, "Iron Man")], &[])
+        .await
+        .unwrap();
+
+    let transaction = context.transaction(
+        &[export_my_reviews(&program_id, &payer, 0, &index.reviews)],
+        &[],
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_matches!(simulation.result, Some(Ok(())));
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, program_id);
+    let records = decode_review_records(&return_data.data).unwrap();
+
+    let mut expected = Vec::new();
+    for (review_pda, title, rating) in
+        [(captain_america, "Captain America", 30), (thor, "Thor", 50)]
+    {
+        let review = context.banks_client.get_review(review_pda).await;
+        expected.push(ReviewRecord {
+            title_hash: title_hash(title),
+            rating,
+            created_at: review.created_at,
+        });
+    }
+    assert_eq!(records, expected);
+
+    // The reviews must be passed in index order
+    let transaction = context.transaction(
+        &[export_my_reviews(
+            &program_id,
+            &payer,
+            0,
+            &[thor, iron_man, captain_america],
+        )],
+        &[],
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulation.result.unwrap().unwrap_err(),
+        review_error(0, ReviewError::IncorrectAccountError)
+    );
+}
+
+#[tokio::test]
+async fn test_index_reviews_fills_a_page() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let mut reviews = Vec::new();
+    for count in 0..=REVIEW_INDEX_PAGE_LEN {
+        let title = format!("Movie {}", count);
+        reviews.push(context.add_review(&title, 3, "Fine").await);
+    }
+    let (full, overflow) = reviews.split_at(REVIEW_INDEX_PAGE_LEN);
+
+    let (first_half, second_half) = full.split_at(REVIEW_INDEX_PAGE_LEN / 2);
+    context
+        .process(&[index_reviews(&program_id, &payer, 0, first_half)], &[])
+        .await
+        .unwrap();
+    context
+        .process(&[index_reviews(&program_id, &payer, 0, second_half)], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .process(&[index_reviews(&program_id, &payer, 0, overflow)], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::ReviewIndexPageFull)
+    );
+
+    // The next page takes the rest
+    context
+        .process(&[index_reviews(&program_id, &payer, 1, overflow)], &[])
+        .await
+        .unwrap();
+    let (index_pda, _bump_seed) = find_review_index_address(&program_id, &payer, 1);
+    let index = context
+        .banks_client
+        .get_state::<ReviewIndexState>(index_pda)
+        .await;
+    assert_eq!(index.page, 1);
+    assert_eq!(index.reviews, overflow);
+}
@@ -0,0 +1,1507 @@
+mod common;
+
+use {
+    assert_matches::*,
+    borsh::BorshSerialize,
+    common::{review_error, review_state, BanksClientExt, ReviewTestContext},
+    movie_review::cpi,
+    movie_review_client::{
+        add_comment, add_draft_review, add_entity_review, add_movie_review,
+        add_movie_review_idempotent, archive_review, delete_movie_review,
+        error::ReviewError,
+        expire_review, find_comment_counter_address, find_config_address,
+        find_mint_authority_address, find_review_address, find_snapshot_address,
+        find_summary_address, find_token_mint_address, initialize_config_idempotent,
+        initialize_reviewer_profile_idempotent, initialize_token_mint, migrate_legacy_review,
+        publish_review, set_review_expiry, set_snapshot_interval, set_visibility,
+        snapshot_aggregate,
+        state::{
+            self, AggregateSnapshot, EntityKind, MovieAccountState, Visibility, MAX_COMMENT_LEN,
+            MAX_DESCRIPTION_LEN, MAX_TITLE_LEN,
+        },
+        update_comment, update_movie_review, vote_on_review, ReviewFeeAccounts,
+    },
+    num_traits::FromPrimitive,
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        decode_error::DecodeError,
+        entrypoint::ProgramResult,
+        hash::hashv,
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_instruction, system_program,
+    },
+    solana_program_test::{processor, tokio},
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+    spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account,
+    },
+};
+
+// First unit test
+#[tokio::test]
+async fn test_initialize_mint_instruction() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // Process transaction and compare the result
+    assert_matches!(
+        context
+            .process(&[initialize_token_mint(&program_id, &payer)], &[])
+            .await,
+        Ok(_)
+    );
+}
+
+// Second unit test
+#[tokio::test]
+async fn test_add_movie_review_instruction() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let (mint_auth, _bump_seed) = find_mint_authority_address(&program_id);
+
+    // Derive the review, comment counter and user associated token account
+    // addresses
+    let title = "Captain America";
+    const RATING: u8 = 3;
+    let review = "Liked the movie";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let (comment_pda, _bump_seed) = find_comment_counter_address(&program_id, &review_pda);
+
+    // Create user associate token account of token mint
+    let init_ata_ix = create_associated_token_account(&payer, &payer, &mint, &spl_token::ID);
+    let user_ata = get_associated_token_address(&payer, &mint);
+
+    let mut add_review_ix = add_movie_review(
+        &program_id,
+        &payer,
+        title,
+        RATING,
+        review,
+        ReviewFeeAccounts::default(),
+    );
+    add_review_ix.accounts.extend([
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(mint_auth, false),
+        AccountMeta::new(user_ata, false),
+        AccountMeta::new(comment_pda, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+    ]);
+
+    // Process transaction and compare the result
+    assert_matches!(
+        context
+            .process(
+                &[
+                    initialize_token_mint(&program_id, &payer),
+                    init_ata_ix,
+                    add_review_ix,
+                ],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+}
+
+#[tokio::test]
+async fn test_field_length_errors() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+    let comment_pda = context.add_comment(review_pda, 0, "Short").await;
+
+    let long_title = "t".repeat(MAX_TITLE_LEN + 1);
+    let long_description = "d".repeat(MAX_DESCRIPTION_LEN + 1);
+    let long_comment = "c".repeat(MAX_COMMENT_LEN + 1);
+    let add_review = |title: &str, description: &str| {
+        add_movie_review(
+            &program_id,
+            &payer,
+            title,
+            3,
+            description,
+            ReviewFeeAccounts::default(),
+        )
+    };
+    let cases = [
+        (add_review(&long_title, "Fine"), ReviewError::TitleTooLong),
+        (
+            add_review("Thor", &long_description),
+            ReviewError::DescriptionTooLong,
+        ),
+        (
+            update_movie_review(&program_id, &payer, title, 4, &long_description),
+            ReviewError::DescriptionTooLong,
+        ),
+        (
+            add_comment(&program_id, &payer, &review_pda, 1, &long_comment, None),
+            ReviewError::CommentTooLong,
+        ),
+        (
+            update_comment(&program_id, &payer, &comment_pda, &long_comment),
+            ReviewError::CommentTooLong,
+        ),
+    ];
+    for (instruction, error) in cases {
+        assert_eq!(
+            context
+                .process(&[instruction], &[])
+                .await
+                .unwrap_err()
+                .unwrap(),
+            review_error(0, error)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_empty_fields_are_rejected() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+    let comment_pda = context.add_comment(review_pda, 0, "Short").await;
+
+    let add_review = |title: &str, description: &str| {
+        add_movie_review(
+            &program_id,
+            &payer,
+            title,
+            3,
+            description,
+            ReviewFeeAccounts::default(),
+        )
+    };
+    let cases = [
+        add_review("", "Liked it"),
+        add_review("Thor", ""),
+        update_movie_review(&program_id, &payer, title, 4, ""),
+        add_comment(&program_id, &payer, &review_pda, 1, "", None),
+        update_comment(&program_id, &payer, &comment_pda, ""),
+    ];
+    for instruction in cases {
+        assert_eq!(
+            context
+                .process(&[instruction], &[])
+                .await
+                .unwrap_err()
+                .unwrap(),
+            review_error(0, ReviewError::EmptyField)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_delete_movie_review_refunds_rent() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    assert_matches!(
+        context
+            .process_audited(
+                &[add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    3,
+                    "Liked the movie",
+                    ReviewFeeAccounts::default(),
+                )],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let review_lamports = context.get_balance(review_pda).await;
+    let payer_lamports = context.get_balance(payer).await;
+
+    assert_matches!(
+        context
+            .process_audited(&[delete_movie_review(&program_id, &payer, title)], &[])
+            .await,
+        Ok(_)
+    );
+
+    context.assert_closed(review_pda).await;
+    assert_eq!(
+        context.get_balance(payer).await,
+        payer_lamports + review_lamports - 5000
+    );
+}
+
+#[tokio::test]
+async fn test_movie_summary_tracks_average() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = Keypair::new();
+
+    let title = "Captain America";
+    assert_matches!(
+        context
+            .process_audited(
+                &[
+                    system_instruction::transfer(&payer, &other.pubkey(), 100_000_000),
+                    add_movie_review(
+                        &program_id,
+                        &payer,
+                        title,
+                        3,
+                        "Liked the movie",
+                        ReviewFeeAccounts::default(),
+                    ),
+                    add_movie_review(
+                        &program_id,
+                        &other.pubkey(),
+                        title,
+                        4,
+                        "Loved it",
+                        ReviewFeeAccounts::default(),
+                    ),
+                ],
+                &[&other],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!(summary.title_hash, state::title_hash(title));
+    assert_eq!((summary.review_count, summary.rating_sum), (2, 70));
+    assert_eq!(summary.rating_histogram, [0, 0, 1, 1, 0]);
+    assert_eq!(summary.average_rating(), Some(3.5));
+
+    assert_matches!(
+        context
+            .process_audited(
+                &[update_movie_review(
+                    &program_id,
+                    &other.pubkey(),
+                    title,
+                    1,
+                    "Not good",
+                )],
+                &[&other],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (2, 40));
+
+    assert_matches!(
+        context
+            .process_audited(&[delete_movie_review(&program_id, &payer, title)], &[])
+            .await,
+        Ok(_)
+    );
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (1, 10));
+    assert_eq!(summary.rating_histogram, [1, 0, 0, 0, 0]);
+    assert_eq!(summary.average_rating(), Some(1.0));
+}
+
+#[tokio::test]
+async fn test_movie_summary_counts_only_listed_reviews() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = context.funded_keypair(1_000_000_000).await;
+
+    let title = "Captain America";
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+    context
+        .process_audited(
+            &[
+                context.initialize_free_config(),
+                add_draft_review(&program_id, &payer, title, 3, "Work in progress"),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // Drafts open the summary without being counted in it.
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (0, 0));
+
+    context
+        .process_audited(&[publish_review(&program_id, &payer, title, false)], &[])
+        .await
+        .unwrap();
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (1, 30));
+
+    // Updates to an unlisted review are left out until it is public again.
+    context
+        .process_audited(
+            &[
+                set_visibility(&program_id, &payer, title, Visibility::Unlisted),
+                update_movie_review(&program_id, &payer, title, 4, "Liked it more"),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (0, 0));
+
+    context
+        .process_audited(
+            &[set_visibility(
+                &program_id,
+                &payer,
+                title,
+                Visibility::Public,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (1, 40));
+
+    // Expiring takes the review out, and purging it afterwards doesn't again.
+    context
+        .process_audited(
+            &[
+                set_review_expiry(&program_id, &payer, title, Some(1)),
+                expire_review(&program_id, EntityKind::Movie, &payer, title, false),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (0, 0));
+
+    context
+        .process_audited(
+            &[expire_review(
+                &program_id,
+                EntityKind::Movie,
+                &payer,
+                title,
+                true,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (0, 0));
+
+    // Archiving takes a published review out.
+    context
+        .process_audited(
+            &[add_movie_review(
+                &program_id,
+                &other.pubkey(),
+                title,
+                5,
+                "Loved it",
+                ReviewFeeAccounts::default(),
+            )],
+            &[&other],
+        )
+        .await
+        .unwrap();
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (1, 50));
+
+    context
+        .process_audited(
+            &[archive_review(
+                &program_id,
+                &payer,
+                EntityKind::Movie,
+                &other.pubkey(),
+                title,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.review_count, summary.rating_sum), (0, 0));
+}
+
+#[tokio::test]
+async fn test_snapshot_aggregate_copies_summary_once_per_epoch() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = context.funded_keypair(1_000_000_000).await;
+
+    let title = "Captain America";
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+    context
+        .process_audited(
+            &[
+                context.initialize_free_config(),
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    45,
+                    "Liked the movie",
+                    ReviewFeeAccounts::default(),
+                ),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // Snapshots wait for the admin to set an epoch length.
+    let snapshot_ix = snapshot_aggregate(&program_id, &payer, EntityKind::Movie, title, 0);
+    assert_eq!(
+        context
+            .process(std::slice::from_ref(&snapshot_ix), &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::SnapshotsNotConfigured)
+    );
+
+    // An interval this long keeps every timestamp in epoch 0.
+    assert_eq!(
+        context
+            .process(
+                &[
+                    set_snapshot_interval(&program_id, &payer, u64::MAX),
+                    snapshot_aggregate(&program_id, &payer, EntityKind::Movie, title, 1),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::InvalidPDA)
+    );
+
+    context
+        .process_audited(
+            &[
+                set_snapshot_interval(&program_id, &payer, u64::MAX),
+                snapshot_ix.clone(),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (first_pda, _bump_seed) = find_snapshot_address(&program_id, &summary_pda, 0);
+    let snapshot = context
+        .banks_client
+        .get_state::<AggregateSnapshot>(first_pda)
+        .await;
+    assert_eq!((snapshot.summary, snapshot.epoch), (summary_pda, 0));
+    assert_eq!((snapshot.review_count, snapshot.rating_sum), (1, 45));
+    assert_eq!(snapshot.rating_histogram, [0, 0, 0, 1, 0]);
+    assert_eq!(snapshot.average_rating(), Some(4.5));
+
+    // A new blockhash keeps it from being deduplicated with the first.
+    context.refresh_blockhash().await;
+    assert_eq!(
+        context
+            .process(&[snapshot_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+
+    // With an interval of the current time, it is epoch 1 until then again.
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    context
+        .process_audited(
+            &[
+                add_movie_review(
+                    &program_id,
+                    &other.pubkey(),
+                    title,
+                    2,
+                    "Not for me",
+                    ReviewFeeAccounts::default(),
+                ),
+                set_snapshot_interval(&program_id, &payer, clock.unix_timestamp as u64),
+                snapshot_aggregate(&program_id, &other.pubkey(), EntityKind::Movie, title, 1),
+            ],
+            &[&other],
+        )
+        .await
+        .unwrap();
+
+    let (second_pda, _bump_seed) = find_snapshot_address(&program_id, &summary_pda, 1);
+    let snapshot = context
+        .banks_client
+        .get_state::<AggregateSnapshot>(second_pda)
+        .await;
+    assert_eq!((snapshot.review_count, snapshot.rating_sum), (2, 65));
+    assert_eq!(snapshot.rating_histogram, [0, 1, 0, 1, 0]);
+
+    // Earlier snapshots keep the summary as it was.
+    let snapshot = context
+        .banks_client
+        .get_state::<AggregateSnapshot>(first_pda)
+        .await;
+    assert_eq!((snapshot.review_count, snapshot.rating_sum), (1, 45));
+}
+
+#[tokio::test]
+async fn test_half_star_ratings() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    assert_matches!(
+        context
+            .process_audited(
+                &[add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    35,
+                    "Liked it",
+                    ReviewFeeAccounts::default(),
+                )],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.rating, 35);
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!(summary.average_rating(), Some(3.5));
+
+    // Between whole stars and tenths, and past five stars.
+    for rating in [0, 6, 9, 51] {
+        assert_eq!(
+            context
+                .process(
+                    &[add_movie_review(
+                        &program_id,
+                        &payer,
+                        "Iron Man",
+                        rating,
+                        "Hm",
+                        ReviewFeeAccounts::default(),
+                    )],
+                    &[],
+                )
+                .await
+                .unwrap_err()
+                .unwrap(),
+            review_error(0, ReviewError::InvalidRating),
+            "rating {}",
+            rating
+        );
+    }
+}
+
+/// A companion program that adds its signer's review titled with the
+/// instruction data, or deletes it when the data is empty, through `cpi`.
+fn companion_process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [reviewer, review, system_program, config, summary, movie_review] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if data.is_empty() {
+        let accounts = cpi::DeleteMovieReview {
+            reviewer: reviewer.clone(),
+            review: review.clone(),
+            summary: summary.clone(),
+        };
+        return cpi::delete_movie_review(cpi::CpiContext::new(movie_review.clone(), accounts));
+    }
+
+    let accounts = cpi::AddMovieReview {
+        reviewer: reviewer.clone(),
+        review: review.clone(),
+        system_program: system_program.clone(),
+        config: config.clone(),
+        summary: summary.clone(),
+    };
+    cpi::add_movie_review(
+        cpi::CpiContext::new(movie_review.clone(), accounts),
+        String::from_utf8(data.to_vec()).unwrap(),
+        4,
+        "Added by a companion program".to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_cpi_adds_and_deletes_review() {
+    let program_id = Pubkey::new_unique();
+    let companion_id = Pubkey::new_unique();
+    let mut program_test = ReviewTestContext::program_test(program_id);
+    program_test.add_program(
+        "companion",
+        companion_id,
+        processor!(companion_process_instruction),
+    );
+    let mut context = ReviewTestContext::start(program_id, program_test).await;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let (config_pda, _bump_seed) = find_config_address(&program_id);
+    let (summary_pda, _bump_seed) = find_summary_address(&program_id, EntityKind::Movie, title);
+    let companion_ix = |data: &[u8]| Instruction {
+        program_id: companion_id,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(review_pda, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(summary_pda, false),
+            AccountMeta::new_readonly(program_id, false),
+        ],
+        data: data.to_vec(),
+    };
+
+    context
+        .process(&[companion_ix(title.as_bytes())], &[])
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.reviewer, payer);
+    assert_eq!(review.title, title);
+    assert_eq!(review.rating, 40);
+
+    context.process(&[companion_ix(&[])], &[]).await.unwrap();
+
+    context.assert_closed(review_pda).await;
+}
+
+#[tokio::test]
+async fn test_review_address_checked_with_stored_bump() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let voter = context.funded_keypair(1_000_000_000).await;
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 4, "Fun").await;
+    let (_review_pda, bump_seed) = find_review_address(&program_id, &payer, title);
+
+    let mut account = context.get_account(review_pda).await.unwrap();
+    let mut review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.bump, bump_seed);
+    assert_eq!(review.address(&program_id), Some(review_pda));
+
+    // A review is only found at the address its stored bump gives.
+    review.bump = bump_seed.wrapping_sub(1);
+    assert_ne!(review.address(&program_id), Some(review_pda));
+    let data = review.try_to_vec().unwrap();
+    account.data[..data.len()].copy_from_slice(&data);
+    context.set_account(review_pda, account);
+
+    assert_eq!(
+        context
+            .process(
+                &[vote_on_review(
+                    &program_id,
+                    &voter.pubkey(),
+                    &review_pda,
+                    true
+                )],
+                &[&voter],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidPDA)
+    );
+}
+
+#[tokio::test]
+async fn test_corrupted_review_fails_to_deserialize() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // A review cut off partway through its fields
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let data = (MovieAccountState::DISCRIMINATOR.to_string(), true, [7u8; 5])
+        .try_to_vec()
+        .unwrap();
+    context.set_account_data(review_pda, data);
+
+    for instruction in [
+        update_movie_review(&program_id, &payer, title, 4, "Loved it"),
+        delete_movie_review(&program_id, &payer, title),
+    ] {
+        assert_eq!(
+            context
+                .process(&[instruction], &[])
+                .await
+                .unwrap_err()
+                .unwrap(),
+            review_error(0, ReviewError::DeserializationFailed)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_add_movie_review_allocates_exact_size() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+
+    let size =
+        MovieAccountState::get_account_size(title.to_string(), "Liked the movie".to_string())
+            .unwrap();
+    let account = context.get_account(review_pda).await.unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.data.len(), size);
+    assert_eq!(account.lamports, rent.minimum_balance(size));
+
+    // There is nothing left to migrate.
+    assert_eq!(
+        context
+            .process(
+                &[migrate_legacy_review(&program_id, &review_pda, &payer)],
+                &[]
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::AlreadyMigrated)
+    );
+}
+
+#[tokio::test]
+async fn test_update_movie_review_shrinks_and_refunds() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let reviewer = context.funded_keypair(100_000_000).await;
+
+    let title = "Captain America";
+    context
+        .process(
+            &[add_movie_review(
+                &program_id,
+                &reviewer.pubkey(),
+                title,
+                3,
+                "Liked the movie, especially the second half",
+                ReviewFeeAccounts::default(),
+            )],
+            &[&reviewer],
+        )
+        .await
+        .unwrap();
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &reviewer.pubkey(), title);
+    let review_lamports = context.get_balance(review_pda).await;
+    let reviewer_lamports = context.get_balance(reviewer.pubkey()).await;
+
+    context
+        .process(
+            &[update_movie_review(
+                &program_id,
+                &reviewer.pubkey(),
+                title,
+                4,
+                "Liked it",
+            )],
+            &[&reviewer],
+        )
+        .await
+        .unwrap();
+
+    let size =
+        MovieAccountState::get_account_size(title.to_string(), "Liked it".to_string()).unwrap();
+    let account = context.get_account(review_pda).await.unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.data.len(), size);
+    assert_eq!(account.lamports, rent.minimum_balance(size));
+    assert_eq!(
+        context.get_balance(reviewer.pubkey()).await,
+        reviewer_lamports + review_lamports - account.lamports
+    );
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.description, "Liked it");
+    assert_eq!(review.rating, 40);
+}
+
+#[tokio::test]
+async fn test_update_movie_review_changes_rating_and_description() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+    let before = context.banks_client.get_review(review_pda).await;
+
+    context
+        .process(
+            &[update_movie_review(
+                &program_id,
+                &payer,
+                title,
+                5,
+                "Loved the movie",
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.rating, 50);
+    assert_eq!(review.description, "Loved the movie");
+    assert_eq!(review.title, before.title);
+    assert_eq!(review.reviewer, before.reviewer);
+    assert_eq!(review.kind, before.kind);
+    assert_eq!(review.created_at, before.created_at);
+}
+
+#[tokio::test]
+async fn test_rejects_other_reviewers_review() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let attacker = Keypair::new();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+
+    // The attacker signs with their own key but passes the payer's review.
+    let mut update_ix = update_movie_review(&program_id, &attacker.pubkey(), title, 1, "Hated it");
+    update_ix.accounts[1].pubkey = review_pda;
+    let mut delete_ix = delete_movie_review(&program_id, &attacker.pubkey(), title);
+    delete_ix.accounts[1].pubkey = review_pda;
+    for instruction in [update_ix, delete_ix] {
+        assert_eq!(
+            context
+                .process(&[instruction], &[&attacker])
+                .await
+                .unwrap_err()
+                .unwrap(),
+            review_error(0, ReviewError::UnauthorizedReviewer)
+        );
+    }
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.rating, 30);
+    assert_eq!(review.description, "Liked the movie");
+}
+
+#[tokio::test]
+async fn test_review_with_title_longer_than_a_seed() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "The Lord of the Rings: The Fellowship of the Ring (Extended)";
+    assert_eq!(title.len(), 60);
+    let (review_pda, _bump_seed) = Pubkey::find_program_address(
+        &[payer.as_ref(), hashv(&[title.as_bytes()]).as_ref()],
+        &program_id,
+    );
+    assert_eq!(
+        find_review_address(&program_id, &payer, title).0,
+        review_pda
+    );
+
+    context
+        .process(
+            &[
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    5,
+                    "Liked it",
+                    ReviewFeeAccounts::default(),
+                ),
+                update_movie_review(&program_id, &payer, title, 4, "Still liked it"),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.title, title);
+    assert_eq!(review.description, "Still liked it");
+
+    context
+        .process(&[delete_movie_review(&program_id, &payer, title)], &[])
+        .await
+        .unwrap();
+    context.assert_closed(review_pda).await;
+}
+
+#[tokio::test]
+async fn test_review_timestamps_track_add_and_update() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked it").await;
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.created_at, clock.unix_timestamp);
+    assert_eq!(review.updated_at, review.created_at);
+
+    context.warp_to_slot(clock.slot + 10_000);
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    assert!(clock.unix_timestamp > review.created_at);
+
+    context.refresh_blockhash().await;
+    context
+        .process(
+            &[update_movie_review(
+                &program_id,
+                &payer,
+                title,
+                4,
+                "Loved it",
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let updated = context.banks_client.get_review(review_pda).await;
+    assert_eq!(updated.created_at, review.created_at);
+    assert_eq!(updated.updated_at, clock.unix_timestamp);
+}
+
+#[tokio::test]
+async fn test_custom_errors_decode_to_review_error() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let err = context
+        .process(
+            &[add_movie_review(
+                &program_id,
+                &payer,
+                "Iron Man",
+                6,
+                "Liked it",
+                ReviewFeeAccounts::default(),
+            )],
+            &[],
+        )
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    let TransactionError::InstructionError(0, InstructionError::Custom(code)) = err else {
+        panic!("unexpected error: {:?}", err);
+    };
+    let error = ReviewError::from_u32(code).unwrap();
+    assert_matches!(error, ReviewError::InvalidRating);
+    assert_eq!(
+        error.to_string(),
+        "Rating must be 10 to 50 tenths of a star, or 1 to 5 whole stars"
+    );
+    assert_eq!(
+        <ReviewError as DecodeError<ReviewError>>::type_of(),
+        "ReviewError"
+    );
+    assert!(ReviewError::from_u32(u32::MAX).is_none());
+}
+
+#[tokio::test]
+async fn test_review_instructions_fail_with_their_errors() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let reviewer = context.funded_keypair(1_000_000_000).await;
+    let title = "Captain America";
+    let (_review_pda, bump_seed) = find_review_address(&program_id, &reviewer.pubkey(), title);
+
+    // The reviewer's review, copied to an address it wasn't derived for
+    let misplaced_review = Pubkey::new_unique();
+    let review = MovieAccountState {
+        bump: bump_seed,
+        ..review_state(reviewer.pubkey(), title, 30, "Liked the movie")
+    };
+    let mut data = review.try_to_vec().unwrap();
+    data.resize(
+        MovieAccountState::get_account_size(title.to_string(), review.description.clone()).unwrap(),
+        0,
+    );
+    context.set_account_data(misplaced_review, data);
+
+    let add_ix = add_movie_review(
+        &program_id,
+        &reviewer.pubkey(),
+        title,
+        3,
+        "Liked the movie",
+        ReviewFeeAccounts::default(),
+    );
+    let reinit_ix = add_ix.clone();
+    context.process(&[add_ix], &[&reviewer]).await.unwrap();
+    // Keeps the retried add from being deduplicated with the first
+    context.refresh_blockhash().await;
+
+    let mut misplaced_update_ix =
+        update_movie_review(&program_id, &reviewer.pubkey(), title, 4, "Loved the movie");
+    misplaced_update_ix.accounts[1].pubkey = misplaced_review;
+    let mut unsigned_update_ix =
+        update_movie_review(&program_id, &reviewer.pubkey(), title, 4, "Loved the movie");
+    unsigned_update_ix.accounts[0].is_signer = false;
+
+    let long_title = "a".repeat(MAX_TITLE_LEN + 1);
+    let long_description = "a".repeat(MAX_DESCRIPTION_LEN + 1);
+    let add_review = |title: &str, rating: u8, description: &str| {
+        add_movie_review(
+            &program_id,
+            &reviewer.pubkey(),
+            title,
+            rating,
+            description,
+            ReviewFeeAccounts::default(),
+        )
+    };
+    let cases = [
+        (
+            add_review("Iron Man", 0, "Fun"),
+            InstructionError::Custom(ReviewError::InvalidRating as u32),
+        ),
+        (
+            add_review(&long_title, 3, "Fun"),
+            InstructionError::Custom(ReviewError::TitleTooLong as u32),
+        ),
+        (
+            add_review("Iron Man", 3, &long_description),
+            InstructionError::Custom(ReviewError::DescriptionTooLong as u32),
+        ),
+        (
+            add_review("", 3, "Fun"),
+            InstructionError::Custom(ReviewError::EmptyField as u32),
+        ),
+        (
+            misplaced_update_ix,
+            InstructionError::Custom(ReviewError::InvalidPDA as u32),
+        ),
+        (
+            reinit_ix,
+            InstructionError::Custom(ReviewError::ReviewAlreadyExists as u32),
+        ),
+        // Missing signatures fail with the built-in `ProgramError` rather
+        // than a `ReviewError`.
+        (
+            unsigned_update_ix,
+            InstructionError::MissingRequiredSignature,
+        ),
+    ];
+    for (instruction, expected) in cases {
+        let signers: Vec<&Keypair> = if instruction.accounts[0].is_signer {
+            vec![&reviewer]
+        } else {
+            vec![]
+        };
+        assert_eq!(
+            context
+                .process(&[instruction], &signers)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, expected)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_review_instructions_require_writable_accounts() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let reviewer = context.funded_keypair(1_000_000_000).await;
+
+    let title = "Captain America";
+    let add_ix = add_movie_review(
+        &program_id,
+        &reviewer.pubkey(),
+        title,
+        3,
+        "Liked the movie",
+        ReviewFeeAccounts::default(),
+    );
+    let update_ix =
+        update_movie_review(&program_id, &reviewer.pubkey(), title, 4, "Loved the movie");
+    let delete_ix = delete_movie_review(&program_id, &reviewer.pubkey(), title);
+
+    // Each instruction with its reviewer (0) or review (1) marked read-only
+    let read_only = |instruction: &Instruction, index: usize| {
+        let mut instruction = instruction.clone();
+        instruction.accounts[index].is_writable = false;
+        instruction
+    };
+    let not_writable = review_error(0, ReviewError::AccountNotWritable);
+
+    for index in [0, 1] {
+        assert_eq!(
+            context
+                .process(&[read_only(&add_ix, index)], &[&reviewer])
+                .await
+                .unwrap_err()
+                .unwrap(),
+            not_writable
+        );
+    }
+    context.process(&[add_ix], &[&reviewer]).await.unwrap();
+
+    for instruction in [&update_ix, &delete_ix] {
+        for index in [0, 1] {
+            assert_eq!(
+                context
+                    .process(&[read_only(instruction, index)], &[&reviewer])
+                    .await
+                    .unwrap_err()
+                    .unwrap(),
+                not_writable
+            );
+        }
+    }
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &reviewer.pubkey(), title);
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.description, "Liked the movie");
+}
+
+#[tokio::test]
+async fn test_closed_review_cannot_be_revived() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+
+    // Close the review, refund it to rent exemption and try to use it again
+    let delete_ix = delete_movie_review(&program_id, &payer, title);
+    let top_up_ix = system_instruction::transfer(&payer, &review_pda, 100_000_000);
+    assert_eq!(
+        context
+            .process(
+                &[
+                    delete_ix.clone(),
+                    top_up_ix.clone(),
+                    update_movie_review(&program_id, &payer, title, 5, "Revived"),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(2, ReviewError::AccountClosed)
+    );
+
+    // Nor can a closed review be closed again to drain the top-up
+    assert_eq!(
+        context
+            .process(&[delete_ix.clone(), top_up_ix, delete_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(2, ReviewError::AccountClosed)
+    );
+}
+
+#[tokio::test]
+async fn test_create_instructions_accept_prefunded_pdas() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let config_pda = context.config_address();
+
+    // Funding a PDA before it is created must not block its creation
+    assert_matches!(
+        context
+            .process(
+                &[
+                    system_instruction::transfer(&payer, &review_pda, 1_000_000),
+                    system_instruction::transfer(&payer, &config_pda, 1_000_000),
+                    context.initialize_free_config(),
+                    add_movie_review(
+                        &program_id,
+                        &payer,
+                        title,
+                        3,
+                        "Liked the movie",
+                        ReviewFeeAccounts::default(),
+                    ),
+                ],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let review_account = context.get_account(review_pda).await.unwrap();
+    assert_eq!(review_account.owner, program_id);
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.discriminator, MovieAccountState::DISCRIMINATOR);
+    assert_eq!(review.title, title);
+
+    let config_account = context.get_account(config_pda).await.unwrap();
+    assert_eq!(config_account.owner, program_id);
+}
+
+#[tokio::test]
+async fn test_add_movie_review_rejects_existing_account() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // Simulate a review PDA that already carries valid state
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let mut data = review_state(payer, title, 1, "Original")
+        .try_to_vec()
+        .unwrap();
+    data.resize(1000, 0);
+    context.set_account_data(review_pda, data);
+
+    assert_eq!(
+        context
+            .process(
+                &[add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    5,
+                    "Overwritten",
+                    ReviewFeeAccounts::default(),
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::ReviewAlreadyExists)
+    );
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.description, "Original");
+}
+
+#[tokio::test]
+async fn test_add_movie_review_twice_fails_with_review_already_exists() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Captain America";
+    let review_pda = context.add_review(title, 3, "Liked the movie").await;
+
+    assert_eq!(
+        context
+            .process(
+                &[add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    5,
+                    "Loved the movie",
+                    ReviewFeeAccounts::default(),
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::ReviewAlreadyExists)
+    );
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.description, "Liked the movie");
+}
+
+#[tokio::test]
+async fn test_idempotent_instructions_succeed_on_retry() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let treasury = Pubkey::new_unique();
+    let title = "Captain America";
+
+    let init_config_ix = |review_fee_lamports| {
+        initialize_config_idempotent(
+            &program_id,
+            &payer,
+            &treasury,
+            review_fee_lamports,
+            0,
+            &Pubkey::default(),
+            0,
+        )
+    };
+    let init_profile_ix = || initialize_reviewer_profile_idempotent(&program_id, &payer);
+    let add_review_ix = |rating| {
+        add_movie_review_idempotent(
+            &program_id,
+            &payer,
+            title,
+            rating,
+            "Liked it",
+            ReviewFeeAccounts::default(),
+        )
+    };
+
+    context
+        .process(
+            &[init_config_ix(0), init_profile_ix(), add_review_ix(4)],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    // A retry, with a duplicate thrown in, changes nothing
+    context
+        .process(
+            &[
+                init_config_ix(0),
+                init_profile_ix(),
+                add_review_ix(4),
+                add_review_ix(4),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .process(&[add_review_ix(5)], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::ReviewAlreadyExists)
+    );
+
+    assert_eq!(
+        context
+            .process(&[init_config_ix(1)], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.rating, 40);
+
+    let config = context
+        .banks_client
+        .get_config(context.config_address())
+        .await;
+    assert_eq!(config.review_fee_lamports, 0);
+}
+
+#[tokio::test]
+async fn test_entity_kinds_have_separate_reviews() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let title = "Dune";
+    let (movie_pda, _bump_seed) =
+        state::derive_review_pda(&program_id, EntityKind::Movie, &payer, title);
+    let (book_pda, _bump_seed) =
+        state::derive_review_pda(&program_id, EntityKind::Book, &payer, title);
+    assert_eq!(
+        movie_pda,
+        Pubkey::find_program_address(&[payer.as_ref(), title.as_bytes()], &program_id).0
+    );
+    assert_ne!(book_pda, movie_pda);
+
+    let mut update_book_ix = update_movie_review(&program_id, &payer, title, 3, "Slow");
+    update_book_ix.accounts[1].pubkey = book_pda;
+    update_book_ix.accounts[4].pubkey =
+        find_summary_address(&program_id, EntityKind::Book, title).0;
+    context
+        .process(
+            &[
+                add_entity_review(
+                    &program_id,
+                    &payer,
+                    EntityKind::Movie,
+                    title,
+                    4,
+                    "Great visuals",
+                    ReviewFeeAccounts::default(),
+                ),
+                add_entity_review(
+                    &program_id,
+                    &payer,
+                    EntityKind::Book,
+                    title,
+                    5,
+                    "Better than the movie",
+                    ReviewFeeAccounts::default(),
+                ),
+                update_book_ix,
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let movie = context.banks_client.get_review(movie_pda).await;
+    assert_eq!(movie.kind, EntityKind::Movie);
+    assert_eq!(movie.rating, 40);
+
+    let book = context.banks_client.get_review(book_pda).await;
+    assert_eq!(book.kind, EntityKind::Book);
+    assert_eq!(book.rating, 30);
+    assert_eq!(book.description, "Slow");
+}
@@ -0,0 +1,361 @@
+mod common;
+
+use {
+    assert_matches::*,
+    common::{now, pyth_price_account, review_error, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        add_comment, add_movie_review, claim_rewards,
+        error::ReviewError,
+        find_profile_address, find_review_address, find_token_mint_address, initialize_config,
+        initialize_token_mint, set_comment_reward, set_referral_bonus, set_reward_schedule,
+        state::{RewardHalvingUnit, COMMENT_REWARD_AMOUNT},
+        ReviewFeeAccounts,
+    },
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_program_test::tokio,
+    solana_sdk::signature::{Keypair, Signer},
+    spl_associated_token_account::get_associated_token_address,
+};
+
+#[cfg(feature = "test-utils")]
+#[tokio::test]
+async fn test_localnet_faucet_and_profile_fixture() {
+    use movie_review_client::{mint_test_tokens, set_test_profile};
+
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let recipient = Pubkey::new_unique();
+    context
+        .process(
+            &[
+                initialize_token_mint(&program_id, &payer),
+                mint_test_tokens(&program_id, &payer, &recipient, 1_000),
+                set_test_profile(&program_id, &payer, &recipient, 7, 3, 50),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let ata = get_associated_token_address(&recipient, &mint);
+    assert_eq!(context.banks_client.get_token_amount(ata).await, 1_000);
+
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &recipient);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.owner, recipient);
+    assert_eq!(profile.comments_created, 7);
+    assert_eq!(profile.reviews_published, 3);
+    assert_eq!(profile.pending_rewards, 50);
+}
+
+#[tokio::test]
+async fn test_referral_bonus_on_first_review() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // The referrer needs a profile of their own
+    let referee = Keypair::new();
+    let referred = ReviewFeeAccounts {
+        referrer: Some(payer),
+        ..ReviewFeeAccounts::default()
+    };
+    context
+        .process_audited(
+            &[
+                context.initialize_free_config(),
+                set_referral_bonus(&program_id, &payer, 20),
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    "Iron Man",
+                    4,
+                    "Liked it",
+                    ReviewFeeAccounts {
+                        reward: true,
+                        ..ReviewFeeAccounts::default()
+                    },
+                ),
+                system_instruction::transfer(&payer, &referee.pubkey(), 1_000_000_000),
+                add_movie_review(
+                    &program_id,
+                    &referee.pubkey(),
+                    "Thor",
+                    3,
+                    "Referred here",
+                    referred,
+                ),
+            ],
+            &[&referee],
+        )
+        .await
+        .unwrap();
+
+    let (referee_profile, _bump_seed) = find_profile_address(&program_id, &referee.pubkey());
+    let profile = context.banks_client.get_profile(referee_profile).await;
+    assert_eq!(profile.referred_by, Some(payer));
+    assert_eq!(profile.reviews_published, 1);
+    assert_eq!(profile.pending_rewards, 20);
+
+    let (referrer_profile, _bump_seed) = find_profile_address(&program_id, &payer);
+    let profile = context.banks_client.get_profile(referrer_profile).await;
+    assert_eq!(profile.referred_by, None);
+    assert_eq!(profile.pending_rewards, 20);
+
+    // The referral edge can't be reused on a later review
+    assert_eq!(
+        context
+            .process_audited(
+                &[add_movie_review(
+                    &program_id,
+                    &referee.pubkey(),
+                    "Hulk",
+                    3,
+                    "Referred again",
+                    referred,
+                )],
+                &[&referee],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::ReferralNotAllowed)
+    );
+}
+
+#[tokio::test]
+async fn test_add_movie_review_accrues_halving_reward() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let mut instructions = vec![
+        initialize_token_mint(&program_id, &payer),
+        context.initialize_free_config(),
+        set_reward_schedule(&program_id, &payer, 100, 1, RewardHalvingUnit::Reviews),
+    ];
+    for title in ["Captain America", "Iron Man", "Thor"] {
+        instructions.push(add_movie_review(
+            &program_id,
+            &payer,
+            title,
+            3,
+            "Liked the movie",
+            ReviewFeeAccounts {
+                reward: true,
+                ..ReviewFeeAccounts::default()
+            },
+        ));
+    }
+    context.process(&instructions, &[]).await.unwrap();
+
+    // 100 for the first review, then 50 and 25 after each halving
+    let (profile_pda, _bump_seed) = find_profile_address(&program_id, &payer);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.pending_rewards, 175);
+
+    let config_pda = context.config_address();
+    let config = context.banks_client.get_config(config_pda).await;
+    assert_eq!(config.rewarded_reviews, 3);
+
+    // Nothing is minted until the rewards are claimed
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let user_ata = get_associated_token_address(&payer, &mint);
+    assert!(context.get_account(user_ata).await.is_none());
+
+    context
+        .process(&[claim_rewards(&program_id, &payer)], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(context.banks_client.get_token_amount(user_ata).await, 175);
+    let profile = context.banks_client.get_profile(profile_pda).await;
+    assert_eq!(profile.pending_rewards, 0);
+    assert_eq!(profile.rewards_claimed, 175);
+}
+
+#[tokio::test]
+async fn test_add_comment_accrues_reward() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let commenter = Keypair::new();
+
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, "Captain America");
+    let comment = |count: u64| {
+        add_comment(
+            &program_id,
+            &commenter.pubkey(),
+            &review_pda,
+            count,
+            "Agreed",
+            None,
+        )
+    };
+    context
+        .process(
+            &[
+                initialize_token_mint(&program_id, &payer),
+                context.initialize_free_config(),
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    "Captain America",
+                    3,
+                    "Liked the movie",
+                    ReviewFeeAccounts::default(),
+                ),
+                system_instruction::transfer(&payer, &commenter.pubkey(), 100_000_000),
+                comment(0),
+                comment(1),
+            ],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+
+    // Comments earn rewards of their own while review rewards are off
+    let config = context
+        .banks_client
+        .get_config(context.config_address())
+        .await;
+    assert_eq!(config.reward_base_amount, 0);
+    assert_eq!(config.comment_reward_amount, COMMENT_REWARD_AMOUNT);
+    let (commenter_profile_pda, _bump_seed) =
+        find_profile_address(&program_id, &commenter.pubkey());
+    let profile = context
+        .banks_client
+        .get_profile(commenter_profile_pda)
+        .await;
+    assert_eq!(profile.comments_created, 2);
+    assert_eq!(profile.pending_rewards, 2 * COMMENT_REWARD_AMOUNT);
+
+    // Switched off, comments earn nothing even with review rewards on
+    context
+        .process(
+            &[
+                set_reward_schedule(&program_id, &payer, 100, 0, RewardHalvingUnit::Reviews),
+                set_comment_reward(&program_id, &payer, 0),
+                comment(2),
+            ],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+
+    let profile = context
+        .banks_client
+        .get_profile(commenter_profile_pda)
+        .await;
+    assert_eq!(profile.comments_created, 3);
+    assert_eq!(profile.pending_rewards, 2 * COMMENT_REWARD_AMOUNT);
+
+    context
+        .process(
+            &[
+                set_comment_reward(&program_id, &payer, 8),
+                comment(3),
+                claim_rewards(&program_id, &commenter.pubkey()),
+            ],
+            &[&commenter],
+        )
+        .await
+        .unwrap();
+
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    let commenter_ata = get_associated_token_address(&commenter.pubkey(), &mint);
+    let claimed = 2 * COMMENT_REWARD_AMOUNT + 8;
+    assert_eq!(
+        context.banks_client.get_token_amount(commenter_ata).await,
+        claimed
+    );
+    let profile = context
+        .banks_client
+        .get_profile(commenter_profile_pda)
+        .await;
+    assert_eq!(profile.pending_rewards, 0);
+    assert_eq!(profile.rewards_claimed, claimed);
+}
+
+#[tokio::test]
+async fn test_add_movie_review_pays_usd_fee() {
+    let program_id = Pubkey::new_unique();
+    let price_feed = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+
+    // $20.00 per SOL
+    let mut program_test = ReviewTestContext::program_test(program_id);
+    program_test.add_account(price_feed, pyth_price_account(2_000_000_000, now()));
+    let mut context = ReviewTestContext::start(program_id, program_test).await;
+    context.set_upgrade_authority(Some(context.payer.pubkey()));
+    let payer = context.payer.pubkey();
+
+    // A $1.00 fee is 0.05 SOL at this price
+    assert_matches!(
+        context
+            .process_audited(
+                &[
+                    initialize_config(&program_id, &payer, &treasury, 0, 100, &price_feed, 3600),
+                    add_movie_review(
+                        &program_id,
+                        &payer,
+                        "Captain America",
+                        3,
+                        "Liked the movie",
+                        ReviewFeeAccounts {
+                            price_feed: Some(price_feed),
+                            treasury: Some(treasury),
+                            ..ReviewFeeAccounts::default()
+                        },
+                    ),
+                ],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    assert_eq!(context.get_balance(treasury).await, 50_000_000);
+}
+
+#[tokio::test]
+async fn test_add_movie_review_rejects_stale_price() {
+    let program_id = Pubkey::new_unique();
+    let price_feed = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+
+    let mut program_test = ReviewTestContext::program_test(program_id);
+    program_test.add_account(price_feed, pyth_price_account(2_000_000_000, now() - 7200));
+    let mut context = ReviewTestContext::start(program_id, program_test).await;
+    context.set_upgrade_authority(Some(context.payer.pubkey()));
+    let payer = context.payer.pubkey();
+
+    assert_eq!(
+        context
+            .process(
+                &[
+                    initialize_config(&program_id, &payer, &treasury, 0, 100, &price_feed, 3600),
+                    add_movie_review(
+                        &program_id,
+                        &payer,
+                        "Captain America",
+                        3,
+                        "Liked the movie",
+                        ReviewFeeAccounts {
+                            price_feed: Some(price_feed),
+                            treasury: Some(treasury),
+                            ..ReviewFeeAccounts::default()
+                        },
+                    ),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::StalePriceFeed)
+    );
+}
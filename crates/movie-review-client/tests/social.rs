@@ -0,0 +1,651 @@
+mod common;
+
+use {
+    assert_matches::*,
+    borsh::BorshSerialize,
+    common::{now, review_error, review_state, BanksClientExt, ReviewTestContext},
+    movie_review_client::{
+        add_movie_review, add_to_watchlist, claim_rewards, create_versus_poll,
+        error::ReviewError,
+        finalize_versus_poll, find_review_address, find_review_vote_address, find_summary_address,
+        find_token_mint_address, find_versus_poll_address, find_watchlist_address,
+        initialize_token_mint, remove_from_watchlist, set_disabled_features, set_reward_schedule,
+        state::{
+            self, EntityKind, Feature, MovieAccountState, ReviewVote, RewardHalvingUnit,
+            VersusPollState, VersusSide, WatchlistState,
+        },
+        tip_reviewer, tip_reviewer_token, vote_on_review, vote_versus, ReviewFeeAccounts,
+    },
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_program_test::tokio,
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+    spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account,
+    },
+};
+
+#[tokio::test]
+async fn test_versus_poll_takes_one_vote_per_wallet() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let voter = Keypair::new();
+
+    assert_eq!(
+        context
+            .process(
+                &[create_versus_poll(
+                    &program_id,
+                    &payer,
+                    "Iron Man",
+                    "Iron Man",
+                    now() + 3600,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidVersusPair)
+    );
+
+    let (poll_pda, _bump_seed) =
+        find_versus_poll_address(&program_id, "Captain America", "Iron Man");
+    context
+        .process(
+            &[
+                create_versus_poll(
+                    &program_id,
+                    &payer,
+                    "Captain America",
+                    "Iron Man",
+                    now() + 3600,
+                ),
+                system_instruction::transfer(&payer, &voter.pubkey(), 100_000_000),
+                vote_versus(&program_id, &payer, &poll_pda, VersusSide::A),
+                vote_versus(&program_id, &voter.pubkey(), &poll_pda, VersusSide::B),
+            ],
+            &[&voter],
+        )
+        .await
+        .unwrap();
+
+    // A second vote finds the receipt already there.
+    assert_eq!(
+        context
+            .process(
+                &[vote_versus(&program_id, &payer, &poll_pda, VersusSide::B)],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+
+    assert_eq!(
+        context
+            .process(
+                &[finalize_versus_poll(
+                    &program_id,
+                    &payer,
+                    "Captain America",
+                    "Iron Man",
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::PollOpen)
+    );
+
+    let poll = context
+        .banks_client
+        .get_state::<VersusPollState>(poll_pda)
+        .await;
+    assert_eq!((poll.votes_a, poll.votes_b), (1, 1));
+    assert!(!poll.finalized);
+}
+
+#[tokio::test]
+async fn test_versus_poll_finalizes_after_it_ends() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let (poll_pda, _bump_seed) =
+        find_versus_poll_address(&program_id, "Captain America", "Iron Man");
+    let poll = VersusPollState {
+        discriminator: VersusPollState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        creator: Pubkey::new_unique(),
+        movie_a: "Captain America".to_string(),
+        movie_b: "Iron Man".to_string(),
+        ends_at: now() - 60,
+        votes_a: 2,
+        votes_b: 3,
+        finalized: false,
+        winner: None,
+    };
+    let mut data = poll.try_to_vec().unwrap();
+    data.resize(
+        VersusPollState::get_account_size(&poll.movie_a, &poll.movie_b).unwrap(),
+        0,
+    );
+    context.set_account_data(poll_pda, data);
+
+    assert_eq!(
+        context
+            .process(
+                &[vote_versus(&program_id, &payer, &poll_pda, VersusSide::A)],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::PollClosed)
+    );
+
+    // Finalizing twice fails on the second attempt.
+    let finalize_ix = finalize_versus_poll(&program_id, &payer, "Captain America", "Iron Man");
+    assert_eq!(
+        context
+            .process(&[finalize_ix.clone(), finalize_ix.clone()], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::PollClosed)
+    );
+
+    // Only Captain America has been reviewed, so Iron Man's summary is
+    // created to record its win.
+    context
+        .process(
+            &[
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    "Captain America",
+                    4,
+                    "Liked it",
+                    ReviewFeeAccounts::default(),
+                ),
+                finalize_ix,
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let poll = context
+        .banks_client
+        .get_state::<VersusPollState>(poll_pda)
+        .await;
+    assert!(poll.finalized);
+    assert_eq!(poll.winner, Some(VersusSide::B));
+
+    let (summary_pda, _bump_seed) =
+        find_summary_address(&program_id, EntityKind::Movie, "Captain America");
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.versus_wins, summary.versus_losses), (0, 1));
+    assert_eq!((summary.review_count, summary.rating_sum), (1, 40));
+
+    let (summary_pda, _bump_seed) =
+        find_summary_address(&program_id, EntityKind::Movie, "Iron Man");
+    let summary = context.banks_client.get_summary(summary_pda).await;
+    assert_eq!((summary.versus_wins, summary.versus_losses), (1, 0));
+    assert_eq!(summary.review_count, 0);
+}
+
+#[tokio::test]
+async fn test_versus_poll_between_titles_longer_than_a_seed() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    let movie_a = "The Lord of the Rings: The Fellowship of the Ring (Extended)";
+    let movie_b = "The Lord of the Rings: The Return of the King (Extended)";
+    let too_long = "A".repeat(state::MAX_TITLE_LEN + 1);
+    assert_eq!(
+        context
+            .process(
+                &[create_versus_poll(
+                    &program_id,
+                    &payer,
+                    movie_a,
+                    &too_long,
+                    now() + 3600,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::TitleTooLong)
+    );
+
+    let (poll_pda, _bump_seed) = find_versus_poll_address(&program_id, movie_a, movie_b);
+    context
+        .process(
+            &[
+                create_versus_poll(&program_id, &payer, movie_a, movie_b, now() + 3600),
+                vote_versus(&program_id, &payer, &poll_pda, VersusSide::A),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let poll = context
+        .banks_client
+        .get_state::<VersusPollState>(poll_pda)
+        .await;
+    assert_eq!(
+        (poll.movie_a.as_str(), poll.movie_b.as_str()),
+        (movie_a, movie_b)
+    );
+    assert_eq!((poll.votes_a, poll.votes_b), (1, 0));
+}
+
+#[tokio::test]
+async fn test_vote_on_review_takes_one_vote_per_wallet() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let voter = Keypair::new();
+
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &payer, title);
+    context
+        .process(
+            &[
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    title,
+                    4,
+                    "Liked it",
+                    ReviewFeeAccounts::default(),
+                ),
+                system_instruction::transfer(&payer, &voter.pubkey(), 100_000_000),
+                vote_on_review(&program_id, &payer, &review_pda, true),
+                vote_on_review(&program_id, &voter.pubkey(), &review_pda, false),
+            ],
+            &[&voter],
+        )
+        .await
+        .unwrap();
+
+    // A second vote finds the receipt already there.
+    assert_eq!(
+        context
+            .process(
+                &[vote_on_review(
+                    &program_id,
+                    &voter.pubkey(),
+                    &review_pda,
+                    true
+                )],
+                &[&voter],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!((review.upvotes, review.downvotes), (1, 1));
+
+    let (vote_pda, _bump_seed) =
+        find_review_vote_address(&program_id, &review_pda, &voter.pubkey());
+    let vote = context.banks_client.get_state::<ReviewVote>(vote_pda).await;
+    assert_eq!((vote.review, vote.voter), (review_pda, voter.pubkey()));
+    assert!(!vote.is_upvote);
+}
+
+#[tokio::test]
+async fn test_vote_on_review_rejects_vote_count_overflow() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let reviewer = Pubkey::new_unique();
+    let voter = context.funded_keypair(1_000_000_000).await;
+    let downvoter = context.funded_keypair(1_000_000_000).await;
+    let title = "Captain America";
+    let (review_pda, bump_seed) = find_review_address(&program_id, &reviewer, title);
+
+    // A review that has already been upvoted as often as it can count
+    let review = MovieAccountState {
+        upvotes: u64::MAX,
+        downvotes: u64::MAX - 1,
+        bump: bump_seed,
+        ..review_state(reviewer, title, 30, "Liked the movie")
+    };
+    let mut data = review.try_to_vec().unwrap();
+    data.resize(
+        MovieAccountState::get_account_size(title.to_string(), review.description.clone()).unwrap(),
+        0,
+    );
+    context.set_account_data(review_pda, data);
+
+    assert_eq!(
+        context
+            .process(
+                &[vote_on_review(
+                    &program_id,
+                    &voter.pubkey(),
+                    &review_pda,
+                    true
+                )],
+                &[&voter],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::ArithmeticOverflow)
+    );
+
+    // The last downvote still fits, cast by another wallet.
+    context
+        .process_audited(
+            &[vote_on_review(
+                &program_id,
+                &downvoter.pubkey(),
+                &review_pda,
+                false,
+            )],
+            &[&downvoter],
+        )
+        .await
+        .unwrap();
+
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!((review.upvotes, review.downvotes), (u64::MAX, u64::MAX));
+}
+
+#[tokio::test]
+async fn test_tip_reviewer_pays_the_stored_reviewer() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let reviewer = Keypair::new();
+
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &reviewer.pubkey(), title);
+    context
+        .process(
+            &[
+                context.initialize_free_config(),
+                system_instruction::transfer(&payer, &reviewer.pubkey(), 100_000_000),
+                add_movie_review(
+                    &program_id,
+                    &reviewer.pubkey(),
+                    title,
+                    4,
+                    "Liked it",
+                    ReviewFeeAccounts::default(),
+                ),
+            ],
+            &[&reviewer],
+        )
+        .await
+        .unwrap();
+
+    // Tips only go to the reviewer the review records.
+    assert_eq!(
+        context
+            .process(
+                &[tip_reviewer(
+                    &program_id,
+                    &payer,
+                    &review_pda,
+                    &payer,
+                    1_000_000,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::IncorrectAccountError)
+    );
+
+    // The config admin can switch tips off.
+    let tip_ix = tip_reviewer(
+        &program_id,
+        &payer,
+        &review_pda,
+        &reviewer.pubkey(),
+        1_000_000,
+    );
+    assert_eq!(
+        context
+            .process(
+                &[
+                    set_disabled_features(&program_id, &payer, &[Feature::Tips]),
+                    tip_ix.clone(),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::FeatureDisabled)
+    );
+
+    let balance = context.get_balance(reviewer.pubkey()).await;
+    context
+        .process(
+            &[
+                tip_ix,
+                tip_reviewer(
+                    &program_id,
+                    &payer,
+                    &review_pda,
+                    &reviewer.pubkey(),
+                    500_000,
+                ),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context.get_balance(reviewer.pubkey()).await,
+        balance + 1_500_000
+    );
+    let review = context.banks_client.get_review(review_pda).await;
+    assert_eq!(review.tips_received, 1_500_000);
+}
+
+#[tokio::test]
+async fn test_tip_reviewer_token_pays_the_reviewers_token_account() {
+    let mut context = ReviewTestContext::with_upgrade_authority().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let reviewer = Keypair::new();
+
+    // The reviewer reviews before rewards start; the tipper earns 100
+    // reward tokens for their own review and claims them.
+    let title = "Captain America";
+    let (review_pda, _bump_seed) = find_review_address(&program_id, &reviewer.pubkey(), title);
+    let (mint, _bump_seed) = find_token_mint_address(&program_id);
+    context
+        .process(
+            &[
+                system_instruction::transfer(&payer, &reviewer.pubkey(), 100_000_000),
+                add_movie_review(
+                    &program_id,
+                    &reviewer.pubkey(),
+                    title,
+                    4,
+                    "Liked it",
+                    ReviewFeeAccounts::default(),
+                ),
+                initialize_token_mint(&program_id, &payer),
+                context.initialize_free_config(),
+                set_reward_schedule(&program_id, &payer, 100, 1, RewardHalvingUnit::Reviews),
+                add_movie_review(
+                    &program_id,
+                    &payer,
+                    "Iron Man",
+                    3,
+                    "Liked the movie",
+                    ReviewFeeAccounts {
+                        reward: true,
+                        ..ReviewFeeAccounts::default()
+                    },
+                ),
+                claim_rewards(&program_id, &payer),
+                create_associated_token_account(&payer, &reviewer.pubkey(), &mint, &spl_token::ID),
+            ],
+            &[&reviewer],
+        )
+        .await
+        .unwrap();
+
+    // Tips only go to the reviewer's own token account.
+    let tipper_ata = get_associated_token_address(&payer, &mint);
+    let reviewer_ata = get_associated_token_address(&reviewer.pubkey(), &mint);
+    assert_eq!(
+        context
+            .process(
+                &[tip_reviewer_token(
+                    &program_id,
+                    &payer,
+                    &review_pda,
+                    &payer,
+                    40,
+                )],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::IncorrectAccountError)
+    );
+
+    let tip_ix = tip_reviewer_token(&program_id, &payer, &review_pda, &reviewer.pubkey(), 40);
+    assert_eq!(
+        context
+            .process(
+                &[
+                    set_disabled_features(&program_id, &payer, &[Feature::Tips]),
+                    tip_ix.clone(),
+                ],
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(1, ReviewError::FeatureDisabled)
+    );
+
+    context.process(&[tip_ix], &[]).await.unwrap();
+
+    for (ata, amount) in [(tipper_ata, 60), (reviewer_ata, 40)] {
+        assert_eq!(context.banks_client.get_token_amount(ata).await, amount);
+    }
+}
+
+#[tokio::test]
+async fn test_review_clears_watchlist_entry() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+
+    // Adding a movie twice leaves one entry.
+    let (watchlist_pda, _bump_seed) = find_watchlist_address(&program_id, &payer, 0);
+    let add_iron_man_ix = add_to_watchlist(&program_id, &payer, 0, "Iron Man");
+    let remove_hulk_ix = remove_from_watchlist(&program_id, &payer, 0, "Hulk");
+    assert_matches!(
+        context
+            .process(
+                &[
+                    add_iron_man_ix.clone(),
+                    add_to_watchlist(&program_id, &payer, 0, "Hulk"),
+                    add_iron_man_ix,
+                    remove_hulk_ix.clone(),
+                ],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let watchlist = context
+        .banks_client
+        .get_state::<WatchlistState>(watchlist_pda)
+        .await;
+    assert_eq!(watchlist.movie_ids, vec!["Iron Man".to_string()]);
+
+    assert_eq!(
+        context
+            .process(&[remove_hulk_ix], &[])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::NotOnWatchlist)
+    );
+
+    // Reviewing the movie with the page passed takes it off.
+    assert_matches!(
+        context
+            .process(
+                &[add_movie_review(
+                    &program_id,
+                    &payer,
+                    "Iron Man",
+                    4,
+                    "Finally watched it",
+                    ReviewFeeAccounts {
+                        watchlist_page: Some(0),
+                        ..ReviewFeeAccounts::default()
+                    },
+                )],
+                &[],
+            )
+            .await,
+        Ok(_)
+    );
+
+    let watchlist = context
+        .banks_client
+        .get_state::<WatchlistState>(watchlist_pda)
+        .await;
+    assert!(watchlist.movie_ids.is_empty());
+}
+
+#[tokio::test]
+async fn test_watchlist_rejects_other_users_page() {
+    let mut context = ReviewTestContext::new().await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let other = Keypair::new();
+
+    assert_matches!(
+        context
+            .process(&[add_to_watchlist(&program_id, &payer, 3, "Iron Man")], &[])
+            .await,
+        Ok(_)
+    );
+
+    let (watchlist_pda, _bump_seed) = find_watchlist_address(&program_id, &payer, 3);
+    let mut remove_ix = remove_from_watchlist(&program_id, &other.pubkey(), 3, "Iron Man");
+    remove_ix.accounts[1].pubkey = watchlist_pda;
+    assert_eq!(
+        context
+            .process(&[remove_ix], &[&other])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        review_error(0, ReviewError::InvalidPDA)
+    );
+}
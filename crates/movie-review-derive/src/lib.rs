@@ -0,0 +1,358 @@
+//! `#[derive(Accounts)]` for movie review program handlers.
+//!
+//! Deriving `Accounts` on a struct of `&'a AccountInfo<'info>` fields
+//! generates `try_accounts`, which takes the next account for each field in
+//! declaration order and checks its `#[account(...)]` constraints:
+//!
+//! - `signer`: the account signed the transaction.
+//! - `mut`: the account was passed writable.
+//! - `address = <expr>`: the account key equals `<expr>`.
+//! - `owner = <expr>`: the account is owned by `<expr>`, usually `program_id`.
+//! - `pda = [<seed>, ...]`: the account key is the program address for the
+//!   seeds. A seed naming an earlier field uses that account's key; any other
+//!   seed is an expression whose bytes are used as is.
+//! - `bump`: stores the PDA bump seed in the `<field>_bump: u8` field.
+//!
+//! Fields without constraints are taken from the iterator unchecked.
+//!
+//! Seeds can refer to instruction arguments declared on the struct with
+//! `#[instruction(title: &str)]`; they become extra `try_accounts` parameters.
+//! Accounts left in the iterator are untouched, so handlers can pass them on.
+//!
+//! `#[derive(VersionedState)]` implements Borsh for a state struct whose first
+//! fields are `discriminator: String` and `version: u8`. Version 1 accounts
+//! predate the version field, so it is left out of their encoding as well as
+//! fields marked `#[state(since = <version>)]` for a later version, which read
+//! as their `Default`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Data, DeriveInput, Error, Expr, ExprArray, Fields, Ident, Token, Type,
+};
+
+#[proc_macro_derive(Accounts, attributes(account, instruction))]
+pub fn derive_accounts(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct Constraints {
+    signer: bool,
+    writable: bool,
+    address: Option<Expr>,
+    owner: Option<Expr>,
+    pda: Option<ExprArray>,
+    bump: bool,
+}
+
+/// An `#[instruction(name: Type)]` argument.
+struct InstructionArg {
+    ident: Ident,
+    ty: Type,
+}
+
+impl Parse for InstructionArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(Self { ident, ty })
+    }
+}
+
+struct AccountField {
+    ident: Ident,
+    ty: Type,
+    constraints: Constraints,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new(name.span(), "Accounts needs named fields")),
+        },
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "Accounts can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut args = Vec::new();
+    for attr in &input.attrs {
+        if attr.path().is_ident("instruction") {
+            let parsed =
+                attr.parse_args_with(Punctuated::<InstructionArg, Token![,]>::parse_terminated)?;
+            args.extend(parsed);
+        }
+    }
+
+    let mut accounts = Vec::new();
+    for field in fields {
+        let mut constraints = Constraints::default();
+        for attr in &field.attrs {
+            if attr.path().is_ident("account") {
+                constraints = parse_constraints(attr)?;
+            }
+        }
+        accounts.push(AccountField {
+            ident: field.ident.clone().unwrap(),
+            ty: field.ty.clone(),
+            constraints,
+        });
+    }
+
+    // `<field>_bump` fields of `bump` accounts are filled in, not taken from
+    // the accounts.
+    let bump_fields: Vec<Ident> = accounts
+        .iter()
+        .filter(|a| a.constraints.bump)
+        .map(|a| format_ident!("{}_bump", a.ident))
+        .collect();
+    if let Some(missing) = bump_fields
+        .iter()
+        .find(|bump| !accounts.iter().any(|a| a.ident == **bump))
+    {
+        return Err(Error::new(
+            name.span(),
+            format!("`bump` needs a `{}: u8` field", missing),
+        ));
+    }
+    accounts.retain(|a| !bump_fields.contains(&a.ident));
+
+    let item_ty = match accounts.first() {
+        Some(account) => &account.ty,
+        None => {
+            return Err(Error::new(
+                name.span(),
+                "Accounts needs an #[account] field",
+            ))
+        }
+    };
+
+    let mut steps = Vec::new();
+    for (index, account) in accounts.iter().enumerate() {
+        let ident = &account.ident;
+        steps.push(quote! {
+            let #ident = ::solana_program::account_info::next_account_info(account_info_iter)?;
+        });
+
+        let c = &account.constraints;
+        if c.signer {
+            steps.push(quote! {
+                if !#ident.is_signer {
+                    ::solana_program::msg!("Missing required signature");
+                    return Err(::solana_program::program_error::ProgramError::MissingRequiredSignature);
+                }
+            });
+        }
+        if c.writable {
+            steps.push(quote! {
+                if !#ident.is_writable {
+                    ::solana_program::msg!("Account {} must be writable", #ident.key);
+                    return Err(crate::error::ReviewError::AccountNotWritable.into());
+                }
+            });
+        }
+        if let Some(address) = &c.address {
+            let message = format!("Incorrect {}", ident.to_string().replace('_', " "));
+            steps.push(quote! {
+                if *#ident.key != #address {
+                    ::solana_program::msg!(#message);
+                    return Err(::solana_program::program_error::ProgramError::IncorrectProgramId);
+                }
+            });
+        }
+        if let Some(owner) = &c.owner {
+            steps.push(quote! {
+                if #ident.owner != #owner {
+                    return Err(::solana_program::program_error::ProgramError::IllegalOwner);
+                }
+            });
+        }
+        if let Some(pda) = &c.pda {
+            let earlier: Vec<&Ident> = accounts[..index].iter().map(|a| &a.ident).collect();
+            let seeds = pda.elems.iter().map(|seed| match seed {
+                Expr::Path(path) if earlier.iter().any(|e| path.path.is_ident(*e)) => {
+                    quote! { #seed.key.as_ref() }
+                }
+                _ => quote! { ::core::convert::AsRef::<[u8]>::as_ref(&#seed) },
+            });
+            let bump = format_ident!("{}_bump", ident);
+            let bump_binding = if c.bump { quote!(#bump) } else { quote!(_) };
+            steps.push(quote! {
+                let (__pda, #bump_binding) = ::solana_program::pubkey::Pubkey::find_program_address(
+                    &[#(#seeds),*],
+                    program_id,
+                );
+                if __pda != *#ident.key {
+                    ::solana_program::msg!("Invalid seeds for PDA");
+                    return Err(crate::error::ReviewError::InvalidPDA.into());
+                }
+            });
+        } else if c.bump {
+            return Err(Error::new(ident.span(), "`bump` needs `pda = [...]`"));
+        }
+    }
+
+    let idents = accounts.iter().map(|a| &a.ident);
+    let arg_params = args
+        .iter()
+        .map(|InstructionArg { ident, ty }| quote! { #ident: #ty });
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Takes this instruction's accounts from `account_info_iter` and
+            /// checks their `#[account]` constraints.
+            #[allow(clippy::too_many_arguments)]
+            pub fn try_accounts<I>(
+                program_id: &::solana_program::pubkey::Pubkey,
+                account_info_iter: &mut I,
+                #(#arg_params),*
+            ) -> ::core::result::Result<Self, ::solana_program::program_error::ProgramError>
+            where
+                I: ::core::iter::Iterator<Item = #item_ty>,
+            {
+                #(#steps)*
+                Ok(Self {
+                    #(#idents,)*
+                    #(#bump_fields,)*
+                })
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(VersionedState, attributes(state))]
+pub fn derive_versioned_state(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_versioned_state(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_versioned_state(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new(name.span(), "VersionedState needs named fields")),
+        },
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "VersionedState can only be derived for structs",
+            ))
+        }
+    };
+
+    let idents: Vec<&Ident> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    if idents.len() < 2 || idents[0] != "discriminator" || idents[1] != "version" {
+        return Err(Error::new(
+            name.span(),
+            "VersionedState needs `discriminator` and `version` as its first fields",
+        ));
+    }
+
+    let mut body = Vec::new();
+    for field in fields.iter().skip(2) {
+        let mut since = None;
+        for attr in &field.attrs {
+            if attr.path().is_ident("state") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("since") {
+                        since = Some(meta.value()?.parse::<syn::LitInt>()?.base10_parse::<u8>()?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("unknown state attribute"))
+                    }
+                })?;
+            }
+        }
+        body.push((field.ident.as_ref().unwrap(), since));
+    }
+
+    let serialize = body.iter().map(|(ident, since)| match since {
+        Some(since) => quote! {
+            if self.version >= #since {
+                ::borsh::BorshSerialize::serialize(&self.#ident, writer)?;
+            }
+        },
+        None => quote! {
+            ::borsh::BorshSerialize::serialize(&self.#ident, writer)?;
+        },
+    });
+    let deserialize = body.iter().map(|(ident, since)| match since {
+        Some(since) => quote! {
+            #ident: if version >= #since {
+                ::borsh::BorshDeserialize::deserialize(buf)?
+            } else {
+                ::core::default::Default::default()
+            },
+        },
+        None => quote! {
+            #ident: ::borsh::BorshDeserialize::deserialize(buf)?,
+        },
+    });
+
+    Ok(quote! {
+        impl ::borsh::BorshSerialize for #name {
+            fn serialize<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+                ::borsh::BorshSerialize::serialize(&self.discriminator, writer)?;
+                if self.version > 1 {
+                    ::borsh::BorshSerialize::serialize(&self.version, writer)?;
+                }
+                #(#serialize)*
+                Ok(())
+            }
+        }
+
+        impl ::borsh::BorshDeserialize for #name {
+            fn deserialize(buf: &mut &[u8]) -> ::std::io::Result<Self> {
+                let discriminator: String = ::borsh::BorshDeserialize::deserialize(buf)?;
+                let version = crate::state::read_version(&discriminator, buf)?;
+                Ok(Self {
+                    discriminator,
+                    version,
+                    #(#deserialize)*
+                })
+            }
+        }
+    })
+}
+
+fn parse_constraints(attr: &syn::Attribute) -> syn::Result<Constraints> {
+    let mut constraints = Constraints::default();
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("signer") {
+            constraints.signer = true;
+        } else if meta.path.is_ident("mut") {
+            constraints.writable = true;
+        } else if meta.path.is_ident("bump") {
+            constraints.bump = true;
+        } else if meta.path.is_ident("address") {
+            constraints.address = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("owner") {
+            constraints.owner = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("pda") {
+            constraints.pda = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("unknown account constraint"));
+        }
+        Ok(())
+    })?;
+    Ok(constraints)
+}
@@ -0,0 +1,24 @@
+//! Account data as handlers load it. Decoding must never panic, and a decoded
+//! review must encode to bytes that decode to the same encoding again, in
+//! whichever layout version it was read as.
+
+#![no_main]
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    libfuzzer_sys::fuzz_target,
+    movie_review::{accounts::parse_any, state::MovieAccountState},
+    solana_program::borsh::try_from_slice_unchecked,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_any(data);
+    let _ = MovieAccountState::try_from_slice(data);
+
+    // Accounts are padded past their contents, so handlers ignore the rest.
+    if let Ok(review) = try_from_slice_unchecked::<MovieAccountState>(data) {
+        let encoded = review.try_to_vec().unwrap();
+        let decoded = MovieAccountState::try_from_slice(&encoded).unwrap();
+        assert_eq!(decoded.try_to_vec().unwrap(), encoded);
+    }
+});
@@ -0,0 +1,21 @@
+//! Instruction data as the entrypoint receives it. Decoding must never panic,
+//! and whatever decodes must pack back to data that decodes the same way.
+
+#![no_main]
+
+use {
+    libfuzzer_sys::fuzz_target,
+    movie_review::instruction::{split_instruction_version, MovieInstruction},
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((_version, payload)) = split_instruction_version(data) else {
+        return;
+    };
+    if let Ok(instruction) = MovieInstruction::unpack(payload) {
+        assert_eq!(
+            MovieInstruction::unpack(&instruction.pack()),
+            Ok(instruction)
+        );
+    }
+});
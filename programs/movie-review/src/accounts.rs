@@ -0,0 +1,247 @@
+//! Typed views over program-owned accounts. Constructing one performs the
+//! owner, closed-marker, initialization, discriminator and (for `load_mut`)
+//! writability checks every handler would otherwise repeat by hand.
+
+use crate::error::ReviewError;
+use crate::processor::load_account;
+use crate::state::{
+    self, AccessKeyHintState, AclState, AggregateSnapshot, AirdropClaimStatus, AirdropState,
+    ConfigState, EscrowState, HandleState, InboxState, MovieAccountState, MovieComment,
+    MovieCommentCounter, MovieSummary, PremiumContentState, ProgramVersionState, ReviewIndexState,
+    ReviewVote, ReviewerProfile, SubscriptionState, TreasuryState, VersusPollState,
+    VersusVoteReceipt, VestingState, WatchAttestation, WatchlistState,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo, borsh::try_from_slice_unchecked, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, program_pack::IsInitialized, pubkey::Pubkey,
+};
+use std::ops::{Deref, DerefMut};
+
+/// State stored in a program-owned account behind a leading discriminator.
+pub trait AccountState: BorshSerialize + BorshDeserialize + IsInitialized {
+    const DISCRIMINATOR: &'static str;
+    const KIND: AccountKind;
+
+    fn discriminator(&self) -> &str;
+
+    fn kind(&self) -> AccountKind {
+        Self::KIND
+    }
+}
+
+/// Registers every state struct under its `AccountKind`, generating the
+/// `AccountState` impls, `AnyAccount` and the kind-to-type dispatch from the
+/// one list.
+macro_rules! account_kinds {
+    ($($kind:ident => $state:ty),* $(,)?) => {
+        /// Every kind of state account the program owns, one per
+        /// discriminator.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum AccountKind {
+            $($kind,)*
+        }
+
+        impl AccountKind {
+            pub const ALL: &'static [AccountKind] = &[$(AccountKind::$kind,)*];
+
+            pub fn discriminator(self) -> &'static str {
+                match self {
+                    $(AccountKind::$kind => <$state>::DISCRIMINATOR,)*
+                }
+            }
+
+            pub fn from_discriminator(discriminator: &str) -> Option<Self> {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|kind| kind.discriminator() == discriminator)
+            }
+        }
+
+        /// Program account state of any kind, as decoded by `parse_any`.
+        #[derive(Debug)]
+        pub enum AnyAccount {
+            $($kind($state),)*
+        }
+
+        impl AnyAccount {
+            pub fn kind(&self) -> AccountKind {
+                match self {
+                    $(AnyAccount::$kind(_) => AccountKind::$kind,)*
+                }
+            }
+        }
+
+        fn parse_kind(kind: AccountKind, data: &[u8]) -> Result<AnyAccount, ProgramError> {
+            Ok(match kind {
+                $(AccountKind::$kind => AnyAccount::$kind(
+                    try_from_slice_unchecked(data).map_err(|_| ProgramError::InvalidAccountData)?,
+                ),)*
+            })
+        }
+
+        $(
+            impl AccountState for $state {
+                const DISCRIMINATOR: &'static str = <$state>::DISCRIMINATOR;
+                const KIND: AccountKind = AccountKind::$kind;
+
+                fn discriminator(&self) -> &str {
+                    &self.discriminator
+                }
+            }
+        )*
+    };
+}
+
+account_kinds!(
+    Review => MovieAccountState,
+    CommentCounter => MovieCommentCounter,
+    Comment => MovieComment,
+    Airdrop => AirdropState,
+    AirdropClaims => AirdropClaimStatus,
+    Config => ConfigState,
+    Treasury => TreasuryState,
+    Vesting => VestingState,
+    Escrow => EscrowState,
+    ProgramVersion => ProgramVersionState,
+    WatchAttestation => WatchAttestation,
+    Profile => ReviewerProfile,
+    Handle => HandleState,
+    Inbox => InboxState,
+    Subscription => SubscriptionState,
+    PremiumContent => PremiumContentState,
+    AccessKeyHint => AccessKeyHintState,
+    Acl => AclState,
+    VersusPoll => VersusPollState,
+    VersusVote => VersusVoteReceipt,
+    Watchlist => WatchlistState,
+    ReviewVoteReceipt => ReviewVote,
+    Summary => MovieSummary,
+    Snapshot => AggregateSnapshot,
+    ReviewIndex => ReviewIndexState,
+);
+
+/// Decodes the data of any program-owned account, picking the type by its
+/// discriminator. Closed accounts fail with `AccountClosed`; data that is not
+/// program state fails with `InvalidAccountData`.
+pub fn parse_any(data: &[u8]) -> Result<AnyAccount, ProgramError> {
+    if state::is_closed(data) {
+        return Err(ReviewError::AccountClosed.into());
+    }
+    let discriminator =
+        try_from_slice_unchecked::<String>(data).map_err(|_| ProgramError::InvalidAccountData)?;
+    let kind =
+        AccountKind::from_discriminator(&discriminator).ok_or(ProgramError::InvalidAccountData)?;
+    parse_kind(kind, data)
+}
+
+/// An account owned by the program holding initialized `T` state. Derefs to
+/// the state; `save` writes changes back.
+pub struct TypedAccount<'a, 'info, T: AccountState> {
+    info: &'a AccountInfo<'info>,
+    data: T,
+    writable: bool,
+}
+
+pub type ReviewAccount<'a, 'info> = TypedAccount<'a, 'info, MovieAccountState>;
+pub type CommentAccount<'a, 'info> = TypedAccount<'a, 'info, MovieComment>;
+pub type ConfigAccount<'a, 'info> = TypedAccount<'a, 'info, ConfigState>;
+pub type ProfileAccount<'a, 'info> = TypedAccount<'a, 'info, ReviewerProfile>;
+pub type HandleAccount<'a, 'info> = TypedAccount<'a, 'info, HandleState>;
+pub type AirdropAccount<'a, 'info> = TypedAccount<'a, 'info, AirdropState>;
+pub type VestingAccount<'a, 'info> = TypedAccount<'a, 'info, VestingState>;
+pub type EscrowAccount<'a, 'info> = TypedAccount<'a, 'info, EscrowState>;
+pub type VersusPollAccount<'a, 'info> = TypedAccount<'a, 'info, VersusPollState>;
+pub type WatchlistAccount<'a, 'info> = TypedAccount<'a, 'info, WatchlistState>;
+pub type SummaryAccount<'a, 'info> = TypedAccount<'a, 'info, MovieSummary>;
+pub type ReviewIndexAccount<'a, 'info> = TypedAccount<'a, 'info, ReviewIndexState>;
+
+impl<'a, 'info, T: AccountState> TypedAccount<'a, 'info, T> {
+    /// Loads `info` for reading.
+    pub fn load(program_id: &Pubkey, info: &'a AccountInfo<'info>) -> Result<Self, ProgramError> {
+        if info.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let data = load_account::<T>(info)?;
+        if !data.is_initialized() {
+            msg!("Account is not initialized");
+            return Err(ReviewError::UninitializedAccount.into());
+        }
+
+        if data.discriminator() != T::DISCRIMINATOR {
+            msg!("Account {} is not a {} account", info.key, T::DISCRIMINATOR);
+            return Err(ReviewError::InvalidAccountType.into());
+        }
+
+        Ok(Self {
+            info,
+            data,
+            writable: false,
+        })
+    }
+
+    /// Loads `info` for modification, checking it was passed writable.
+    pub fn load_mut(
+        program_id: &Pubkey,
+        info: &'a AccountInfo<'info>,
+    ) -> Result<Self, ProgramError> {
+        let mut account = Self::load(program_id, info)?;
+        if !info.is_writable {
+            msg!("Account {} must be writable", info.key);
+            return Err(ReviewError::AccountNotWritable.into());
+        }
+        account.writable = true;
+        Ok(account)
+    }
+
+    pub fn info(&self) -> &'a AccountInfo<'info> {
+        self.info
+    }
+
+    pub fn key(&self) -> &'a Pubkey {
+        self.info.key
+    }
+
+    /// Serializes the state back into the account.
+    pub fn save(&self) -> ProgramResult {
+        if !self.writable {
+            msg!("Account {} was loaded read-only", self.info.key);
+            return Err(ReviewError::AccountNotWritable.into());
+        }
+        self.data
+            .serialize(&mut &mut self.info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+}
+
+impl<'a, 'info, T: AccountState> TryFrom<(&Pubkey, &'a AccountInfo<'info>)>
+    for TypedAccount<'a, 'info, T>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (program_id, info): (&Pubkey, &'a AccountInfo<'info>),
+    ) -> Result<Self, Self::Error> {
+        Self::load(program_id, info)
+    }
+}
+
+impl<T: AccountState> Deref for TypedAccount<'_, '_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<T: AccountState> DerefMut for TypedAccount<'_, '_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
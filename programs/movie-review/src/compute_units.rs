@@ -0,0 +1,146 @@
+//! Upper estimates of the compute units each instruction uses, for clients
+//! sizing a `SetComputeUnitLimit`. Bump an estimate whenever a handler grows;
+//! a transaction that runs out of units fails even though every check passed.
+
+use crate::instruction::MovieInstruction;
+
+/// Most compute units a transaction can request.
+pub const MAX_TRANSACTION_UNITS: u32 = 1_400_000;
+
+/// Added for each account past the ones an instruction always takes: fee and
+/// reward accounts, mentioned inboxes, accounts to purge or reclaim.
+pub const PER_EXTRA_ACCOUNT: u32 = 10_000;
+
+/// Added for instructions the config can switch off, which look it up before
+/// running.
+pub const FEATURE_CHECK: u32 = 5_000;
+
+pub const ADD_MOVIE_REVIEW: u32 = 80_000;
+pub const UPDATE_MOVIE_REVIEW: u32 = 30_000;
+pub const DELETE_MOVIE_REVIEW: u32 = 20_000;
+pub const ADD_COMMENT: u32 = 70_000;
+pub const UPDATE_COMMENT: u32 = 25_000;
+pub const DELETE_COMMENT: u32 = 15_000;
+pub const PUBLISH_REVIEW: u32 = 30_000;
+pub const INITIALIZE_TOKEN_MINT: u32 = 40_000;
+pub const INITIALIZE_CONFIG: u32 = 30_000;
+pub const INITIALIZE_PROGRAM: u32 = 90_000;
+pub const SET_AIRDROP_ROOT: u32 = 30_000;
+pub const CLAIM_AIRDROP: u32 = 90_000;
+pub const PURGE_EXPIRED: u32 = 15_000;
+pub const MARK_NOTIFICATIONS_READ: u32 = 25_000;
+pub const SUBSCRIBE: u32 = 35_000;
+/// Review-scoped accounts: premium content, key hints and ACLs.
+pub const CREATE_REVIEW_ACCOUNT: u32 = 30_000;
+pub const ATTEST_WATCHED: u32 = 25_000;
+/// Admin setters that only rewrite the config.
+pub const SET_CONFIG: u32 = 10_000;
+/// Reviewer-signed changes to a single review.
+pub const SET_REVIEW_FIELD: u32 = 10_000;
+pub const REGISTER_HANDLE: u32 = 35_000;
+pub const RELEASE_HANDLE: u32 = 20_000;
+pub const CLAIM_REWARDS: u32 = 70_000;
+pub const CLAIM_VESTED: u32 = 70_000;
+pub const INITIALIZE_REVIEWER_PROFILE: u32 = 25_000;
+pub const SYNC_PROGRAM_VERSION: u32 = 25_000;
+pub const MIGRATE_LEGACY_REVIEW: u32 = 20_000;
+pub const MIGRATE_REVIEW: u32 = 20_000;
+pub const RECLAIM_ORPHANS: u32 = 20_000;
+pub const FUND_ESCROW: u32 = 35_000;
+pub const ESCROW_TRANSITION: u32 = 15_000;
+pub const EXPIRE_REVIEW: u32 = 10_000;
+pub const ARCHIVE_REVIEW: u32 = 15_000;
+pub const CREATE_VERSUS_POLL: u32 = 30_000;
+pub const VOTE_VERSUS: u32 = 30_000;
+pub const FINALIZE_VERSUS_POLL: u32 = 40_000;
+pub const ADD_TO_WATCHLIST: u32 = 30_000;
+pub const REMOVE_FROM_WATCHLIST: u32 = 15_000;
+pub const VOTE_ON_REVIEW: u32 = 30_000;
+pub const TIP_REVIEWER: u32 = 15_000;
+pub const TIP_REVIEWER_TOKEN: u32 = 25_000;
+pub const SNAPSHOT_AGGREGATE: u32 = 30_000;
+pub const INDEX_REVIEWS: u32 = 30_000;
+pub const EXPORT_MY_REVIEWS: u32 = 10_000;
+
+/// Estimated units for `instruction` when passed `extra_accounts` accounts
+/// beyond the ones it always takes.
+pub fn estimate(instruction: &MovieInstruction, extra_accounts: usize) -> u32 {
+    use MovieInstruction::*;
+
+    let base = match instruction {
+        AddMovieReview { .. }
+        | AddDraftReview { .. }
+        | AddEntityReview { .. }
+        | AddMovieReviewIdempotent { .. } => ADD_MOVIE_REVIEW,
+        UpdateMovieReview { .. } => UPDATE_MOVIE_REVIEW,
+        DeleteMovieReview => DELETE_MOVIE_REVIEW,
+        AddComment { .. } => ADD_COMMENT,
+        UpdateComment { .. } => UPDATE_COMMENT,
+        DeleteComment => DELETE_COMMENT,
+        PublishReview => PUBLISH_REVIEW,
+        InitializeTokenMint => INITIALIZE_TOKEN_MINT,
+        InitializeConfig { .. } | InitializeConfigIdempotent { .. } => INITIALIZE_CONFIG,
+        InitializeProgram { .. } => INITIALIZE_PROGRAM,
+        SetAirdropRoot { .. } => SET_AIRDROP_ROOT,
+        ClaimAirdrop { .. } => CLAIM_AIRDROP,
+        PurgeExpired => PURGE_EXPIRED,
+        MarkNotificationsRead { .. } => MARK_NOTIFICATIONS_READ,
+        Subscribe { .. } => SUBSCRIBE,
+        SetPremiumContent { .. }
+        | GrantAccessKeyHint { .. }
+        | GrantAccess { .. }
+        | RevokeAccess { .. }
+        | SetCommentCapOverride { .. } => CREATE_REVIEW_ACCOUNT,
+        AttestWatched { .. } | AttestEntityWatched { .. } => ATTEST_WATCHED,
+        SetWatchRequirement { .. }
+        | SetCommentLimit { .. }
+        | SetCommentEditWindow { .. }
+        | SetRewardSchedule { .. }
+        | SetVestingPolicy { .. }
+        | SetReferralBonus { .. }
+        | SetEscrowPolicy { .. }
+        | SetDisabledFeatures { .. }
+        | SetReviewLengthLimits { .. }
+        | SetPaused { .. }
+        | SetSnapshotInterval { .. }
+        | SetCommentReward { .. } => SET_CONFIG,
+        SetVisibility { .. } | SchedulePublish { .. } | SetReviewExpiry { .. } => SET_REVIEW_FIELD,
+        RegisterHandle { .. } => REGISTER_HANDLE,
+        ReleaseHandle => RELEASE_HANDLE,
+        ClaimRewards => CLAIM_REWARDS,
+        ClaimVested => CLAIM_VESTED,
+        #[cfg(feature = "test-utils")]
+        MintTestTokens { .. } => CLAIM_REWARDS,
+        #[cfg(feature = "test-utils")]
+        SetTestProfile { .. } => INITIALIZE_REVIEWER_PROFILE,
+        InitializeReviewerProfile | InitializeReviewerProfileIdempotent => {
+            INITIALIZE_REVIEWER_PROFILE
+        }
+        SyncProgramVersion => SYNC_PROGRAM_VERSION,
+        MigrateLegacyReview => MIGRATE_LEGACY_REVIEW,
+        ReclaimOrphans => RECLAIM_ORPHANS,
+        FundEscrow { .. } => FUND_ESCROW,
+        DisputeEscrow | ResolveEscrow { .. } | SettleEscrow | CloseEscrow => ESCROW_TRANSITION,
+        ExpireReview { .. } => EXPIRE_REVIEW,
+        ArchiveReview => ARCHIVE_REVIEW,
+        CreateVersusPoll { .. } => CREATE_VERSUS_POLL,
+        VoteVersus { .. } => VOTE_VERSUS,
+        FinalizeVersusPoll => FINALIZE_VERSUS_POLL,
+        AddToWatchlist { .. } => ADD_TO_WATCHLIST,
+        RemoveFromWatchlist { .. } => REMOVE_FROM_WATCHLIST,
+        VoteOnReview { .. } => VOTE_ON_REVIEW,
+        TipReviewer { .. } => TIP_REVIEWER,
+        TipReviewerToken { .. } => TIP_REVIEWER_TOKEN,
+        MigrateReview => MIGRATE_REVIEW,
+        SnapshotAggregate => SNAPSHOT_AGGREGATE,
+        IndexReviews { .. } => INDEX_REVIEWS,
+        ExportMyReviews { .. } => EXPORT_MY_REVIEWS,
+    };
+    let base = match instruction.feature() {
+        Some(_) => base.saturating_add(FEATURE_CHECK),
+        None => base,
+    };
+    base.saturating_add(
+        PER_EXTRA_ACCOUNT.saturating_mul(u32::try_from(extra_accounts).unwrap_or(u32::MAX)),
+    )
+}
@@ -0,0 +1,165 @@
+//! Helpers for programs that CPI into this one. Each builds the instruction
+//! from the accounts in a `CpiContext` and invokes it, signing with the
+//! context's seeds for accounts the calling program owns as PDAs.
+
+use crate::instruction::MovieInstruction;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+/// Accounts an instruction always takes, in the order the program reads them.
+pub trait CpiAccounts<'info> {
+    fn to_account_metas(&self) -> Vec<AccountMeta>;
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>>;
+}
+
+/// The movie review program, the accounts an instruction always takes and
+/// any optional ones after them, such as fee or reward accounts.
+pub struct CpiContext<'a, 'info, T> {
+    pub program: AccountInfo<'info>,
+    pub accounts: T,
+    pub remaining_accounts: Vec<AccountInfo<'info>>,
+    pub signer_seeds: &'a [&'a [&'a [u8]]],
+}
+
+impl<'a, 'info, T: CpiAccounts<'info>> CpiContext<'a, 'info, T> {
+    pub fn new(program: AccountInfo<'info>, accounts: T) -> Self {
+        Self::new_with_signer(program, accounts, &[])
+    }
+
+    pub fn new_with_signer(
+        program: AccountInfo<'info>,
+        accounts: T,
+        signer_seeds: &'a [&'a [&'a [u8]]],
+    ) -> Self {
+        Self {
+            program,
+            accounts,
+            remaining_accounts: Vec::new(),
+            signer_seeds,
+        }
+    }
+
+    pub fn with_remaining_accounts(mut self, accounts: Vec<AccountInfo<'info>>) -> Self {
+        self.remaining_accounts = accounts;
+        self
+    }
+
+    /// Invokes `instruction`, tagged with this build's instruction version,
+    /// with the context's accounts. Remaining accounts keep the signer and
+    /// writable flags they were passed with.
+    fn invoke(self, instruction: MovieInstruction) -> ProgramResult {
+        let mut accounts = self.accounts.to_account_metas();
+        accounts.extend(self.remaining_accounts.iter().map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }));
+        let instruction = Instruction {
+            program_id: *self.program.key,
+            accounts,
+            data: instruction.pack_versioned(),
+        };
+
+        let mut infos = self.accounts.to_account_infos();
+        infos.extend(self.remaining_accounts);
+        infos.push(self.program);
+        invoke_signed(&instruction, &infos, self.signer_seeds)
+    }
+}
+
+pub struct AddMovieReview<'info> {
+    pub reviewer: AccountInfo<'info>,
+    pub review: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    /// The title's `MovieSummary`, created by its first review.
+    pub summary: AccountInfo<'info>,
+}
+
+impl<'info> CpiAccounts<'info> for AddMovieReview<'info> {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*self.reviewer.key, true),
+            AccountMeta::new(*self.review.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+            AccountMeta::new_readonly(*self.config.key, false),
+            AccountMeta::new(*self.summary.key, false),
+        ]
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.reviewer.clone(),
+            self.review.clone(),
+            self.system_program.clone(),
+            self.config.clone(),
+            self.summary.clone(),
+        ]
+    }
+}
+
+/// Adds a movie review by `reviewer`, who must sign the calling instruction
+/// or be a PDA signed for by `ctx`.
+pub fn add_movie_review<'info>(
+    ctx: CpiContext<'_, 'info, AddMovieReview<'info>>,
+    title: String,
+    rating: u8,
+    description: String,
+) -> ProgramResult {
+    ctx.invoke(MovieInstruction::AddMovieReview {
+        title,
+        rating,
+        description,
+    })
+}
+
+/// Accounts for `update_movie_review`, the same ones adding the review took.
+pub type UpdateMovieReview<'info> = AddMovieReview<'info>;
+
+pub fn update_movie_review<'info>(
+    ctx: CpiContext<'_, 'info, UpdateMovieReview<'info>>,
+    title: String,
+    rating: u8,
+    description: String,
+) -> ProgramResult {
+    ctx.invoke(MovieInstruction::UpdateMovieReview {
+        title,
+        rating,
+        description,
+    })
+}
+
+pub struct DeleteMovieReview<'info> {
+    pub reviewer: AccountInfo<'info>,
+    pub review: AccountInfo<'info>,
+    pub summary: AccountInfo<'info>,
+}
+
+impl<'info> CpiAccounts<'info> for DeleteMovieReview<'info> {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*self.reviewer.key, true),
+            AccountMeta::new(*self.review.key, false),
+            AccountMeta::new(*self.summary.key, false),
+        ]
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.reviewer.clone(),
+            self.review.clone(),
+            self.summary.clone(),
+        ]
+    }
+}
+
+/// Deletes `reviewer`'s review, refunding its rent to them.
+pub fn delete_movie_review<'info>(
+    ctx: CpiContext<'_, 'info, DeleteMovieReview<'info>>,
+) -> ProgramResult {
+    ctx.invoke(MovieInstruction::DeleteMovieReview)
+}
@@ -0,0 +1,206 @@
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+use thiserror::Error;
+
+/// Errors the program fails with, as `ProgramError::Custom(code)`. Clients
+/// match on the codes, so a variant keeps its number for good and new ones
+/// are added at the end.
+#[derive(Debug, Error, FromPrimitive)]
+pub enum ReviewError {
+    #[error("Account not initialized yet")]
+    UninitializedAccount = 0,
+
+    #[error("PDA derived does not equal PDA passed in")]
+    InvalidPDA = 1,
+
+    #[error("Input data exceeds max length")]
+    InvalidDataLength = 2,
+
+    #[error("Rating must be 10 to 50 tenths of a star, or 1 to 5 whole stars")]
+    InvalidRating = 3,
+
+    #[error("Accounts do not match")]
+    IncorrectAccountError = 4,
+
+    #[error("Signer is not the expected authority")]
+    InvalidAuthority = 5,
+
+    #[error("Merkle proof does not match the airdrop root")]
+    InvalidMerkleProof = 6,
+
+    #[error("Airdrop already claimed")]
+    AirdropAlreadyClaimed = 7,
+
+    #[error("Price feed account is invalid")]
+    InvalidPriceFeed = 8,
+
+    #[error("Price feed has not updated recently enough")]
+    StalePriceFeed = 9,
+
+    #[error("Account is not eligible for purging")]
+    AccountNotPurgeable = 10,
+
+    #[error("Account has been closed")]
+    AccountClosed = 11,
+
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow = 12,
+
+    #[error("Subscription must last at least one month")]
+    InvalidSubscriptionLength = 13,
+
+    #[error("An active subscription is required")]
+    SubscriptionRequired = 14,
+
+    #[error("Review is not premium content")]
+    NotPremiumContent = 15,
+
+    #[error("Access list is full")]
+    AclFull = 16,
+
+    #[error("Review is already published")]
+    AlreadyPublished = 17,
+
+    #[error("Scheduled publication time has not been reached")]
+    PublishTimeNotReached = 18,
+
+    #[error("Reviewer has not attested to watching the movie")]
+    WatchAttestationRequired = 19,
+
+    #[error("Timestamp is in the future")]
+    InvalidTimestamp = 20,
+
+    #[error("Review has reached its comment limit")]
+    CommentLimitReached = 21,
+
+    #[error("Comment can no longer be edited")]
+    CommentLocked = 22,
+
+    #[error("Handle must be 3-32 characters of a-z, 0-9 or _")]
+    InvalidHandle = 23,
+
+    #[error("Handle is already registered")]
+    HandleTaken = 24,
+
+    #[error("Profile already has a handle")]
+    HandleAlreadySet = 25,
+
+    #[error("Referrals only apply to a reviewer's first review")]
+    ReferralNotAllowed = 26,
+
+    #[error("Account must be writable")]
+    AccountNotWritable = 27,
+
+    #[error("Instruction version is not supported by this program")]
+    UnsupportedInstructionVersion = 28,
+
+    #[error("Review account is already sized to its contents")]
+    AlreadyMigrated = 29,
+
+    #[error("Account is not orphaned")]
+    AccountNotOrphaned = 30,
+
+    #[error("Escrow arbiter is not configured")]
+    EscrowNotConfigured = 31,
+
+    #[error("Escrow is not in the required state")]
+    InvalidEscrowStatus = 32,
+
+    #[error("Escrow dispute window has closed")]
+    DisputeWindowClosed = 33,
+
+    #[error("Escrow cannot be settled yet")]
+    EscrowLocked = 34,
+
+    #[error("Review has expired")]
+    ReviewExpired = 35,
+
+    #[error("Review has not expired")]
+    ReviewNotExpired = 36,
+
+    #[error("Accounts are not in ascending key order")]
+    AccountsOutOfOrder = 37,
+
+    #[error("Instruction is disabled by the config")]
+    FeatureDisabled = 38,
+
+    #[error("A versus poll needs two different movies")]
+    InvalidVersusPair = 39,
+
+    #[error("Poll has ended")]
+    PollClosed = 40,
+
+    #[error("Poll has not ended yet")]
+    PollOpen = 41,
+
+    #[error("Watchlist page is full")]
+    WatchlistPageFull = 42,
+
+    #[error("Movie is not on the watchlist page")]
+    NotOnWatchlist = 43,
+
+    #[error("Account holds a different kind of state")]
+    InvalidAccountType = 44,
+
+    #[error("Review has been archived")]
+    ReviewArchived = 45,
+
+    #[error("Program is paused by the config")]
+    ProgramPaused = 46,
+
+    #[error("Account data could not be deserialized")]
+    DeserializationFailed = 47,
+
+    #[error("Review title is too long")]
+    TitleTooLong = 48,
+
+    #[error("Review description is too long")]
+    DescriptionTooLong = 49,
+
+    #[error("Comment is too long")]
+    CommentTooLong = 50,
+
+    #[error("Field cannot be empty")]
+    EmptyField = 51,
+
+    #[error("Account is already at the current layout version")]
+    AlreadyCurrentVersion = 52,
+
+    #[error("Review already exists")]
+    ReviewAlreadyExists = 53,
+
+    #[error("Signer is not the review's reviewer")]
+    UnauthorizedReviewer = 54,
+
+    #[error("Rating snapshots are not configured")]
+    SnapshotsNotConfigured = 55,
+
+    #[error("Review index page is full")]
+    ReviewIndexPageFull = 56,
+}
+
+impl From<ReviewError> for ProgramError {
+    fn from(e: ReviewError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for ReviewError {
+    fn type_of() -> &'static str {
+        "ReviewError"
+    }
+}
+
+impl PrintProgramError for ReviewError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("Error: {}", self);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,60 @@
+//! Events the program logs with `sol_log_data`, so indexers can follow
+//! reviews and comments without parsing `msg!` output. Each is logged as a
+//! single field holding the Borsh-encoded `Event`, which shows up in the
+//! transaction logs as a base64 `Program data:` line.
+
+use crate::state::{EntityKind, ReviewStatus};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey};
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ReviewAdded {
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub kind: EntityKind,
+    pub status: ReviewStatus,
+    pub title: String,
+    /// In tenths of a star.
+    pub rating: u8,
+    pub description: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ReviewUpdated {
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    /// In tenths of a star.
+    pub rating: u8,
+    pub description: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct CommentAdded {
+    pub review: Pubkey,
+    pub comment_account: Pubkey,
+    pub commenter: Pubkey,
+    /// Position of the comment on the review, from 0.
+    pub count: u64,
+    pub comment: String,
+}
+
+/// New variants go at the end, since the variant index is the event's tag.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum Event {
+    ReviewAdded(ReviewAdded),
+    ReviewUpdated(ReviewUpdated),
+    CommentAdded(CommentAdded),
+}
+
+impl Event {
+    pub fn emit(&self) -> ProgramResult {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
+    }
+
+    /// Decodes the data of one `sol_log_data` field, `None` if it isn't an
+    /// event.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        Self::try_from_slice(data).ok()
+    }
+}
@@ -0,0 +1,1200 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{
+    EncryptionScheme, EntityKind, Feature, RewardHalvingUnit, VersusSide, Visibility,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Instruction version this build sends and understands. Bump it whenever an
+/// instruction's accounts or payload change incompatibly, and raise the
+/// changed instructions' `MovieInstruction::min_version` to match.
+///
+/// Version 2 passes each review's title summary to the instructions that
+/// change what the summary counts, and both movies' summaries to
+/// `FinalizeVersusPoll`.
+pub const INSTRUCTION_VERSION: u16 = 2;
+
+/// Oldest instruction version still accepted for any instruction. Raise it
+/// once clients sending older instructions are no longer supported; until
+/// then unchanged instructions keep working unversioned.
+pub const MIN_INSTRUCTION_VERSION: u16 = 0;
+
+/// First byte of a versioned instruction, followed by the little-endian `u16`
+/// version and then the instruction itself. No variant uses this byte.
+pub const VERSIONED_INSTRUCTION_TAG: u8 = 0xff;
+
+/// Splits the version off a versioned instruction. Unversioned instructions
+/// are version 0.
+pub fn split_instruction_version(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+    match input.split_first() {
+        Some((&VERSIONED_INSTRUCTION_TAG, rest)) => {
+            if rest.len() < 2 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let (version, instruction) = rest.split_at(2);
+            Ok((u16::from_le_bytes([version[0], version[1]]), instruction))
+        }
+        _ => Ok((0, input)),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MovieInstruction {
+    AddMovieReview {
+        title: String,
+        /// Tenths of a star, 10 to 50, as in every review instruction. Whole
+        /// stars, 1 to 5, from older clients are still accepted.
+        rating: u8,
+        description: String,
+    },
+    UpdateMovieReview {
+        title: String,
+        /// As in `AddMovieReview`. Updating a review written in whole stars
+        /// stores it in tenths.
+        rating: u8,
+        description: String,
+    },
+    AddComment {
+        comment: String,
+    },
+    InitializeTokenMint,
+    SetAirdropRoot {
+        merkle_root: [u8; 32],
+        max_num_claims: u64,
+    },
+    ClaimAirdrop {
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    },
+    InitializeConfig {
+        treasury: Pubkey,
+        review_fee_lamports: u64,
+        review_fee_usd_cents: u64,
+        price_feed: Pubkey,
+        max_price_age_secs: u64,
+    },
+    PurgeExpired,
+    DeleteMovieReview,
+    MarkNotificationsRead {
+        upto_seq: u64,
+    },
+    Subscribe {
+        months: u16,
+    },
+    SetPremiumContent {
+        scheme: EncryptionScheme,
+        plaintext_hash: [u8; 32],
+    },
+    GrantAccessKeyHint {
+        key_hint: Vec<u8>,
+    },
+    SetVisibility {
+        visibility: Visibility,
+    },
+    GrantAccess {
+        member: Pubkey,
+    },
+    RevokeAccess {
+        member: Pubkey,
+    },
+    AddDraftReview {
+        title: String,
+        rating: u8,
+        description: String,
+    },
+    PublishReview,
+    SchedulePublish {
+        publish_at: Option<i64>,
+    },
+    AttestWatched {
+        movie_id: String,
+        watched_at: i64,
+    },
+    SetWatchRequirement {
+        required: bool,
+    },
+    SetCommentLimit {
+        max_comments_per_review: u64,
+    },
+    SetCommentCapOverride {
+        uncapped: bool,
+    },
+    UpdateComment {
+        comment: String,
+    },
+    SetCommentEditWindow {
+        comment_edit_window_secs: u64,
+    },
+    RegisterHandle {
+        handle: String,
+    },
+    ReleaseHandle,
+    InitializeProgram {
+        review_fee_lamports: u64,
+        review_fee_usd_cents: u64,
+        price_feed: Pubkey,
+        max_price_age_secs: u64,
+    },
+    SetRewardSchedule {
+        base_amount: u64,
+        halving_interval: u64,
+        halving_unit: RewardHalvingUnit,
+    },
+    ClaimRewards,
+    ClaimVested,
+    SetVestingPolicy {
+        threshold: u64,
+        duration_secs: u64,
+    },
+    SetReferralBonus {
+        referral_bonus: u64,
+    },
+    #[cfg(feature = "test-utils")]
+    MintTestTokens {
+        amount: u64,
+    },
+    #[cfg(feature = "test-utils")]
+    SetTestProfile {
+        owner: Pubkey,
+        comments_created: u64,
+        reviews_published: u64,
+        pending_rewards: u64,
+    },
+    InitializeReviewerProfile,
+    AddEntityReview {
+        kind: EntityKind,
+        title: String,
+        rating: u8,
+        description: String,
+    },
+    AttestEntityWatched {
+        kind: EntityKind,
+        entity_id: String,
+        watched_at: i64,
+    },
+    SyncProgramVersion,
+    MigrateLegacyReview,
+    ReclaimOrphans,
+    SetEscrowPolicy {
+        arbiter: Pubkey,
+        dispute_window_secs: u64,
+        resolution_timeout_secs: u64,
+    },
+    FundEscrow {
+        nonce: u64,
+        amount: u64,
+    },
+    DisputeEscrow,
+    ResolveEscrow {
+        release: bool,
+    },
+    SettleEscrow,
+    CloseEscrow,
+    SetReviewExpiry {
+        valid_until: Option<i64>,
+    },
+    ExpireReview {
+        purge: bool,
+    },
+    AddMovieReviewIdempotent {
+        title: String,
+        rating: u8,
+        description: String,
+    },
+    InitializeReviewerProfileIdempotent,
+    InitializeConfigIdempotent {
+        treasury: Pubkey,
+        review_fee_lamports: u64,
+        review_fee_usd_cents: u64,
+        price_feed: Pubkey,
+        max_price_age_secs: u64,
+    },
+    SetDisabledFeatures {
+        disabled_features: u32,
+    },
+    CreateVersusPoll {
+        movie_a: String,
+        movie_b: String,
+        ends_at: i64,
+    },
+    VoteVersus {
+        side: VersusSide,
+    },
+    FinalizeVersusPoll,
+    AddToWatchlist {
+        page: u32,
+        movie_id: String,
+    },
+    RemoveFromWatchlist {
+        page: u32,
+        movie_id: String,
+    },
+    DeleteComment,
+    ArchiveReview,
+    SetReviewLengthLimits {
+        max_title_len: u32,
+        max_description_len: u32,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    VoteOnReview {
+        is_upvote: bool,
+    },
+    TipReviewer {
+        lamports: u64,
+    },
+    TipReviewerToken {
+        amount: u64,
+    },
+    MigrateReview,
+    SetSnapshotInterval {
+        snapshot_interval_secs: u64,
+    },
+    SnapshotAggregate,
+    SetCommentReward {
+        amount: u64,
+    },
+    IndexReviews {
+        page: u32,
+    },
+    ExportMyReviews {
+        page: u32,
+    },
+}
+
+#[derive(BorshDeserialize)]
+struct MovieReviewPayload {
+    title: String,
+    rating: u8,
+    description: String,
+}
+
+#[derive(BorshDeserialize)]
+struct EntityReviewPayload {
+    kind: EntityKind,
+    title: String,
+    rating: u8,
+    description: String,
+}
+
+#[derive(BorshDeserialize)]
+struct CommentPayload {
+    comment: String,
+}
+
+#[derive(BorshDeserialize)]
+struct SetAirdropRootPayload {
+    merkle_root: [u8; 32],
+    max_num_claims: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct ClaimAirdropPayload {
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeConfigPayload {
+    treasury: Pubkey,
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: Pubkey,
+    max_price_age_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct MarkNotificationsReadPayload {
+    upto_seq: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SubscribePayload {
+    months: u16,
+}
+
+#[derive(BorshDeserialize)]
+struct SetPremiumContentPayload {
+    scheme: EncryptionScheme,
+    plaintext_hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct GrantAccessKeyHintPayload {
+    key_hint: Vec<u8>,
+}
+
+#[derive(BorshDeserialize)]
+struct SetVisibilityPayload {
+    visibility: Visibility,
+}
+
+#[derive(BorshDeserialize)]
+struct AccessPayload {
+    member: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct SchedulePublishPayload {
+    publish_at: Option<i64>,
+}
+
+#[derive(BorshDeserialize)]
+struct AttestWatchedPayload {
+    movie_id: String,
+    watched_at: i64,
+}
+
+#[derive(BorshDeserialize)]
+struct AttestEntityWatchedPayload {
+    kind: EntityKind,
+    entity_id: String,
+    watched_at: i64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetEscrowPolicyPayload {
+    arbiter: Pubkey,
+    dispute_window_secs: u64,
+    resolution_timeout_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct FundEscrowPayload {
+    nonce: u64,
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct ResolveEscrowPayload {
+    release: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetReviewExpiryPayload {
+    valid_until: Option<i64>,
+}
+
+#[derive(BorshDeserialize)]
+struct ExpireReviewPayload {
+    purge: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetWatchRequirementPayload {
+    required: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetCommentLimitPayload {
+    max_comments_per_review: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetCommentCapOverridePayload {
+    uncapped: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetCommentEditWindowPayload {
+    comment_edit_window_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct RegisterHandlePayload {
+    handle: String,
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeProgramPayload {
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: Pubkey,
+    max_price_age_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetRewardSchedulePayload {
+    base_amount: u64,
+    halving_interval: u64,
+    halving_unit: RewardHalvingUnit,
+}
+
+#[derive(BorshDeserialize)]
+struct SetVestingPolicyPayload {
+    threshold: u64,
+    duration_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetReferralBonusPayload {
+    referral_bonus: u64,
+}
+
+#[cfg(feature = "test-utils")]
+#[derive(BorshDeserialize)]
+struct MintTestTokensPayload {
+    amount: u64,
+}
+
+#[cfg(feature = "test-utils")]
+#[derive(BorshDeserialize)]
+struct SetTestProfilePayload {
+    owner: Pubkey,
+    comments_created: u64,
+    reviews_published: u64,
+    pending_rewards: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetDisabledFeaturesPayload {
+    disabled_features: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct SetReviewLengthLimitsPayload {
+    max_title_len: u32,
+    max_description_len: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct SetPausedPayload {
+    paused: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct VoteOnReviewPayload {
+    is_upvote: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct TipReviewerPayload {
+    lamports: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct TipReviewerTokenPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetSnapshotIntervalPayload {
+    snapshot_interval_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetCommentRewardPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct ReviewIndexPagePayload {
+    page: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct CreateVersusPollPayload {
+    movie_a: String,
+    movie_b: String,
+    ends_at: i64,
+}
+
+#[derive(BorshDeserialize)]
+struct VoteVersusPayload {
+    side: VersusSide,
+}
+
+#[derive(BorshDeserialize)]
+struct WatchlistPayload {
+    page: u32,
+    movie_id: String,
+}
+
+fn pack<T: BorshSerialize + ?Sized>(variant: u8, payload: &T) -> Vec<u8> {
+    let mut data = vec![variant];
+    data.extend(payload.try_to_vec().unwrap());
+    data
+}
+
+impl MovieInstruction {
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&variant, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(match variant {
+            0 => {
+                let payload = MovieReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddMovieReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                }
+            }
+            1 => {
+                let payload = MovieReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpdateMovieReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                }
+            }
+            2 => {
+                let payload = CommentPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddComment {
+                    comment: payload.comment,
+                }
+            }
+            3 => Self::InitializeTokenMint,
+            4 => {
+                let payload = SetAirdropRootPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetAirdropRoot {
+                    merkle_root: payload.merkle_root,
+                    max_num_claims: payload.max_num_claims,
+                }
+            }
+            5 => {
+                let payload = ClaimAirdropPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ClaimAirdrop {
+                    index: payload.index,
+                    amount: payload.amount,
+                    proof: payload.proof,
+                }
+            }
+            6 => {
+                let payload = InitializeConfigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::InitializeConfig {
+                    treasury: payload.treasury,
+                    review_fee_lamports: payload.review_fee_lamports,
+                    review_fee_usd_cents: payload.review_fee_usd_cents,
+                    price_feed: payload.price_feed,
+                    max_price_age_secs: payload.max_price_age_secs,
+                }
+            }
+            7 => Self::PurgeExpired,
+            8 => Self::DeleteMovieReview,
+            9 => {
+                let payload = MarkNotificationsReadPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::MarkNotificationsRead {
+                    upto_seq: payload.upto_seq,
+                }
+            }
+            10 => {
+                let payload = SubscribePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::Subscribe {
+                    months: payload.months,
+                }
+            }
+            11 => {
+                let payload = SetPremiumContentPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetPremiumContent {
+                    scheme: payload.scheme,
+                    plaintext_hash: payload.plaintext_hash,
+                }
+            }
+            12 => {
+                let payload = GrantAccessKeyHintPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::GrantAccessKeyHint {
+                    key_hint: payload.key_hint,
+                }
+            }
+            13 => {
+                let payload = SetVisibilityPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetVisibility {
+                    visibility: payload.visibility,
+                }
+            }
+            14 => {
+                let payload = AccessPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::GrantAccess {
+                    member: payload.member,
+                }
+            }
+            15 => {
+                let payload = AccessPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RevokeAccess {
+                    member: payload.member,
+                }
+            }
+            16 => {
+                let payload = MovieReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddDraftReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                }
+            }
+            17 => Self::PublishReview,
+            18 => {
+                let payload = SchedulePublishPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SchedulePublish {
+                    publish_at: payload.publish_at,
+                }
+            }
+            19 => {
+                let payload = AttestWatchedPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AttestWatched {
+                    movie_id: payload.movie_id,
+                    watched_at: payload.watched_at,
+                }
+            }
+            20 => {
+                let payload = SetWatchRequirementPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetWatchRequirement {
+                    required: payload.required,
+                }
+            }
+            21 => {
+                let payload = SetCommentLimitPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetCommentLimit {
+                    max_comments_per_review: payload.max_comments_per_review,
+                }
+            }
+            22 => {
+                let payload = SetCommentCapOverridePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetCommentCapOverride {
+                    uncapped: payload.uncapped,
+                }
+            }
+            23 => {
+                let payload = CommentPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpdateComment {
+                    comment: payload.comment,
+                }
+            }
+            24 => {
+                let payload = SetCommentEditWindowPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetCommentEditWindow {
+                    comment_edit_window_secs: payload.comment_edit_window_secs,
+                }
+            }
+            25 => {
+                let payload = RegisterHandlePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RegisterHandle {
+                    handle: payload.handle,
+                }
+            }
+            26 => Self::ReleaseHandle,
+            27 => {
+                let payload = InitializeProgramPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::InitializeProgram {
+                    review_fee_lamports: payload.review_fee_lamports,
+                    review_fee_usd_cents: payload.review_fee_usd_cents,
+                    price_feed: payload.price_feed,
+                    max_price_age_secs: payload.max_price_age_secs,
+                }
+            }
+            28 => {
+                let payload = SetRewardSchedulePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetRewardSchedule {
+                    base_amount: payload.base_amount,
+                    halving_interval: payload.halving_interval,
+                    halving_unit: payload.halving_unit,
+                }
+            }
+            29 => Self::ClaimRewards,
+            30 => Self::ClaimVested,
+            31 => {
+                let payload = SetVestingPolicyPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetVestingPolicy {
+                    threshold: payload.threshold,
+                    duration_secs: payload.duration_secs,
+                }
+            }
+            32 => {
+                let payload = SetReferralBonusPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetReferralBonus {
+                    referral_bonus: payload.referral_bonus,
+                }
+            }
+            #[cfg(feature = "test-utils")]
+            33 => {
+                let payload = MintTestTokensPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::MintTestTokens {
+                    amount: payload.amount,
+                }
+            }
+            #[cfg(feature = "test-utils")]
+            34 => {
+                let payload = SetTestProfilePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetTestProfile {
+                    owner: payload.owner,
+                    comments_created: payload.comments_created,
+                    reviews_published: payload.reviews_published,
+                    pending_rewards: payload.pending_rewards,
+                }
+            }
+            35 => Self::InitializeReviewerProfile,
+            36 => {
+                let payload = EntityReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddEntityReview {
+                    kind: payload.kind,
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                }
+            }
+            37 => {
+                let payload = AttestEntityWatchedPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AttestEntityWatched {
+                    kind: payload.kind,
+                    entity_id: payload.entity_id,
+                    watched_at: payload.watched_at,
+                }
+            }
+            38 => Self::SyncProgramVersion,
+            39 => Self::MigrateLegacyReview,
+            40 => Self::ReclaimOrphans,
+            41 => {
+                let payload = SetEscrowPolicyPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetEscrowPolicy {
+                    arbiter: payload.arbiter,
+                    dispute_window_secs: payload.dispute_window_secs,
+                    resolution_timeout_secs: payload.resolution_timeout_secs,
+                }
+            }
+            42 => {
+                let payload = FundEscrowPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::FundEscrow {
+                    nonce: payload.nonce,
+                    amount: payload.amount,
+                }
+            }
+            43 => Self::DisputeEscrow,
+            44 => {
+                let payload = ResolveEscrowPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ResolveEscrow {
+                    release: payload.release,
+                }
+            }
+            45 => Self::SettleEscrow,
+            46 => Self::CloseEscrow,
+            47 => {
+                let payload = SetReviewExpiryPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetReviewExpiry {
+                    valid_until: payload.valid_until,
+                }
+            }
+            48 => {
+                let payload = ExpireReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ExpireReview {
+                    purge: payload.purge,
+                }
+            }
+            49 => {
+                let payload = MovieReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddMovieReviewIdempotent {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                }
+            }
+            50 => Self::InitializeReviewerProfileIdempotent,
+            51 => {
+                let payload = InitializeConfigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::InitializeConfigIdempotent {
+                    treasury: payload.treasury,
+                    review_fee_lamports: payload.review_fee_lamports,
+                    review_fee_usd_cents: payload.review_fee_usd_cents,
+                    price_feed: payload.price_feed,
+                    max_price_age_secs: payload.max_price_age_secs,
+                }
+            }
+            52 => {
+                let payload = SetDisabledFeaturesPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetDisabledFeatures {
+                    disabled_features: payload.disabled_features,
+                }
+            }
+            53 => {
+                let payload = CreateVersusPollPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateVersusPoll {
+                    movie_a: payload.movie_a,
+                    movie_b: payload.movie_b,
+                    ends_at: payload.ends_at,
+                }
+            }
+            54 => {
+                let payload = VoteVersusPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::VoteVersus { side: payload.side }
+            }
+            55 => Self::FinalizeVersusPoll,
+            56 => {
+                let payload = WatchlistPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddToWatchlist {
+                    page: payload.page,
+                    movie_id: payload.movie_id,
+                }
+            }
+            57 => {
+                let payload = WatchlistPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RemoveFromWatchlist {
+                    page: payload.page,
+                    movie_id: payload.movie_id,
+                }
+            }
+            58 => Self::DeleteComment,
+            59 => Self::ArchiveReview,
+            60 => {
+                let payload = SetReviewLengthLimitsPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetReviewLengthLimits {
+                    max_title_len: payload.max_title_len,
+                    max_description_len: payload.max_description_len,
+                }
+            }
+            61 => {
+                let payload = SetPausedPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetPaused {
+                    paused: payload.paused,
+                }
+            }
+            62 => {
+                let payload = VoteOnReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::VoteOnReview {
+                    is_upvote: payload.is_upvote,
+                }
+            }
+            63 => {
+                let payload = TipReviewerPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::TipReviewer {
+                    lamports: payload.lamports,
+                }
+            }
+            64 => {
+                let payload = TipReviewerTokenPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::TipReviewerToken {
+                    amount: payload.amount,
+                }
+            }
+            65 => Self::MigrateReview,
+            66 => {
+                let payload = SetSnapshotIntervalPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetSnapshotInterval {
+                    snapshot_interval_secs: payload.snapshot_interval_secs,
+                }
+            }
+            67 => Self::SnapshotAggregate,
+            68 => {
+                let payload = SetCommentRewardPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetCommentReward {
+                    amount: payload.amount,
+                }
+            }
+            69 => {
+                let payload = ReviewIndexPagePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::IndexReviews { page: payload.page }
+            }
+            70 => {
+                let payload = ReviewIndexPagePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ExportMyReviews { page: payload.page }
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+
+    /// Oldest instruction version this instruction is accepted at: that of
+    /// the last incompatible change to it, or `MIN_INSTRUCTION_VERSION`.
+    pub fn min_version(&self) -> u16 {
+        match self {
+            Self::AddMovieReview { .. }
+            | Self::AddDraftReview { .. }
+            | Self::AddEntityReview { .. }
+            | Self::AddMovieReviewIdempotent { .. }
+            | Self::UpdateMovieReview { .. }
+            | Self::DeleteMovieReview
+            | Self::SetVisibility { .. }
+            | Self::PublishReview
+            | Self::ExpireReview { .. }
+            | Self::ArchiveReview
+            | Self::MigrateReview
+            | Self::FinalizeVersusPoll => 2,
+            _ => MIN_INSTRUCTION_VERSION,
+        }
+    }
+
+    /// Encodes the instruction tagged with `INSTRUCTION_VERSION`.
+    pub fn pack_versioned(&self) -> Vec<u8> {
+        let mut data = vec![VERSIONED_INSTRUCTION_TAG];
+        data.extend(INSTRUCTION_VERSION.to_le_bytes());
+        data.extend(self.pack());
+        data
+    }
+
+    /// Encodes the instruction the way `unpack` reads it, without a version.
+    pub fn pack(&self) -> Vec<u8> {
+        match self {
+            Self::AddMovieReview {
+                title,
+                rating,
+                description,
+            } => pack(0, &(title, rating, description)),
+            Self::UpdateMovieReview {
+                title,
+                rating,
+                description,
+            } => pack(1, &(title, rating, description)),
+            Self::AddComment { comment } => pack(2, comment),
+            Self::InitializeTokenMint => vec![3],
+            Self::SetAirdropRoot {
+                merkle_root,
+                max_num_claims,
+            } => pack(4, &(merkle_root, max_num_claims)),
+            Self::ClaimAirdrop {
+                index,
+                amount,
+                proof,
+            } => pack(5, &(index, amount, proof)),
+            Self::InitializeConfig {
+                treasury,
+                review_fee_lamports,
+                review_fee_usd_cents,
+                price_feed,
+                max_price_age_secs,
+            } => pack(
+                6,
+                &(
+                    treasury,
+                    review_fee_lamports,
+                    review_fee_usd_cents,
+                    price_feed,
+                    max_price_age_secs,
+                ),
+            ),
+            Self::PurgeExpired => vec![7],
+            Self::DeleteMovieReview => vec![8],
+            Self::MarkNotificationsRead { upto_seq } => pack(9, upto_seq),
+            Self::Subscribe { months } => pack(10, months),
+            Self::SetPremiumContent {
+                scheme,
+                plaintext_hash,
+            } => pack(11, &(scheme, plaintext_hash)),
+            Self::GrantAccessKeyHint { key_hint } => pack(12, key_hint),
+            Self::SetVisibility { visibility } => pack(13, visibility),
+            Self::GrantAccess { member } => pack(14, member),
+            Self::RevokeAccess { member } => pack(15, member),
+            Self::AddDraftReview {
+                title,
+                rating,
+                description,
+            } => pack(16, &(title, rating, description)),
+            Self::PublishReview => vec![17],
+            Self::SchedulePublish { publish_at } => pack(18, publish_at),
+            Self::AttestWatched {
+                movie_id,
+                watched_at,
+            } => pack(19, &(movie_id, watched_at)),
+            Self::SetWatchRequirement { required } => pack(20, required),
+            Self::SetCommentLimit {
+                max_comments_per_review,
+            } => pack(21, max_comments_per_review),
+            Self::SetCommentCapOverride { uncapped } => pack(22, uncapped),
+            Self::UpdateComment { comment } => pack(23, comment),
+            Self::SetCommentEditWindow {
+                comment_edit_window_secs,
+            } => pack(24, comment_edit_window_secs),
+            Self::RegisterHandle { handle } => pack(25, handle),
+            Self::ReleaseHandle => vec![26],
+            Self::InitializeProgram {
+                review_fee_lamports,
+                review_fee_usd_cents,
+                price_feed,
+                max_price_age_secs,
+            } => pack(
+                27,
+                &(
+                    review_fee_lamports,
+                    review_fee_usd_cents,
+                    price_feed,
+                    max_price_age_secs,
+                ),
+            ),
+            Self::SetRewardSchedule {
+                base_amount,
+                halving_interval,
+                halving_unit,
+            } => pack(28, &(base_amount, halving_interval, halving_unit)),
+            Self::ClaimRewards => vec![29],
+            Self::ClaimVested => vec![30],
+            Self::SetVestingPolicy {
+                threshold,
+                duration_secs,
+            } => pack(31, &(threshold, duration_secs)),
+            Self::SetReferralBonus { referral_bonus } => pack(32, referral_bonus),
+            #[cfg(feature = "test-utils")]
+            Self::MintTestTokens { amount } => pack(33, amount),
+            #[cfg(feature = "test-utils")]
+            Self::SetTestProfile {
+                owner,
+                comments_created,
+                reviews_published,
+                pending_rewards,
+            } => pack(
+                34,
+                &(owner, comments_created, reviews_published, pending_rewards),
+            ),
+            Self::InitializeReviewerProfile => vec![35],
+            Self::AddEntityReview {
+                kind,
+                title,
+                rating,
+                description,
+            } => pack(36, &(kind, title, rating, description)),
+            Self::AttestEntityWatched {
+                kind,
+                entity_id,
+                watched_at,
+            } => pack(37, &(kind, entity_id, watched_at)),
+            Self::SyncProgramVersion => vec![38],
+            Self::MigrateLegacyReview => vec![39],
+            Self::ReclaimOrphans => vec![40],
+            Self::SetEscrowPolicy {
+                arbiter,
+                dispute_window_secs,
+                resolution_timeout_secs,
+            } => pack(41, &(arbiter, dispute_window_secs, resolution_timeout_secs)),
+            Self::FundEscrow { nonce, amount } => pack(42, &(nonce, amount)),
+            Self::DisputeEscrow => vec![43],
+            Self::ResolveEscrow { release } => pack(44, release),
+            Self::SettleEscrow => vec![45],
+            Self::CloseEscrow => vec![46],
+            Self::SetReviewExpiry { valid_until } => pack(47, valid_until),
+            Self::ExpireReview { purge } => pack(48, purge),
+            Self::AddMovieReviewIdempotent {
+                title,
+                rating,
+                description,
+            } => pack(49, &(title, rating, description)),
+            Self::InitializeReviewerProfileIdempotent => vec![50],
+            Self::InitializeConfigIdempotent {
+                treasury,
+                review_fee_lamports,
+                review_fee_usd_cents,
+                price_feed,
+                max_price_age_secs,
+            } => pack(
+                51,
+                &(
+                    treasury,
+                    review_fee_lamports,
+                    review_fee_usd_cents,
+                    price_feed,
+                    max_price_age_secs,
+                ),
+            ),
+            Self::SetDisabledFeatures { disabled_features } => pack(52, disabled_features),
+            Self::CreateVersusPoll {
+                movie_a,
+                movie_b,
+                ends_at,
+            } => pack(53, &(movie_a, movie_b, ends_at)),
+            Self::VoteVersus { side } => pack(54, side),
+            Self::FinalizeVersusPoll => vec![55],
+            Self::AddToWatchlist { page, movie_id } => pack(56, &(page, movie_id)),
+            Self::RemoveFromWatchlist { page, movie_id } => pack(57, &(page, movie_id)),
+            Self::DeleteComment => vec![58],
+            Self::ArchiveReview => vec![59],
+            Self::SetReviewLengthLimits {
+                max_title_len,
+                max_description_len,
+            } => pack(60, &(max_title_len, max_description_len)),
+            Self::SetPaused { paused } => pack(61, paused),
+            Self::VoteOnReview { is_upvote } => pack(62, is_upvote),
+            Self::TipReviewer { lamports } => pack(63, lamports),
+            Self::TipReviewerToken { amount } => pack(64, amount),
+            Self::MigrateReview => vec![65],
+            Self::SetSnapshotInterval {
+                snapshot_interval_secs,
+            } => pack(66, snapshot_interval_secs),
+            Self::SnapshotAggregate => vec![67],
+            Self::SetCommentReward { amount } => pack(68, amount),
+            Self::IndexReviews { page } => pack(69, page),
+            Self::ExportMyReviews { page } => pack(70, page),
+        }
+    }
+
+    /// The family the config admin can switch this instruction off with, if
+    /// any.
+    pub fn feature(&self) -> Option<Feature> {
+        match self {
+            Self::AddComment { .. } | Self::UpdateComment { .. } => Some(Feature::Comments),
+            Self::Subscribe { .. } => Some(Feature::Subscriptions),
+            Self::FundEscrow { .. } => Some(Feature::Escrow),
+            Self::ClaimAirdrop { .. } | Self::ClaimRewards | Self::ClaimVested => {
+                Some(Feature::Minting)
+            }
+            Self::CreateVersusPoll { .. } | Self::VoteVersus { .. } | Self::VoteOnReview { .. } => {
+                Some(Feature::Votes)
+            }
+            Self::TipReviewer { .. } | Self::TipReviewerToken { .. } => Some(Feature::Tips),
+            _ => None,
+        }
+    }
+
+    /// Whether pausing the config stops this instruction: every user
+    /// instruction that always takes the config. Ones that don't, such as
+    /// deleting a review or settling an escrow, keep working so users can
+    /// still take their data and lamports out.
+    pub fn pausable(&self) -> bool {
+        self.feature().is_some()
+            || matches!(
+                self,
+                Self::AddMovieReview { .. }
+                    | Self::AddDraftReview { .. }
+                    | Self::AddEntityReview { .. }
+                    | Self::AddMovieReviewIdempotent { .. }
+                    | Self::UpdateMovieReview { .. }
+            )
+    }
+}
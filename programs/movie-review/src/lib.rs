@@ -0,0 +1,44 @@
+//! A movie review program. Programs that CPI into it depend on this crate with
+//! the `no-entrypoint` feature and build instructions from
+//! [`instruction::MovieInstruction`] and the account types in [`state`], or
+//! invoke it through the helpers in [`cpi`].
+
+// serde derives are for off-chain tooling only.
+#[cfg(all(feature = "serde", target_os = "solana"))]
+compile_error!("the `serde` feature is not available in BPF builds");
+
+// The faucet mints without authorization, so BPF builds must also opt in to
+// being localnet-only.
+#[cfg(all(
+    feature = "test-utils",
+    target_os = "solana",
+    not(feature = "localnet")
+))]
+compile_error!("the `test-utils` feature is only available in BPF builds with `localnet`");
+
+/// `msg!` for progress and field logging, which costs compute units, so it is
+/// only logged in tests and builds with the `verbose-logs` feature. Errors are
+/// logged with `msg!` regardless.
+macro_rules! debug_msg {
+    ($($arg:tt)+) => {
+        if cfg!(any(test, feature = "verbose-logs")) {
+            ::solana_program::msg!($($arg)+);
+        }
+    };
+}
+
+pub mod accounts;
+pub mod compute_units;
+pub mod cpi;
+#[cfg(not(feature = "no-entrypoint"))]
+pub mod entrypoint;
+pub mod error;
+pub mod events;
+pub mod instruction;
+pub mod mention;
+pub mod merkle;
+pub mod processor;
+pub mod state;
+
+// Downstream programs build against the same `solana_program` as this one.
+pub use solana_program;
@@ -0,0 +1,63 @@
+use crate::error::ReviewError;
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+/// Most users a single comment can notify through mentions.
+pub const MAX_MENTIONS: usize = 5;
+
+pub const MIN_HANDLE_LEN: usize = 3;
+/// Handles are used as a PDA seed, which caps them at 32 bytes.
+pub const MAX_HANDLE_LEN: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mention {
+    Key(Pubkey),
+    /// A normalized handle, resolved through its `[b"handle", handle]` account.
+    Handle(String),
+}
+
+/// Lowercases `handle` and checks it is 3 to 32 characters of `a-z`, `0-9`
+/// and `_`. Handles that would parse as a pubkey are rejected so mentions stay
+/// unambiguous.
+pub fn normalize_handle(handle: &str) -> Result<String, ReviewError> {
+    let normalized = handle.to_ascii_lowercase();
+    if normalized.len() < MIN_HANDLE_LEN
+        || normalized.len() > MAX_HANDLE_LEN
+        || !normalized
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        || Pubkey::from_str(handle).is_ok()
+    {
+        return Err(ReviewError::InvalidHandle);
+    }
+    Ok(normalized)
+}
+
+/// Returns the distinct `@<base58 pubkey>` and `@<handle>` mentions in `text`,
+/// in order of first appearance and capped at `MAX_MENTIONS`. Words that are
+/// neither are left as plain text.
+pub fn parse_mentions(text: &str) -> Vec<Mention> {
+    let mut mentions = Vec::new();
+    for word in text.split_whitespace() {
+        if mentions.len() == MAX_MENTIONS {
+            break;
+        }
+        let Some(candidate) = word.strip_prefix('@') else {
+            continue;
+        };
+        // Allow mentions to be followed by punctuation, e.g. "thanks @abc!"
+        let candidate =
+            candidate.trim_end_matches(|c: char| !(c.is_ascii_alphanumeric() || c == '_'));
+        let mention = if let Ok(mentioned) = Pubkey::from_str(candidate) {
+            Mention::Key(mentioned)
+        } else if let Ok(handle) = normalize_handle(candidate) {
+            Mention::Handle(handle)
+        } else {
+            continue;
+        };
+        if !mentions.contains(&mention) {
+            mentions.push(mention);
+        }
+    }
+    mentions
+}
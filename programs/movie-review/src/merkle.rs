@@ -0,0 +1,32 @@
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+// Leaves and interior nodes are domain-separated so an interior node can never
+// be presented as a leaf.
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn airdrop_leaf(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        &index.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    // Pairs are hashed in sorted order so proofs don't need direction bits.
+    if a <= b {
+        hashv(&[NODE_PREFIX, a, b]).to_bytes()
+    } else {
+        hashv(&[NODE_PREFIX, b, a]).to_bytes()
+    }
+}
+
+pub fn verify_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
@@ -0,0 +1,5871 @@
+use crate::accounts::{
+    parse_any, AccountState, AirdropAccount, AnyAccount, CommentAccount, EscrowAccount,
+    HandleAccount, ProfileAccount, ReviewAccount, ReviewIndexAccount, SummaryAccount,
+    VersusPollAccount, VestingAccount, WatchlistAccount,
+};
+use crate::error::ReviewError;
+use crate::events::{CommentAdded, Event, ReviewAdded, ReviewUpdated};
+use crate::instruction::{self, MovieInstruction, INSTRUCTION_VERSION, MIN_INSTRUCTION_VERSION};
+use crate::mention::{self, Mention};
+use crate::merkle;
+use crate::state::{
+    self, AccessKeyHintState, AclState, AggregateSnapshot, AirdropClaimStatus, AirdropState,
+    ConfigState, EncryptionScheme, EntityKind, EscrowState, EscrowStatus, Feature, HandleState,
+    InboxState, MovieAccountState, MovieComment, MovieCommentCounter, MovieSummary,
+    NotificationKind, PremiumContentState, ProgramVersionState, ReviewIndexState, ReviewRecord,
+    ReviewStatus, ReviewVote, ReviewerProfile, RewardHalvingUnit, SubscriptionState, TreasuryState,
+    VersusPollState, VersusSide, VersusVoteReceipt, VestingState, Visibility, WatchAttestation,
+    WatchlistState, COMMENT_REWARD_AMOUNT, MAX_ACL_MEMBERS, MAX_COMMENT_LEN, MAX_DESCRIPTION_LEN,
+    MAX_KEY_HINT_LEN, MAX_TITLE_LEN, MAX_WATCHLIST_ENTRIES, MAX_WATCHLIST_MOVIE_ID_LEN,
+    PURGE_BOUNTY_BPS, REVIEW_INDEX_PAGE_LEN, SUBSCRIPTION_MONTH_SECS, SUBSCRIPTION_PRICE_LAMPORTS,
+    SUBSCRIPTION_PROTOCOL_CUT_BPS,
+};
+use borsh::BorshSerialize;
+use movie_review_derive::Accounts;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    borsh::try_from_slice_unchecked,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed, set_return_data},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::{self, clock::Clock, rent::Rent, Sysvar},
+};
+use pyth_sdk_solana::{load_price_feed_from_account_info, Price};
+use spl_associated_token_account::get_associated_token_address;
+use std::{cmp::Ordering, convert::TryInto};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (version, instruction_data) = instruction::split_instruction_version(instruction_data)?;
+    if !(MIN_INSTRUCTION_VERSION..=INSTRUCTION_VERSION).contains(&version) {
+        msg!(
+            "Instruction version {} is not supported, expected {} to {}",
+            version,
+            MIN_INSTRUCTION_VERSION,
+            INSTRUCTION_VERSION
+        );
+        return Err(ReviewError::UnsupportedInstructionVersion.into());
+    }
+
+    let instruction = MovieInstruction::unpack(instruction_data)?;
+    if version < instruction.min_version() {
+        msg!(
+            "Instruction version {} is not supported, expected {} or later",
+            version,
+            instruction.min_version()
+        );
+        return Err(ReviewError::UnsupportedInstructionVersion.into());
+    }
+    if instruction.pausable() {
+        require_not_paused(program_id, accounts)?;
+    }
+    if let Some(feature) = instruction.feature() {
+        require_feature_enabled(program_id, accounts, feature)?;
+    }
+
+    match instruction {
+        MovieInstruction::AddMovieReview {
+            title,
+            rating,
+            description,
+        } => add_movie_review(
+            program_id,
+            accounts,
+            EntityKind::Movie,
+            title,
+            rating,
+            description,
+            ReviewStatus::Published,
+        ),
+        MovieInstruction::UpdateMovieReview {
+            title,
+            rating,
+            description,
+        } => update_movie_review(program_id, accounts, title, rating, description),
+        MovieInstruction::AddComment { comment } => add_comment(program_id, accounts, comment),
+        MovieInstruction::InitializeTokenMint => initialize_token_mint(program_id, accounts),
+        MovieInstruction::SetAirdropRoot {
+            merkle_root,
+            max_num_claims,
+        } => set_airdrop_root(program_id, accounts, merkle_root, max_num_claims),
+        MovieInstruction::ClaimAirdrop {
+            index,
+            amount,
+            proof,
+        } => claim_airdrop(program_id, accounts, index, amount, proof),
+        MovieInstruction::InitializeConfig {
+            treasury,
+            review_fee_lamports,
+            review_fee_usd_cents,
+            price_feed,
+            max_price_age_secs,
+        } => initialize_config(
+            program_id,
+            accounts,
+            treasury,
+            review_fee_lamports,
+            review_fee_usd_cents,
+            price_feed,
+            max_price_age_secs,
+        ),
+        MovieInstruction::PurgeExpired => purge_expired(program_id, accounts),
+        MovieInstruction::DeleteMovieReview => delete_movie_review(program_id, accounts),
+        MovieInstruction::MarkNotificationsRead { upto_seq } => {
+            mark_notifications_read(program_id, accounts, upto_seq)
+        }
+        MovieInstruction::Subscribe { months } => subscribe(program_id, accounts, months),
+        MovieInstruction::SetPremiumContent {
+            scheme,
+            plaintext_hash,
+        } => set_premium_content(program_id, accounts, scheme, plaintext_hash),
+        MovieInstruction::GrantAccessKeyHint { key_hint } => {
+            grant_access_key_hint(program_id, accounts, key_hint)
+        }
+        MovieInstruction::SetVisibility { visibility } => {
+            set_visibility(program_id, accounts, visibility)
+        }
+        MovieInstruction::GrantAccess { member } => grant_access(program_id, accounts, member),
+        MovieInstruction::RevokeAccess { member } => revoke_access(program_id, accounts, member),
+        MovieInstruction::AddDraftReview {
+            title,
+            rating,
+            description,
+        } => add_movie_review(
+            program_id,
+            accounts,
+            EntityKind::Movie,
+            title,
+            rating,
+            description,
+            ReviewStatus::Draft,
+        ),
+        MovieInstruction::PublishReview => publish_review(program_id, accounts),
+        MovieInstruction::SchedulePublish { publish_at } => {
+            schedule_publish(program_id, accounts, publish_at)
+        }
+        MovieInstruction::AttestWatched {
+            movie_id,
+            watched_at,
+        } => attest_watched(program_id, accounts, EntityKind::Movie, movie_id, watched_at),
+        MovieInstruction::SetWatchRequirement { required } => {
+            set_watch_requirement(program_id, accounts, required)
+        }
+        MovieInstruction::SetCommentLimit {
+            max_comments_per_review,
+        } => set_comment_limit(program_id, accounts, max_comments_per_review),
+        MovieInstruction::SetCommentCapOverride { uncapped } => {
+            set_comment_cap_override(program_id, accounts, uncapped)
+        }
+        MovieInstruction::UpdateComment { comment } => {
+            update_comment(program_id, accounts, comment)
+        }
+        MovieInstruction::SetCommentEditWindow {
+            comment_edit_window_secs,
+        } => set_comment_edit_window(program_id, accounts, comment_edit_window_secs),
+        MovieInstruction::RegisterHandle { handle } => {
+            register_handle(program_id, accounts, handle)
+        }
+        MovieInstruction::ReleaseHandle => release_handle(program_id, accounts),
+        MovieInstruction::InitializeProgram {
+            review_fee_lamports,
+            review_fee_usd_cents,
+            price_feed,
+            max_price_age_secs,
+        } => initialize_program(
+            program_id,
+            accounts,
+            review_fee_lamports,
+            review_fee_usd_cents,
+            price_feed,
+            max_price_age_secs,
+        ),
+        MovieInstruction::SetRewardSchedule {
+            base_amount,
+            halving_interval,
+            halving_unit,
+        } => set_reward_schedule(
+            program_id,
+            accounts,
+            base_amount,
+            halving_interval,
+            halving_unit,
+        ),
+        MovieInstruction::ClaimRewards => claim_rewards(program_id, accounts),
+        MovieInstruction::ClaimVested => claim_vested(program_id, accounts),
+        MovieInstruction::SetVestingPolicy {
+            threshold,
+            duration_secs,
+        } => set_vesting_policy(program_id, accounts, threshold, duration_secs),
+        MovieInstruction::SetReferralBonus { referral_bonus } => {
+            set_referral_bonus(program_id, accounts, referral_bonus)
+        }
+        #[cfg(feature = "test-utils")]
+        MovieInstruction::MintTestTokens { amount } => {
+            mint_test_tokens(program_id, accounts, amount)
+        }
+        #[cfg(feature = "test-utils")]
+        MovieInstruction::SetTestProfile {
+            owner,
+            comments_created,
+            reviews_published,
+            pending_rewards,
+        } => set_test_profile(
+            program_id,
+            accounts,
+            owner,
+            comments_created,
+            reviews_published,
+            pending_rewards,
+        ),
+        MovieInstruction::InitializeReviewerProfile => {
+            initialize_reviewer_profile(program_id, accounts)
+        }
+        MovieInstruction::AddEntityReview {
+            kind,
+            title,
+            rating,
+            description,
+        } => add_movie_review(
+            program_id,
+            accounts,
+            kind,
+            title,
+            rating,
+            description,
+            ReviewStatus::Published,
+        ),
+        MovieInstruction::AttestEntityWatched {
+            kind,
+            entity_id,
+            watched_at,
+        } => attest_watched(program_id, accounts, kind, entity_id, watched_at),
+        MovieInstruction::SyncProgramVersion => sync_program_version(program_id, accounts),
+        MovieInstruction::MigrateLegacyReview => migrate_legacy_review(program_id, accounts),
+        MovieInstruction::ReclaimOrphans => reclaim_orphans(program_id, accounts),
+        MovieInstruction::SetEscrowPolicy {
+            arbiter,
+            dispute_window_secs,
+            resolution_timeout_secs,
+        } => set_escrow_policy(
+            program_id,
+            accounts,
+            arbiter,
+            dispute_window_secs,
+            resolution_timeout_secs,
+        ),
+        MovieInstruction::FundEscrow { nonce, amount } => {
+            fund_escrow(program_id, accounts, nonce, amount)
+        }
+        MovieInstruction::DisputeEscrow => dispute_escrow(program_id, accounts),
+        MovieInstruction::ResolveEscrow { release } => {
+            resolve_escrow(program_id, accounts, release)
+        }
+        MovieInstruction::SettleEscrow => settle_escrow(program_id, accounts),
+        MovieInstruction::CloseEscrow => close_escrow(program_id, accounts),
+        MovieInstruction::SetReviewExpiry { valid_until } => {
+            set_review_expiry(program_id, accounts, valid_until)
+        }
+        MovieInstruction::ExpireReview { purge } => expire_review(program_id, accounts, purge),
+        MovieInstruction::AddMovieReviewIdempotent {
+            title,
+            rating,
+            description,
+        } => add_movie_review_idempotent(program_id, accounts, title, rating, description),
+        MovieInstruction::InitializeReviewerProfileIdempotent => {
+            initialize_reviewer_profile_idempotent(program_id, accounts)
+        }
+        MovieInstruction::InitializeConfigIdempotent {
+            treasury,
+            review_fee_lamports,
+            review_fee_usd_cents,
+            price_feed,
+            max_price_age_secs,
+        } => initialize_config_idempotent(
+            program_id,
+            accounts,
+            treasury,
+            review_fee_lamports,
+            review_fee_usd_cents,
+            price_feed,
+            max_price_age_secs,
+        ),
+        MovieInstruction::SetDisabledFeatures { disabled_features } => {
+            set_disabled_features(program_id, accounts, disabled_features)
+        }
+        MovieInstruction::CreateVersusPoll {
+            movie_a,
+            movie_b,
+            ends_at,
+        } => create_versus_poll(program_id, accounts, movie_a, movie_b, ends_at),
+        MovieInstruction::VoteVersus { side } => vote_versus(program_id, accounts, side),
+        MovieInstruction::FinalizeVersusPoll => finalize_versus_poll(program_id, accounts),
+        MovieInstruction::AddToWatchlist { page, movie_id } => {
+            add_to_watchlist(program_id, accounts, page, movie_id)
+        }
+        MovieInstruction::RemoveFromWatchlist { page, movie_id } => {
+            remove_from_watchlist(program_id, accounts, page, movie_id)
+        }
+        MovieInstruction::DeleteComment => delete_comment(program_id, accounts),
+        MovieInstruction::ArchiveReview => archive_review(program_id, accounts),
+        MovieInstruction::SetReviewLengthLimits {
+            max_title_len,
+            max_description_len,
+        } => set_review_length_limits(program_id, accounts, max_title_len, max_description_len),
+        MovieInstruction::SetPaused { paused } => set_paused(program_id, accounts, paused),
+        MovieInstruction::VoteOnReview { is_upvote } => {
+            vote_on_review(program_id, accounts, is_upvote)
+        }
+        MovieInstruction::TipReviewer { lamports } => tip_reviewer(program_id, accounts, lamports),
+        MovieInstruction::TipReviewerToken { amount } => {
+            tip_reviewer_token(program_id, accounts, amount)
+        }
+        MovieInstruction::MigrateReview => migrate_review(program_id, accounts),
+        MovieInstruction::SetSnapshotInterval {
+            snapshot_interval_secs,
+        } => set_snapshot_interval(program_id, accounts, snapshot_interval_secs),
+        MovieInstruction::SnapshotAggregate => snapshot_aggregate(program_id, accounts),
+        MovieInstruction::SetCommentReward { amount } => {
+            set_comment_reward(program_id, accounts, amount)
+        }
+        MovieInstruction::IndexReviews { page } => index_reviews(program_id, accounts, page),
+        MovieInstruction::ExportMyReviews { page } => export_my_reviews(program_id, accounts, page),
+    }
+}
+
+/// Fails if the config admin switched `feature` off. Instructions that can be
+/// switched off must pass the config account, wherever it sits among their
+/// accounts. Without an initialized config everything is enabled.
+fn require_feature_enabled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    feature: Feature,
+) -> ProgramResult {
+    let config_account = find_config_account(program_id, accounts)?;
+
+    match load_config(program_id, config_account)? {
+        Some(config_data) if !config_data.is_enabled(feature) => {
+            msg!("{:?} are disabled", feature);
+            Err(ReviewError::FeatureDisabled.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The config account among `accounts`, wherever it sits.
+fn find_config_account<'a, 'info>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a AccountInfo<'info>, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    accounts
+        .iter()
+        .find(|account| *account.key == config_pda)
+        .ok_or_else(|| {
+            msg!("Missing config account");
+            ProgramError::NotEnoughAccountKeys
+        })
+}
+
+/// Fails if the config admin paused the program. Instructions that can be
+/// paused must pass the config account, wherever it sits among their
+/// accounts.
+fn require_not_paused(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let config_account = find_config_account(program_id, accounts)?;
+
+    match load_config(program_id, config_account)? {
+        Some(config_data) if config_data.paused => {
+            msg!("Program is paused");
+            Err(ReviewError::ProgramPaused.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fails if `len` exceeds a config length limit `max`, where zero is no
+/// limit.
+fn require_max_len(field: &str, len: usize, max: u32, error: ReviewError) -> ProgramResult {
+    if max > 0 && len > max as usize {
+        msg!("{} is longer than {} bytes", field, max);
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// Fails if the text `field` is empty.
+fn require_not_empty(field: &str, value: &str) -> ProgramResult {
+    if value.is_empty() {
+        msg!("{} cannot be empty", field);
+        return Err(ReviewError::EmptyField.into());
+    }
+    Ok(())
+}
+
+/// Fails unless every one of `accounts` was passed writable, the check
+/// `#[account(mut)]` makes for handlers that parse their accounts by hand.
+fn require_writable(accounts: &[&AccountInfo]) -> ProgramResult {
+    for account in accounts {
+        if !account.is_writable {
+            msg!("Account {} must be writable", account.key);
+            return Err(ReviewError::AccountNotWritable.into());
+        }
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct SyncProgramVersionAccounts<'a, 'info> {
+    #[account(signer)]
+    payer: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"version"], bump)]
+    version_account: &'a AccountInfo<'info>,
+    version_account_bump: u8,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Records this build's package and instruction versions in the
+/// `[b"version"]` account, creating it if needed. Anyone may call it; it only
+/// ever writes what the deployed program was built with.
+pub fn sync_program_version(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Syncing program version...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let SyncProgramVersionAccounts {
+        payer,
+        version_account,
+        version_account_bump,
+        system_program,
+    } = SyncProgramVersionAccounts::try_accounts(program_id, account_info_iter)?;
+
+    if version_account.owner != program_id {
+        create_pda_account(
+            payer,
+            version_account,
+            system_program,
+            ProgramVersionState::SIZE,
+            program_id,
+            &[b"version", &[version_account_bump]],
+        )?;
+        debug_msg!("Version account created: {}", version_account.key);
+    }
+
+    let parse = |part: &str| {
+        part.parse::<u16>()
+            .map_err(|_| ProgramError::InvalidArgument)
+    };
+    let version_data = ProgramVersionState {
+        discriminator: ProgramVersionState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        major: parse(env!("CARGO_PKG_VERSION_MAJOR"))?,
+        minor: parse(env!("CARGO_PKG_VERSION_MINOR"))?,
+        patch: parse(env!("CARGO_PKG_VERSION_PATCH"))?,
+        instruction_version: INSTRUCTION_VERSION,
+        min_instruction_version: MIN_INSTRUCTION_VERSION,
+    };
+    debug_msg!(
+        "Version {}.{}.{}, instructions {} to {}",
+        version_data.major,
+        version_data.minor,
+        version_data.patch,
+        version_data.min_instruction_version,
+        version_data.instruction_version
+    );
+    version_data.serialize(&mut &mut version_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Deserializes program state from an account, refusing accounts that have
+/// been closed or hold another kind of state. Every read of program-owned
+/// state goes through here so a closed account can't be revived by topping
+/// its lamports back up, and one account type can't be read as another.
+///
+/// Blank data has no discriminator yet and is let through, so callers can
+/// still tell an uninitialized account apart with `is_initialized`.
+pub fn load_account<T: AccountState>(account: &AccountInfo) -> Result<T, ProgramError> {
+    let data = account.data.borrow();
+    if state::is_closed(&data) {
+        msg!("Account {} has been closed", account.key);
+        return Err(ReviewError::AccountClosed.into());
+    }
+    let discriminator = try_from_slice_unchecked::<String>(&data).unwrap_or_default();
+    if !discriminator.is_empty() && discriminator != T::DISCRIMINATOR {
+        msg!(
+            "Account {} is not a {} account",
+            account.key,
+            T::DISCRIMINATOR
+        );
+        return Err(ReviewError::InvalidAccountType.into());
+    }
+    try_from_slice_unchecked::<T>(&data).map_err(|_| {
+        msg!("Account {} data could not be deserialized", account.key);
+        ReviewError::DeserializationFailed.into()
+    })
+}
+
+/// Creates a PDA owned by `owner`, funded by `payer`. Only a blank system
+/// account is accepted as the target, so an address that already holds
+/// program state can never be created over. Lamports sent to the address
+/// beforehand are kept: `create_account` would refuse such an account, which
+/// would let anyone block a PDA by funding it first.
+pub fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    pda_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    if *pda_account.owner != system_program::ID || !pda_account.data_is_empty() {
+        msg!("Account {} already exists", pda_account.key);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(space);
+
+    if pda_account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_account.key,
+                required_lamports,
+                space.try_into().unwrap(),
+                owner,
+            ),
+            &[payer.clone(), pda_account.clone(), system_program.clone()],
+            &[signer_seeds],
+        );
+    }
+
+    debug_msg!("Account {} was pre-funded", pda_account.key);
+    let top_up = required_lamports.saturating_sub(pda_account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, pda_account.key, top_up),
+            &[payer.clone(), pda_account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(pda_account.key, space.try_into().unwrap()),
+        &[pda_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(pda_account.key, owner),
+        &[pda_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Standard close procedure for program accounts: zero the data and mark it
+/// closed, then drain the lamports into `destination`. With `reassign_owner`
+/// the account is also shrunk and handed back to the system program.
+pub fn close_account(
+    account: &AccountInfo,
+    destination: &AccountInfo,
+    reassign_owner: bool,
+) -> ProgramResult {
+    {
+        let mut data = account.try_borrow_mut_data()?;
+        data.fill(0);
+        state::mark_closed(&mut data);
+    }
+
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    if reassign_owner {
+        account.realloc(0, false)?;
+        account.assign(&system_program::ID);
+    }
+
+    Ok(())
+}
+
+/// Checks that `account` sorts strictly after `previous`. Instructions that
+/// take a variable list of accounts want them in ascending key order, so a
+/// list has one valid encoding and can't repeat an account.
+pub fn require_after(previous: Option<&Pubkey>, account: &AccountInfo) -> ProgramResult {
+    match previous {
+        Some(previous) if previous >= account.key => {
+            msg!("Account {} is out of order", account.key);
+            Err(ReviewError::AccountsOutOfOrder.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `accounts` are in strictly ascending key order.
+pub fn require_ascending_keys(accounts: &[&AccountInfo]) -> ProgramResult {
+    accounts
+        .windows(2)
+        .try_for_each(|pair| require_after(Some(pair[0].key), pair[1]))
+}
+
+/// Creates the signer's review of the `kind` entity `title` and opens the
+/// title's summary if needed. The review's comment counter is left to
+/// `AddComment`, which creates it with the first comment: reviews nobody
+/// comments on hold no counter, and the first commenter pays its rent.
+pub fn add_movie_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    kind: EntityKind,
+    title: String,
+    rating: u8,
+    description: String,
+    status: ReviewStatus,
+) -> ProgramResult {
+    debug_msg!("Adding movie review...");
+
+    let (accounts, watchlist_account) = split_watchlist(program_id, accounts);
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let summary_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    require_writable(&[initializer, pda_account, summary_account])?;
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (pda, bump_seed) = state::derive_review_pda(program_id, kind, initializer.key, &title);
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Checked before any fee is paid, rather than left to `create_pda_account`.
+    if pda_account.owner == program_id {
+        msg!("Review {} already exists", pda_account.key);
+        return Err(ReviewError::ReviewAlreadyExists.into());
+    }
+
+    let Some(rating) = state::scale_rating(rating) else {
+        msg!(
+            "Rating must be {} to {} tenths of a star, or 1 to 5 whole stars",
+            state::MIN_RATING,
+            state::MAX_RATING
+        );
+        return Err(ReviewError::InvalidRating.into());
+    };
+
+    require_not_empty("Title", &title)?;
+    require_not_empty("Description", &description)?;
+    if title.len() > MAX_TITLE_LEN {
+        msg!("Title is longer than {} bytes", MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    if description.len() > MAX_DESCRIPTION_LEN {
+        msg!("Description is longer than {} bytes", MAX_DESCRIPTION_LEN);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // Until the config has been initialized reviews are free.
+    if config_account.owner == program_id {
+        let config_data = load_account::<ConfigState>(config_account)?;
+        if config_data.is_initialized() {
+            require_max_len(
+                "Title",
+                title.len(),
+                config_data.max_title_len,
+                ReviewError::TitleTooLong,
+            )?;
+            require_max_len(
+                "Description",
+                description.len(),
+                config_data.max_description_len,
+                ReviewError::DescriptionTooLong,
+            )?;
+
+            let fee = review_fee(&config_data, account_info_iter)?;
+            if fee > 0 {
+                let treasury = next_account_info(account_info_iter)?;
+                if *treasury.key != config_data.treasury {
+                    msg!("Incorrect treasury account");
+                    return Err(ReviewError::IncorrectAccountError.into());
+                }
+
+                debug_msg!("Paying review fee of {} lamports", fee);
+                invoke(
+                    &system_instruction::transfer(initializer.key, treasury.key, fee),
+                    &[
+                        initializer.clone(),
+                        treasury.clone(),
+                        system_program.clone(),
+                    ],
+                )?;
+            }
+
+            if config_data.require_watch_attestation {
+                let attestation_account = next_account_info(account_info_iter)?;
+                require_watch_attestation(
+                    program_id,
+                    attestation_account,
+                    initializer.key,
+                    kind,
+                    &title,
+                )?;
+            }
+        }
+    }
+
+    let account_len = MovieAccountState::get_account_size(title.clone(), description.clone())?;
+
+    create_pda_account(
+        initializer,
+        pda_account,
+        system_program,
+        account_len,
+        program_id,
+        &[
+            kind.seed(),
+            initializer.key.as_ref(),
+            &state::review_title_seed(&title),
+            &[bump_seed],
+        ],
+    )?;
+
+    debug_msg!("PDA created: {}", pda);
+
+    debug_msg!("unpacking state account");
+    let mut account_data = load_account::<MovieAccountState>(pda_account)?;
+    debug_msg!("borrowed account data");
+
+    debug_msg!("checking if movie account is already initialized");
+    if account_data.is_initialized() {
+        msg!("Account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    account_data.discriminator = MovieAccountState::DISCRIMINATOR.to_string();
+    account_data.version = state::STATE_VERSION;
+    account_data.bump = bump_seed;
+    account_data.reviewer = *initializer.key;
+    account_data.title = title;
+    account_data.rating = rating;
+    account_data.description = description;
+    account_data.visibility = Visibility::Public;
+    account_data.status = status;
+    account_data.published_at = match status {
+        ReviewStatus::Published => now,
+        ReviewStatus::Draft | ReviewStatus::Expired | ReviewStatus::Archived => 0,
+    };
+    account_data.publish_at = None;
+    account_data.kind = kind;
+    account_data.valid_until = None;
+    account_data.upvotes = 0;
+    account_data.downvotes = 0;
+    account_data.tips_received = 0;
+    account_data.created_at = now;
+    account_data.updated_at = now;
+    account_data.is_initialized = true;
+
+    debug_msg!("serializing account");
+    account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    debug_msg!("state account serialized");
+
+    Event::ReviewAdded(ReviewAdded {
+        review: pda,
+        reviewer: *initializer.key,
+        kind,
+        status,
+        title: account_data.title.clone(),
+        rating,
+        description: account_data.description.clone(),
+    })
+    .emit()?;
+
+    // Drafts open the summary too, so it is there for them to be counted in
+    // once published.
+    let mut summary_data = open_summary(
+        program_id,
+        initializer,
+        summary_account,
+        system_program,
+        kind,
+        &account_data.title,
+    )?;
+    if let Some(rating) = account_data.counted_rating() {
+        count_in_summary(&mut summary_data, rating)?;
+    }
+    summary_data.serialize(&mut &mut summary_account.data.borrow_mut()[..])?;
+
+    if status == ReviewStatus::Published {
+        reward_review(
+            program_id,
+            config_account,
+            initializer,
+            initializer.key,
+            system_program,
+            account_info_iter,
+        )?;
+    }
+
+    // Reviewing a movie takes it off the watchlist page passed with it.
+    if let Some(watchlist_account) = watchlist_account {
+        let mut watchlist_data = load_watchlist(program_id, watchlist_account, initializer.key)?;
+        if kind == EntityKind::Movie {
+            watchlist_data
+                .movie_ids
+                .retain(|entry| *entry != account_data.title);
+            watchlist_data.save()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `AddMovieReview` that succeeds without changes when the reviewer already
+/// published this exact review, so clients can retry it after a timeout. A
+/// review with other contents still fails with `ReviewAlreadyExists`.
+pub fn add_movie_review_idempotent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    rating: u8,
+    description: String,
+) -> ProgramResult {
+    if let [reviewer, review_account, ..] = accounts {
+        let (pda, _bump_seed) =
+            state::derive_review_pda(program_id, EntityKind::Movie, reviewer.key, &title);
+        if reviewer.is_signer && review_account.owner == program_id && pda == *review_account.key {
+            if let Ok(review) = load_account::<MovieAccountState>(review_account) {
+                if review.is_initialized()
+                    && review.reviewer == *reviewer.key
+                    && review.kind == EntityKind::Movie
+                    && review.status == ReviewStatus::Published
+                    && review.title == title
+                    && state::scale_rating(rating) == Some(review.rating_tenths())
+                    && review.description == description
+                {
+                    debug_msg!("Review already exists");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    add_movie_review(
+        program_id,
+        accounts,
+        EntityKind::Movie,
+        title,
+        rating,
+        description,
+        ReviewStatus::Published,
+    )
+}
+
+/// Accrues the current scheduled reward to `reviewer`'s profile for a newly
+/// published review, to be minted later with `ClaimRewards`. Does nothing
+/// until the config is initialized. Once it enables rewards the next account
+/// must be the reviewer's profile, which `payer` creates if needed; otherwise
+/// the profile is optional. When `payer` is the reviewer, a referrer's profile
+/// may follow theirs on their first review.
+fn reward_review<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    config_account: &AccountInfo<'b>,
+    payer: &AccountInfo<'b>,
+    reviewer: &Pubkey,
+    system_program: &AccountInfo<'b>,
+    account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+) -> ProgramResult {
+    let Some(mut config_data) = load_config(program_id, config_account)? else {
+        return Ok(());
+    };
+
+    let profile_account = if config_data.reward_base_amount > 0 {
+        next_account_info(account_info_iter)?
+    } else {
+        match account_info_iter.next() {
+            Some(profile_account) => profile_account,
+            None => return Ok(()),
+        }
+    };
+    let mut profile_data =
+        open_profile(program_id, payer, profile_account, system_program, reviewer)?;
+    let first_review = profile_data.reviews_published == 0;
+    profile_data.reviews_published = profile_data
+        .reviews_published
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    if config_data.reward_base_amount > 0 {
+        let amount = config_data.reward_amount(Clock::get()?.slot);
+        debug_msg!("Reward: {} tokens to {}", amount, reviewer);
+        profile_data.pending_rewards = profile_data
+            .pending_rewards
+            .checked_add(amount)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+
+        config_data.rewarded_reviews = config_data
+            .rewarded_reviews
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    }
+
+    if let Some(referrer_profile_account) = account_info_iter.next() {
+        if *payer.key != *reviewer {
+            msg!("Only the reviewer can name a referrer");
+            return Err(ReviewError::ReferralNotAllowed.into());
+        }
+        if !first_review || profile_data.referred_by.is_some() {
+            msg!("Referral must come with the reviewer's first review");
+            return Err(ReviewError::ReferralNotAllowed.into());
+        }
+        credit_referral(
+            program_id,
+            &config_data,
+            &mut profile_data,
+            referrer_profile_account,
+        )?;
+    }
+
+    profile_data.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Records the referrer who owns `referrer_profile_account` in the referee's
+/// profile and accrues the referral bonus to both.
+fn credit_referral(
+    program_id: &Pubkey,
+    config_data: &ConfigState,
+    referee_profile: &mut ReviewerProfile,
+    referrer_profile_account: &AccountInfo,
+) -> ProgramResult {
+    if referrer_profile_account.owner != program_id {
+        msg!("Referrer has no profile");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut referrer_profile = ProfileAccount::load_mut(program_id, referrer_profile_account)?;
+
+    let (referrer_profile_pda, _bump_seed) =
+        Pubkey::find_program_address(&[b"profile", referrer_profile.owner.as_ref()], program_id);
+    if referrer_profile_pda != *referrer_profile_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if referrer_profile.owner == referee_profile.owner {
+        msg!("Reviewers cannot refer themselves");
+        return Err(ReviewError::ReferralNotAllowed.into());
+    }
+
+    debug_msg!(
+        "Referral bonus: {} tokens each to {} and {}",
+        config_data.referral_bonus,
+        referee_profile.owner,
+        referrer_profile.owner
+    );
+    referee_profile.referred_by = Some(referrer_profile.owner);
+    referee_profile.pending_rewards = referee_profile
+        .pending_rewards
+        .checked_add(config_data.referral_bonus)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    referrer_profile.pending_rewards = referrer_profile
+        .pending_rewards
+        .checked_add(config_data.referral_bonus)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    referrer_profile.save()?;
+
+    Ok(())
+}
+
+/// Mints `amount` reward tokens to `recipient`'s associated token account,
+/// which `payer` creates if it doesn't exist yet. Takes the token mint, mint
+/// authority, recipient token account, token program and associated token
+/// program from `account_info_iter`.
+fn mint_reward<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'b>,
+    recipient: &AccountInfo<'b>,
+    system_program: &AccountInfo<'b>,
+    account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+    amount: u64,
+) -> ProgramResult {
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let recipient_ata = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let ata_program = next_account_info(account_info_iter)?;
+
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    if *token_mint.key != mint_pda {
+        msg!("Incorrect token mint");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let (mint_auth_pda, mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+    if *mint_auth.key != mint_auth_pda {
+        msg!("Mint passed in and mint derived do not match");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    create_associated_token_account_idempotent(
+        payer,
+        recipient,
+        token_mint,
+        recipient_ata,
+        system_program,
+        token_program,
+        ata_program,
+    )?;
+
+    debug_msg!("Minting {} reward tokens to {}", amount, recipient.key);
+    if amount == 0 {
+        return Ok(());
+    }
+
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            token_mint.key,
+            recipient_ata.key,
+            mint_auth.key,
+            &[],
+            amount,
+        )?,
+        &[token_mint.clone(), recipient_ata.clone(), mint_auth.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]],
+    )
+}
+
+#[derive(Accounts)]
+struct ClaimRewardsAccounts<'a, 'info> {
+    #[account(signer)]
+    claimer: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"profile", claimer])]
+    profile_account: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Mints the signer's accrued rewards to their associated token account,
+/// creating it if needed.
+pub fn claim_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Claiming rewards...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ClaimRewardsAccounts {
+        claimer,
+        profile_account,
+        system_program,
+    } = ClaimRewardsAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut profile_data = ProfileAccount::load_mut(program_id, profile_account)?;
+
+    let amount = profile_data.pending_rewards;
+    profile_data.pending_rewards = 0;
+    profile_data.rewards_claimed = profile_data
+        .rewards_claimed
+        .checked_add(amount)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    profile_data.save()?;
+
+    mint_reward(
+        program_id,
+        claimer,
+        claimer,
+        system_program,
+        account_info_iter,
+        amount,
+    )
+}
+
+/// Replaces the review reward schedule. Halving restarts from the current
+/// slot and review count.
+pub fn set_reward_schedule(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    base_amount: u64,
+    halving_interval: u64,
+    halving_unit: RewardHalvingUnit,
+) -> ProgramResult {
+    debug_msg!(
+        "Setting reward schedule: {} halving every {} {:?}",
+        base_amount,
+        halving_interval,
+        halving_unit
+    );
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.reward_base_amount = base_amount;
+    config_data.reward_halving_interval = halving_interval;
+    config_data.reward_halving_unit = halving_unit;
+    config_data.reward_start_slot = Clock::get()?.slot;
+    config_data.rewarded_reviews = 0;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn update_movie_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _title: String,
+    rating: u8,
+    description: String,
+) -> ProgramResult {
+    debug_msg!("Updating movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    require_writable(&[initializer, pda_account])?;
+
+    debug_msg!("unpacking state account");
+    let mut account_data = load_account::<MovieAccountState>(pda_account)?;
+    debug_msg!("review title: {}", account_data.title);
+
+    if account_data.reviewer != *initializer.key {
+        msg!("Review does not belong to signer");
+        return Err(ReviewError::UnauthorizedReviewer.into());
+    }
+
+    if account_data.address(program_id) != Some(*pda_account.key) {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    debug_msg!("checking if movie account is initialized");
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    require_not_archived(&account_data)?;
+
+    let Some(rating) = state::scale_rating(rating) else {
+        msg!("Invalid Rating");
+        return Err(ReviewError::InvalidRating.into());
+    };
+
+    let config_account = find_config_account(program_id, accounts)?;
+    if let Some(config_data) = load_config(program_id, config_account)? {
+        require_max_len(
+            "Description",
+            description.len(),
+            config_data.max_description_len,
+            ReviewError::DescriptionTooLong,
+        )?;
+    }
+
+    require_not_empty("Description", &description)?;
+    if description.len() > MAX_DESCRIPTION_LEN {
+        msg!("Description is longer than {} bytes", MAX_DESCRIPTION_LEN);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+
+    // Reviews are sized to their contents. A longer description grows the
+    // account at the reviewer's expense, and they then pass the system
+    // program; a shorter one shrinks it and refunds the freed rent.
+    let new_len =
+        MovieAccountState::get_account_size(account_data.title.clone(), description.clone())?;
+    if new_len > pda_account.data_len() {
+        let system_program = next_account_info(account_info_iter)?;
+        if *system_program.key != system_program::ID {
+            msg!("Incorrect system program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        resize_account(pda_account, initializer, system_program, new_len)?;
+    } else if new_len < pda_account.data_len() {
+        shrink_account(pda_account, initializer, new_len)?;
+    }
+
+    let counted_before = account_data.counted_rating();
+    account_data.rating = rating;
+    account_data.description = description;
+    recount_review(
+        program_id,
+        accounts,
+        &account_data,
+        counted_before,
+        account_data.counted_rating(),
+    )?;
+    account_data.updated_at = Clock::get()?.unix_timestamp;
+
+    debug_msg!("serializing account");
+    account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    debug_msg!("state account serialized");
+
+    Event::ReviewUpdated(ReviewUpdated {
+        review: *pda_account.key,
+        reviewer: *initializer.key,
+        rating,
+        description: account_data.description,
+    })
+    .emit()?;
+
+    Ok(())
+}
+
+/// Resolves the configured review fee in lamports. A USD-denominated fee takes
+/// precedence over the fixed lamport fee and consumes the price feed account.
+fn review_fee<'a, 'b: 'a>(
+    config_data: &ConfigState,
+    account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+) -> Result<u64, ProgramError> {
+    if config_data.review_fee_usd_cents == 0 {
+        return Ok(config_data.review_fee_lamports);
+    }
+
+    let price_feed = next_account_info(account_info_iter)?;
+    if *price_feed.key != config_data.price_feed {
+        msg!("Incorrect price feed account");
+        return Err(ReviewError::InvalidPriceFeed.into());
+    }
+
+    let feed = load_price_feed_from_account_info(price_feed).map_err(|_| {
+        msg!("Price feed account could not be loaded");
+        ReviewError::InvalidPriceFeed
+    })?;
+    let clock = Clock::get()?;
+    let price = feed
+        .get_price_no_older_than(clock.unix_timestamp, config_data.max_price_age_secs)
+        .ok_or_else(|| {
+            msg!("Price feed is stale");
+            ReviewError::StalePriceFeed
+        })?;
+
+    usd_cents_to_lamports(config_data.review_fee_usd_cents, &price)
+        .ok_or_else(|| ReviewError::InvalidPriceFeed.into())
+}
+
+/// Converts a USD amount in cents to lamports using a SOL/USD price.
+pub fn usd_cents_to_lamports(usd_cents: u64, price: &Price) -> Option<u64> {
+    if price.price <= 0 {
+        return None;
+    }
+
+    // lamports = cents / 100 * 10^9 / (price * 10^expo)
+    let mut numerator = (usd_cents as u128).checked_mul(10u128.pow(7))?;
+    let mut denominator = price.price as u128;
+    if price.expo < 0 {
+        numerator = numerator.checked_mul(10u128.checked_pow(price.expo.unsigned_abs())?)?;
+    } else {
+        denominator = denominator.checked_mul(10u128.checked_pow(price.expo as u32)?)?;
+    }
+
+    (numerator / denominator).try_into().ok()
+}
+
+pub fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    treasury: Pubkey,
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: Pubkey,
+    max_price_age_secs: u64,
+) -> ProgramResult {
+    debug_msg!("Initializing config...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let program_data = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    check_upgrade_authority(program_id, admin, program_data)?;
+
+    let (config_pda, config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        admin,
+        config_account,
+        system_program,
+        ConfigState::SIZE,
+        program_id,
+        &[b"config", &[config_bump]],
+    )?;
+
+    debug_msg!("Config created: {}", config_pda);
+
+    let config_data = ConfigState {
+        discriminator: ConfigState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        admin: *admin.key,
+        treasury,
+        review_fee_lamports,
+        review_fee_usd_cents,
+        price_feed,
+        max_price_age_secs,
+        require_watch_attestation: false,
+        max_comments_per_review: 0,
+        comment_edit_window_secs: 0,
+        reward_base_amount: 0,
+        reward_halving_interval: 0,
+        reward_halving_unit: RewardHalvingUnit::Reviews,
+        reward_start_slot: Clock::get()?.slot,
+        rewarded_reviews: 0,
+        vesting_threshold: 0,
+        vesting_duration_secs: 0,
+        referral_bonus: 0,
+        escrow_arbiter: Pubkey::default(),
+        escrow_dispute_window_secs: 0,
+        escrow_resolution_timeout_secs: 0,
+        disabled_features: 0,
+        max_title_len: 0,
+        max_description_len: 0,
+        paused: false,
+        snapshot_interval_secs: 0,
+        comment_reward_amount: COMMENT_REWARD_AMOUNT,
+    };
+
+    debug_msg!("serializing account");
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    debug_msg!("state account serialized");
+
+    Ok(())
+}
+
+/// `InitializeConfig` that succeeds without changes when `admin` already
+/// initialized the config with these settings.
+pub fn initialize_config_idempotent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    treasury: Pubkey,
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: Pubkey,
+    max_price_age_secs: u64,
+) -> ProgramResult {
+    if let [admin, config_account, ..] = accounts {
+        if admin.is_signer {
+            if let Some(config_data) = load_config(program_id, config_account)? {
+                if config_data.admin == *admin.key
+                    && config_data.treasury == treasury
+                    && config_data.review_fee_lamports == review_fee_lamports
+                    && config_data.review_fee_usd_cents == review_fee_usd_cents
+                    && config_data.price_feed == price_feed
+                    && config_data.max_price_age_secs == max_price_age_secs
+                {
+                    debug_msg!("Config already initialized");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    initialize_config(
+        program_id,
+        accounts,
+        treasury,
+        review_fee_lamports,
+        review_fee_usd_cents,
+        price_feed,
+        max_price_age_secs,
+    )
+}
+
+pub fn initialize_token_mint(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let sysvar_rent = next_account_info(account_info_iter)?;
+
+    let (mint_pda, mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    let (mint_auth_pda, _mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    debug_msg!("Token mint: {:?}", mint_pda);
+    debug_msg!("Mint authority: {:?}", mint_auth_pda);
+
+    if mint_pda != *token_mint.key {
+        msg!("Incorrect token mint account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if *token_program.key != spl_token::ID {
+        msg!("Incorrect token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *sysvar_rent.key != sysvar::rent::ID {
+        msg!("Incorrect rent sysvar");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        msg!("Incorrect mint auth account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    create_pda_account(
+        initializer,
+        token_mint,
+        system_program,
+        spl_token::state::Mint::LEN,
+        token_program.key,
+        &[b"token_mint", &[mint_bump]],
+    )?;
+
+    debug_msg!("Created token mint account");
+
+    invoke_signed(
+        &spl_token::instruction::initialize_mint(
+            token_program.key,
+            token_mint.key,
+            mint_auth.key,
+            None,
+            9,
+        )?,
+        &[token_mint.clone(), sysvar_rent.clone(), mint_auth.clone()],
+        &[&[b"token_mint", &[mint_bump]]],
+    )?;
+
+    debug_msg!("Initialized token mint");
+
+    Ok(())
+}
+
+/// Requires `authority` to be the upgrade authority recorded in the
+/// program's ProgramData account, so only the deployer can run privileged
+/// bootstrap instructions on a fresh deployment.
+fn check_upgrade_authority(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    program_data: &AccountInfo,
+) -> ProgramResult {
+    let (program_data_address, _bump_seed) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+    if program_data_address != *program_data.key {
+        msg!("Incorrect program data account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *program_data.owner != bpf_loader_upgradeable::ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let data = program_data.data.borrow();
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    if data.len() < metadata_len {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let upgrade_authority_address = match bincode::deserialize(&data[..metadata_len]) {
+        Ok(UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address,
+            ..
+        }) => upgrade_authority_address,
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+
+    if upgrade_authority_address != Some(*authority.key) {
+        msg!("Signer is not the program's upgrade authority");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    Ok(())
+}
+
+/// Bootstraps a fresh deployment in one transaction: the treasury PDA, the
+/// config pointing at it, and the reward token mint. Fails once the config
+/// exists, so it can only ever run once.
+pub fn initialize_program(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: Pubkey,
+    max_price_age_secs: u64,
+) -> ProgramResult {
+    debug_msg!("Initializing program...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let sysvar_rent = next_account_info(account_info_iter)?;
+    let program_data = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    check_upgrade_authority(program_id, admin, program_data)?;
+
+    if config_account.owner == program_id {
+        msg!("Program is already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+    if treasury_pda != *treasury_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        admin,
+        treasury_account,
+        system_program,
+        TreasuryState::SIZE,
+        program_id,
+        &[b"treasury", &[treasury_bump]],
+    )?;
+    debug_msg!("Treasury created: {}", treasury_pda);
+
+    let treasury_data = TreasuryState {
+        discriminator: TreasuryState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+    };
+    treasury_data.serialize(&mut &mut treasury_account.data.borrow_mut()[..])?;
+
+    initialize_config(
+        program_id,
+        &[
+            admin.clone(),
+            config_account.clone(),
+            system_program.clone(),
+            program_data.clone(),
+        ],
+        treasury_pda,
+        review_fee_lamports,
+        review_fee_usd_cents,
+        price_feed,
+        max_price_age_secs,
+    )?;
+
+    initialize_token_mint(
+        program_id,
+        &[
+            admin.clone(),
+            token_mint.clone(),
+            mint_auth.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            sysvar_rent.clone(),
+        ],
+    )
+}
+
+pub fn set_airdrop_root(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    merkle_root: [u8; 32],
+    max_num_claims: u64,
+) -> ProgramResult {
+    debug_msg!("Setting airdrop root...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let airdrop_account = next_account_info(account_info_iter)?;
+    let claims_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    // Only the config admin can publish a root, so any root is one they chose.
+    load_config_as_admin(program_id, authority, config_account)?;
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if max_num_claims == 0 || max_num_claims > AirdropClaimStatus::MAX_NUM_CLAIMS {
+        msg!("Airdrop claim count out of range");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (airdrop_pda, airdrop_bump) = Pubkey::find_program_address(&[b"airdrop"], program_id);
+    if airdrop_pda != *airdrop_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if airdrop_account.owner != program_id {
+        create_pda_account(
+            authority,
+            airdrop_account,
+            system_program,
+            AirdropState::SIZE,
+            program_id,
+            &[b"airdrop", &[airdrop_bump]],
+        )?;
+        debug_msg!("Airdrop account created: {}", airdrop_pda);
+    }
+
+    let mut airdrop_data = load_account::<AirdropState>(airdrop_account)?;
+
+    if !airdrop_data.is_initialized() {
+        airdrop_data.discriminator = AirdropState::DISCRIMINATOR.to_string();
+        airdrop_data.is_initialized = true;
+    }
+    // Follows the config admin, who receives purged claim bitmaps' rent.
+    airdrop_data.authority = *authority.key;
+
+    // Every new root starts a new round with its own, empty claim bitmap.
+    let round = airdrop_data
+        .round
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    let (claims_pda, claims_bump) =
+        Pubkey::find_program_address(&[b"airdrop_claims", &round.to_le_bytes()], program_id);
+    if claims_pda != *claims_account.key {
+        msg!("Invalid seeds for claim bitmap PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        authority,
+        claims_account,
+        system_program,
+        AirdropClaimStatus::get_account_size(max_num_claims)?,
+        program_id,
+        &[b"airdrop_claims", &round.to_le_bytes(), &[claims_bump]],
+    )?;
+    debug_msg!("Claim bitmap created: {}", claims_pda);
+
+    let claims_data = AirdropClaimStatus {
+        discriminator: AirdropClaimStatus::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        round,
+        claimed: vec![0; AirdropClaimStatus::bitmap_len(max_num_claims)?],
+    };
+    claims_data.serialize(&mut &mut claims_account.data.borrow_mut()[..])?;
+
+    airdrop_data.merkle_root = merkle_root;
+    airdrop_data.round = round;
+    airdrop_data.max_num_claims = max_num_claims;
+
+    debug_msg!("serializing account");
+    airdrop_data.serialize(&mut &mut airdrop_account.data.borrow_mut()[..])?;
+    debug_msg!("state account serialized");
+
+    Ok(())
+}
+
+/// Verifies an airdrop claim and mints it to the claimant, or locks it in a
+/// vesting account when it exceeds the config's vesting threshold.
+pub fn claim_airdrop(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> ProgramResult {
+    debug_msg!("Claiming airdrop...");
+    debug_msg!("Index: {}", index);
+    debug_msg!("Amount: {}", amount);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let claimant = next_account_info(account_info_iter)?;
+    let airdrop_account = next_account_info(account_info_iter)?;
+    let claims_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let vesting_account = next_account_info(account_info_iter)?;
+
+    if !claimant.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if airdrop_account.owner != program_id || claims_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (airdrop_pda, _airdrop_bump) = Pubkey::find_program_address(&[b"airdrop"], program_id);
+    if airdrop_pda != *airdrop_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let airdrop_data = AirdropAccount::load(program_id, airdrop_account)?;
+
+    let (claims_pda, _claims_bump) = Pubkey::find_program_address(
+        &[b"airdrop_claims", &airdrop_data.round.to_le_bytes()],
+        program_id,
+    );
+    if claims_pda != *claims_account.key {
+        msg!("Claim bitmap is not for the current round");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut claims_data = load_account::<AirdropClaimStatus>(claims_account)?;
+
+    if index >= airdrop_data.max_num_claims {
+        msg!("Claim index out of range");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if claims_data.is_claimed(index) {
+        msg!("Airdrop already claimed");
+        return Err(ReviewError::AirdropAlreadyClaimed.into());
+    }
+
+    let leaf = merkle::airdrop_leaf(index, claimant.key, amount);
+    if !merkle::verify_proof(&proof, &airdrop_data.merkle_root, leaf) {
+        msg!("Invalid merkle proof");
+        return Err(ReviewError::InvalidMerkleProof.into());
+    }
+
+    claims_data.set_claimed(index);
+    claims_data.serialize(&mut &mut claims_account.data.borrow_mut()[..])?;
+
+    let vesting_policy = load_config(program_id, config_account)?
+        .filter(|config| config.vesting_threshold > 0 && amount > config.vesting_threshold);
+    let Some(config_data) = vesting_policy else {
+        return mint_reward(
+            program_id,
+            claimant,
+            claimant,
+            system_program,
+            account_info_iter,
+            amount,
+        );
+    };
+
+    let (vesting_pda, vesting_bump) = Pubkey::find_program_address(
+        &[
+            b"vesting",
+            claims_account.key.as_ref(),
+            &index.to_le_bytes(),
+        ],
+        program_id,
+    );
+    if vesting_pda != *vesting_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        claimant,
+        vesting_account,
+        system_program,
+        VestingState::SIZE,
+        program_id,
+        &[
+            b"vesting",
+            claims_account.key.as_ref(),
+            &index.to_le_bytes(),
+            &[vesting_bump],
+        ],
+    )?;
+
+    debug_msg!(
+        "Vesting {} tokens over {} seconds",
+        amount,
+        config_data.vesting_duration_secs
+    );
+    let vesting_data = VestingState {
+        discriminator: VestingState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        beneficiary: *claimant.key,
+        total: amount,
+        start: Clock::get()?.unix_timestamp,
+        duration_secs: config_data.vesting_duration_secs,
+        claimed: 0,
+    };
+    vesting_data.serialize(&mut &mut vesting_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct ClaimVestedAccounts<'a, 'info> {
+    #[account(signer)]
+    beneficiary: &'a AccountInfo<'info>,
+    #[account(mut)]
+    vesting_account: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Mints the unlocked, not yet claimed part of a vesting grant to its
+/// beneficiary.
+pub fn claim_vested(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Claiming vested tokens...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ClaimVestedAccounts {
+        beneficiary,
+        vesting_account,
+        system_program,
+    } = ClaimVestedAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut vesting_data = VestingAccount::load_mut(program_id, vesting_account)?;
+
+    if vesting_data.beneficiary != *beneficiary.key {
+        msg!("Signer is not the vesting beneficiary");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    let unlocked = vesting_data.unlocked_amount(Clock::get()?.unix_timestamp);
+    let amount = unlocked.saturating_sub(vesting_data.claimed);
+    vesting_data.claimed = unlocked;
+    vesting_data.save()?;
+
+    mint_reward(
+        program_id,
+        beneficiary,
+        beneficiary,
+        system_program,
+        account_info_iter,
+        amount,
+    )
+}
+
+/// Localnet faucet: mints `amount` reward tokens to any recipient without
+/// authorization.
+#[cfg(feature = "test-utils")]
+pub fn mint_test_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    debug_msg!("Minting {} test tokens...", amount);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let recipient = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    mint_reward(
+        program_id,
+        payer,
+        recipient,
+        system_program,
+        account_info_iter,
+        amount,
+    )
+}
+
+/// Localnet fixture: creates `owner`'s profile if needed and overwrites its
+/// activity counters and pending rewards.
+#[cfg(feature = "test-utils")]
+pub fn set_test_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    owner: Pubkey,
+    comments_created: u64,
+    reviews_published: u64,
+    pending_rewards: u64,
+) -> ProgramResult {
+    debug_msg!("Setting test profile for {}...", owner);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut profile_data =
+        open_profile(program_id, payer, profile_account, system_program, &owner)?;
+    profile_data.comments_created = comments_created;
+    profile_data.reviews_published = reviews_published;
+    profile_data.pending_rewards = pending_rewards;
+
+    profile_data.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_referral_bonus(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    referral_bonus: u64,
+) -> ProgramResult {
+    debug_msg!("Setting referral bonus: {}", referral_bonus);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.referral_bonus = referral_bonus;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_vesting_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    threshold: u64,
+    duration_secs: u64,
+) -> ProgramResult {
+    debug_msg!(
+        "Setting vesting policy: above {} over {} seconds",
+        threshold,
+        duration_secs
+    );
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.vesting_threshold = threshold;
+    config_data.vesting_duration_secs = duration_secs;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Creates `owner`'s associated token account for `mint` if it doesn't exist
+/// yet, paid for by `payer`, so reward flows don't need a separate setup
+/// transaction.
+pub fn create_associated_token_account_idempotent<'a>(
+    payer: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    ata: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    ata_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if *token_program.key != spl_token::ID {
+        msg!("Incorrect token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *ata_program.key != spl_associated_token_account::ID {
+        msg!("Incorrect associated token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if *ata.key != get_associated_token_address(owner.key, mint.key) {
+        msg!("Incorrect token account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    debug_msg!("Creating associated token account: {}", ata.key);
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            payer.key,
+            owner.key,
+            mint.key,
+            token_program.key,
+        ),
+        &[
+            payer.clone(),
+            ata.clone(),
+            owner.clone(),
+            mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            ata_program.clone(),
+        ],
+    )
+}
+
+/// Caller's share of `lamports` reclaimed by `PurgeExpired`.
+pub fn purge_bounty(lamports: u64) -> Result<u64, ProgramError> {
+    share_bps(lamports, PURGE_BOUNTY_BPS)
+}
+
+/// `bps` basis points of `amount`, rounded down.
+fn share_bps(amount: u64, bps: u64) -> Result<u64, ProgramError> {
+    let share = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(ReviewError::ArithmeticOverflow)?
+        / 10_000;
+    share
+        .try_into()
+        .map_err(|_| ReviewError::ArithmeticOverflow.into())
+}
+
+pub fn purge_expired(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Purging expired accounts...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?;
+    let airdrop_account = next_account_info(account_info_iter)?;
+    let rent_recipient = next_account_info(account_info_iter)?;
+
+    if !caller.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let airdrop_data = AirdropAccount::load(program_id, airdrop_account)?;
+
+    let (airdrop_pda, _airdrop_bump) = Pubkey::find_program_address(&[b"airdrop"], program_id);
+    if airdrop_pda != *airdrop_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // Claim bitmaps were paid for by the airdrop authority, so that is where
+    // their rent goes back to.
+    if *rent_recipient.key != airdrop_data.authority {
+        msg!("Rent recipient is not the airdrop authority");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let purge_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    require_ascending_keys(&purge_accounts)?;
+
+    for purge_account in purge_accounts {
+        if purge_account.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let claims_data = load_account::<AirdropClaimStatus>(purge_account)
+            .map_err(|_| ReviewError::AccountNotPurgeable)?;
+        if claims_data.discriminator != AirdropClaimStatus::DISCRIMINATOR
+            || claims_data.round >= airdrop_data.round
+        {
+            msg!("Account {} is still live", purge_account.key);
+            return Err(ReviewError::AccountNotPurgeable.into());
+        }
+
+        let (claims_pda, _claims_bump) = Pubkey::find_program_address(
+            &[b"airdrop_claims", &claims_data.round.to_le_bytes()],
+            program_id,
+        );
+        if claims_pda != *purge_account.key {
+            msg!("Invalid seeds for claim bitmap PDA");
+            return Err(ReviewError::InvalidPDA.into());
+        }
+
+        let lamports = purge_account.lamports();
+        let bounty = purge_bounty(lamports)?;
+        debug_msg!(
+            "Purging claim bitmap for round {}: {} lamports",
+            claims_data.round,
+            lamports
+        );
+
+        **purge_account.try_borrow_mut_lamports()? = lamports
+            .checked_sub(bounty)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        **caller.try_borrow_mut_lamports()? = caller
+            .lamports()
+            .checked_add(bounty)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        close_account(purge_account, rent_recipient, true)?;
+    }
+
+    Ok(())
+}
+
+pub fn delete_movie_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Deleting movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    require_writable(&[initializer, pda_account])?;
+
+    debug_msg!("unpacking state account");
+    let account_data = load_account::<MovieAccountState>(pda_account)?;
+
+    if account_data.reviewer != *initializer.key {
+        msg!("Review does not belong to signer");
+        return Err(ReviewError::UnauthorizedReviewer.into());
+    }
+
+    if account_data.address(program_id) != Some(*pda_account.key) {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    recount_review(
+        program_id,
+        accounts,
+        &account_data,
+        account_data.counted_rating(),
+        None,
+    )?;
+    debug_msg!("Closing review: {}", account_data.title);
+    close_account(pda_account, initializer, false)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct MigrateLegacyReviewAccounts<'a, 'info> {
+    #[account(mut)]
+    review_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    reviewer: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Shrinks a review created with the original fixed 1000-byte allocation to
+/// the exact size of its contents, refunding the freed rent to the reviewer.
+/// Anyone may migrate a review. Fields added since the original layout read
+/// as their defaults from the zeroed padding, and are written out explicitly.
+pub fn migrate_legacy_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Migrating legacy review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let MigrateLegacyReviewAccounts {
+        review_account,
+        reviewer,
+        system_program,
+    } = MigrateLegacyReviewAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let review_data = load_review(program_id, review_account)?;
+    if review_data.reviewer != *reviewer.key {
+        msg!("Incorrect reviewer account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let new_len = MovieAccountState::get_account_size(
+        review_data.title.clone(),
+        review_data.description.clone(),
+    )?;
+    if review_account.data_len() <= new_len {
+        msg!("Review is already {} bytes", review_account.data_len());
+        return Err(ReviewError::AlreadyMigrated.into());
+    }
+
+    debug_msg!(
+        "Resizing review from {} to {} bytes",
+        review_account.data_len(),
+        new_len
+    );
+    resize_account(review_account, reviewer, system_program, new_len)?;
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct MigrateReviewAccounts<'a, 'info> {
+    #[account(mut)]
+    review_account: &'a AccountInfo<'info>,
+    #[account(signer, mut)]
+    reviewer: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+    #[account(mut)]
+    summary_account: &'a AccountInfo<'info>,
+}
+
+/// Upgrades a version 1 review to the current layout, which adds the version
+/// and the bump seed of the review's address. The reviewer pays for the extra
+/// bytes. Every other instruction reads and writes either version, so
+/// migrating is optional, but version 1 reviews are only counted in their
+/// title's summary once migrated.
+pub fn migrate_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Migrating review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let MigrateReviewAccounts {
+        review_account,
+        reviewer,
+        system_program,
+        summary_account,
+    } = MigrateReviewAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut review_data = load_review(program_id, review_account)?;
+    if review_data.reviewer != *reviewer.key {
+        msg!("Review does not belong to signer");
+        return Err(ReviewError::UnauthorizedReviewer.into());
+    }
+    if review_data.version >= state::STATE_VERSION {
+        msg!("Review is already version {}", review_data.version);
+        return Err(ReviewError::AlreadyCurrentVersion.into());
+    }
+
+    let (_review_pda, bump_seed) = state::derive_review_pda(
+        program_id,
+        review_data.kind,
+        reviewer.key,
+        &review_data.title,
+    );
+    review_data.version = state::STATE_VERSION;
+    review_data.bump = bump_seed;
+
+    // Reviews still in a 1000-byte account have room to spare; those are
+    // shrunk by `MigrateLegacyReview`.
+    let new_len = MovieAccountState::get_account_size(
+        review_data.title.clone(),
+        review_data.description.clone(),
+    )?;
+    if review_account.data_len() < new_len {
+        resize_account(review_account, reviewer, system_program, new_len)?;
+    }
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+    debug_msg!("Review migrated to version {}", state::STATE_VERSION);
+
+    if let Some(rating) = review_data.counted_rating() {
+        let mut summary_data = open_summary(
+            program_id,
+            reviewer,
+            summary_account,
+            system_program,
+            review_data.kind,
+            &review_data.title,
+        )?;
+        count_in_summary(&mut summary_data, rating)?;
+        summary_data.serialize(&mut &mut summary_account.data.borrow_mut()[..])?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct ReclaimOrphansAccounts<'a, 'info> {
+    admin: &'a AccountInfo<'info>,
+    config_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    treasury: &'a AccountInfo<'info>,
+    review_account: &'a AccountInfo<'info>,
+}
+
+/// Admin only: closes accounts left behind by a deleted review, sending their
+/// rent to the treasury. The review must be gone, and every remaining account
+/// must sit at the address its kind derives from the review, so nothing that
+/// belongs to a live review can be taken.
+///
+/// A comment counter is followed by the accounts of all the comments it
+/// numbered. Any that still exist are closed with it, since a recreated
+/// review would otherwise number new comments onto their addresses.
+pub fn reclaim_orphans(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Reclaiming orphaned accounts...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ReclaimOrphansAccounts {
+        admin,
+        config_account,
+        treasury,
+        review_account,
+    } = ReclaimOrphansAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let config_data = load_config_as_admin(program_id, admin, config_account)?;
+    if *treasury.key != config_data.treasury {
+        msg!("Incorrect treasury account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    // Deleting a review marks it closed and drains it, after which the
+    // runtime drops the account, so a live review is a program-owned account
+    // without the closed marker.
+    if review_account.owner == program_id && !state::is_closed(&review_account.data.borrow()) {
+        msg!("Review {} still exists", review_account.key);
+        return Err(ReviewError::AccountNotOrphaned.into());
+    }
+    let review = review_account.key;
+
+    // Orphans are in ascending key order; the comments that follow a counter
+    // are ordered by their index instead.
+    let mut previous_orphan = None;
+    while let Some(orphan) = account_info_iter.next() {
+        require_after(previous_orphan, orphan)?;
+        previous_orphan = Some(orphan.key);
+        if orphan.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let mut comments = 0;
+        let orphan_data = parse_any(&orphan.data.borrow())?;
+        let (orphan_pda, _orphan_bump) = match orphan_data {
+            AnyAccount::CommentCounter(counter_data) => {
+                comments = counter_data.counter;
+                Pubkey::find_program_address(&[review.as_ref(), b"comment"], program_id)
+            }
+            AnyAccount::Comment(comment_data) => Pubkey::find_program_address(
+                &[review.as_ref(), comment_data.count.to_be_bytes().as_ref()],
+                program_id,
+            ),
+            AnyAccount::Acl(_) => {
+                Pubkey::find_program_address(&[b"acl", review.as_ref()], program_id)
+            }
+            AnyAccount::PremiumContent(_) => {
+                Pubkey::find_program_address(&[b"premium", review.as_ref()], program_id)
+            }
+            AnyAccount::AccessKeyHint(hint_data) => Pubkey::find_program_address(
+                &[b"key_hint", review.as_ref(), hint_data.subscriber.as_ref()],
+                program_id,
+            ),
+            _ => {
+                msg!("Account {} does not belong to a review", orphan.key);
+                return Err(ReviewError::AccountNotOrphaned.into());
+            }
+        };
+        if orphan_pda != *orphan.key {
+            msg!("Invalid seeds for PDA");
+            return Err(ReviewError::InvalidPDA.into());
+        }
+
+        for count in 0..comments {
+            let comment_account = next_account_info(account_info_iter)?;
+            let (comment_pda, _comment_bump) = Pubkey::find_program_address(
+                &[review.as_ref(), count.to_be_bytes().as_ref()],
+                program_id,
+            );
+            if comment_pda != *comment_account.key {
+                msg!("Invalid seeds for comment PDA");
+                return Err(ReviewError::InvalidPDA.into());
+            }
+            if comment_account.owner == program_id {
+                close_account(comment_account, treasury, true)?;
+            }
+        }
+
+        debug_msg!("Reclaiming {}: {} lamports", orphan.key, orphan.lamports());
+        close_account(orphan, treasury, true)?;
+    }
+
+    Ok(())
+}
+
+/// Creates the `[b"inbox", owner]` account on first use, paid for by `payer`.
+/// Returns the current inbox contents.
+fn open_inbox<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    inbox_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    owner: &Pubkey,
+) -> Result<InboxState, ProgramError> {
+    let (inbox_pda, inbox_bump) =
+        Pubkey::find_program_address(&[b"inbox", owner.as_ref()], program_id);
+    if inbox_pda != *inbox_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if inbox_account.owner == program_id {
+        let inbox_data = load_account::<InboxState>(inbox_account)?;
+        if !inbox_data.is_initialized() {
+            msg!("Account is not initialized");
+            return Err(ReviewError::UninitializedAccount.into());
+        }
+        return Ok(inbox_data);
+    }
+
+    create_pda_account(
+        payer,
+        inbox_account,
+        system_program,
+        InboxState::SIZE,
+        program_id,
+        &[b"inbox", owner.as_ref(), &[inbox_bump]],
+    )?;
+
+    debug_msg!("Inbox created: {}", inbox_pda);
+
+    Ok(InboxState {
+        discriminator: InboxState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        owner: *owner,
+        next_seq: 0,
+        read_seq: 0,
+        notifications: Vec::new(),
+    })
+}
+
+/// Appends a notification to `recipient`'s inbox, creating it at `payer`'s
+/// expense if needed. Called by handlers that mention or reply to a user.
+pub fn notify<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    inbox_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    recipient: &Pubkey,
+    kind: NotificationKind,
+    source: &Pubkey,
+) -> ProgramResult {
+    let mut inbox_data = open_inbox(program_id, payer, inbox_account, system_program, recipient)?;
+
+    let seq = inbox_data.push(kind, *source, Clock::get()?.slot)?;
+    debug_msg!("Notification {} sent to {}", seq, recipient);
+
+    inbox_data.serialize(&mut &mut inbox_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct MarkNotificationsReadAccounts<'a, 'info> {
+    #[account(signer)]
+    user: &'a AccountInfo<'info>,
+    #[account(mut)]
+    inbox_account: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+pub fn mark_notifications_read(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    upto_seq: u64,
+) -> ProgramResult {
+    debug_msg!("Marking notifications read...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let MarkNotificationsReadAccounts {
+        user,
+        inbox_account,
+        system_program,
+    } = MarkNotificationsReadAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut inbox_data = open_inbox(program_id, user, inbox_account, system_program, user.key)?;
+
+    // Never move the cursor past notifications that have not been sent yet,
+    // or they would arrive already read.
+    let upto_seq = upto_seq.min(inbox_data.next_seq);
+    if upto_seq > inbox_data.read_seq {
+        inbox_data.read_seq = upto_seq;
+    }
+    debug_msg!("Read up to: {}", inbox_data.read_seq);
+
+    inbox_data.serialize(&mut &mut inbox_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn subscribe(program_id: &Pubkey, accounts: &[AccountInfo], months: u16) -> ProgramResult {
+    debug_msg!("Subscribing for {} months...", months);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let subscriber = next_account_info(account_info_iter)?;
+    let reviewer = next_account_info(account_info_iter)?;
+    let subscription_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    if !subscriber.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if months == 0 {
+        msg!("Subscription must last at least one month");
+        return Err(ReviewError::InvalidSubscriptionLength.into());
+    }
+
+    let (subscription_pda, subscription_bump) = Pubkey::find_program_address(
+        &[b"sub", subscriber.key.as_ref(), reviewer.key.as_ref()],
+        program_id,
+    );
+    if subscription_pda != *subscription_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let price = SUBSCRIPTION_PRICE_LAMPORTS
+        .checked_mul(months as u64)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    // Until the config has been initialized there is no treasury to take a cut.
+    let mut cut = 0;
+    if config_account.owner == program_id {
+        let config_data = load_account::<ConfigState>(config_account)?;
+        if config_data.is_initialized() {
+            let treasury = next_account_info(account_info_iter)?;
+            if *treasury.key != config_data.treasury {
+                msg!("Incorrect treasury account");
+                return Err(ReviewError::IncorrectAccountError.into());
+            }
+
+            cut = share_bps(price, SUBSCRIPTION_PROTOCOL_CUT_BPS)?;
+            debug_msg!("Paying protocol cut of {} lamports", cut);
+            invoke(
+                &system_instruction::transfer(subscriber.key, treasury.key, cut),
+                &[subscriber.clone(), treasury.clone(), system_program.clone()],
+            )?;
+        }
+    }
+
+    let payout = price
+        .checked_sub(cut)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    debug_msg!("Paying reviewer {} lamports", payout);
+    invoke(
+        &system_instruction::transfer(subscriber.key, reviewer.key, payout),
+        &[subscriber.clone(), reviewer.clone(), system_program.clone()],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut subscription_data = if subscription_account.owner == program_id {
+        load_account::<SubscriptionState>(subscription_account)?
+    } else {
+        create_pda_account(
+            subscriber,
+            subscription_account,
+            system_program,
+            SubscriptionState::SIZE,
+            program_id,
+            &[
+                b"sub",
+                subscriber.key.as_ref(),
+                reviewer.key.as_ref(),
+                &[subscription_bump],
+            ],
+        )?;
+        debug_msg!("Subscription created: {}", subscription_pda);
+
+        SubscriptionState {
+            discriminator: SubscriptionState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            subscriber: *subscriber.key,
+            reviewer: *reviewer.key,
+            expires_at: now,
+        }
+    };
+
+    // Renewing an active subscription extends it rather than restarting it.
+    let duration = SUBSCRIPTION_MONTH_SECS
+        .checked_mul(months as i64)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    subscription_data.expires_at = subscription_data
+        .expires_at
+        .max(now)
+        .checked_add(duration)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    debug_msg!("Subscription expires at: {}", subscription_data.expires_at);
+
+    subscription_data.serialize(&mut &mut subscription_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Fails with `SubscriptionRequired` unless `subscription_account` holds an
+/// active subscription from `subscriber` to `reviewer`. Used to gate premium
+/// content.
+pub fn require_subscription(
+    program_id: &Pubkey,
+    subscription_account: &AccountInfo,
+    subscriber: &Pubkey,
+    reviewer: &Pubkey,
+) -> ProgramResult {
+    let (subscription_pda, _bump_seed) = Pubkey::find_program_address(
+        &[b"sub", subscriber.as_ref(), reviewer.as_ref()],
+        program_id,
+    );
+    if subscription_pda != *subscription_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if subscription_account.owner != program_id {
+        msg!("No subscription found");
+        return Err(ReviewError::SubscriptionRequired.into());
+    }
+
+    let subscription_data = load_account::<SubscriptionState>(subscription_account)?;
+    if !subscription_data.is_active(Clock::get()?.unix_timestamp) {
+        msg!("Subscription has expired");
+        return Err(ReviewError::SubscriptionRequired.into());
+    }
+
+    Ok(())
+}
+
+pub fn set_escrow_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    arbiter: Pubkey,
+    dispute_window_secs: u64,
+    resolution_timeout_secs: u64,
+) -> ProgramResult {
+    debug_msg!(
+        "Setting escrow policy: arbiter {}, {} second dispute window, {} second resolution timeout",
+        arbiter,
+        dispute_window_secs,
+        resolution_timeout_secs
+    );
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.escrow_arbiter = arbiter;
+    config_data.escrow_dispute_window_secs = dispute_window_secs;
+    config_data.escrow_resolution_timeout_secs = resolution_timeout_secs;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Config admin only: replaces the set of switched off `Feature`s.
+pub fn set_disabled_features(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    disabled_features: u32,
+) -> ProgramResult {
+    debug_msg!("Setting disabled features: {:#b}", disabled_features);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.disabled_features = disabled_features;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Config admin only: caps review title and description lengths below what
+/// fits in a review account. Zero removes a cap.
+pub fn set_review_length_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_title_len: u32,
+    max_description_len: u32,
+) -> ProgramResult {
+    debug_msg!(
+        "Setting review length limits: title {}, description {}",
+        max_title_len,
+        max_description_len
+    );
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.max_title_len = max_title_len;
+    config_data.max_description_len = max_description_len;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Config admin only: pauses or unpauses the program.
+pub fn set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    debug_msg!("Setting paused: {}", paused);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.paused = paused;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Config admin only: sets how long a `SnapshotAggregate` epoch lasts. Zero
+/// stops new snapshots; existing ones stay.
+pub fn set_snapshot_interval(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    snapshot_interval_secs: u64,
+) -> ProgramResult {
+    debug_msg!("Setting snapshot interval: {}s", snapshot_interval_secs);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.snapshot_interval_secs = snapshot_interval_secs;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// `now` plus `secs`, failing instead of overflowing.
+fn deadline_after(now: i64, secs: u64) -> Result<i64, ProgramError> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(|secs| now.checked_add(secs))
+        .ok_or_else(|| ReviewError::ArithmeticOverflow.into())
+}
+
+/// Loads an escrow for modification, checking it lives at the address derived
+/// from its funder, recipient and nonce.
+fn load_escrow<'a, 'info>(
+    program_id: &Pubkey,
+    escrow_account: &'a AccountInfo<'info>,
+) -> Result<EscrowAccount<'a, 'info>, ProgramError> {
+    let escrow_data = EscrowAccount::load_mut(program_id, escrow_account)?;
+
+    let (escrow_pda, _bump_seed) = Pubkey::find_program_address(
+        &[
+            b"escrow",
+            escrow_data.funder.as_ref(),
+            escrow_data.recipient.as_ref(),
+            &escrow_data.nonce.to_le_bytes(),
+        ],
+        program_id,
+    );
+    if escrow_pda != *escrow_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    Ok(escrow_data)
+}
+
+/// Moves the escrowed lamports to `destination`, which must be `winner`, and
+/// marks the escrow resolved. The account keeps its rent.
+fn pay_out_escrow(
+    escrow_data: &mut EscrowAccount,
+    destination: &AccountInfo,
+    winner: Pubkey,
+) -> ProgramResult {
+    if *destination.key != winner {
+        msg!("Incorrect escrow destination");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    debug_msg!("Paying {} lamports to {}", escrow_data.amount, winner);
+    let escrow_account = escrow_data.info();
+    **escrow_account.try_borrow_mut_lamports()? = escrow_account
+        .lamports()
+        .checked_sub(escrow_data.amount)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(escrow_data.amount)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+
+    escrow_data.status = EscrowStatus::Resolved;
+    escrow_data.paid_to = winner;
+    escrow_data.save()
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+struct FundEscrowAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    funder: &'a AccountInfo<'info>,
+    recipient: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"escrow", funder, recipient, nonce.to_le_bytes()], bump)]
+    escrow_account: &'a AccountInfo<'info>,
+    escrow_account_bump: u8,
+    config_account: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Moves `amount` lamports from the funder into a new escrow for the
+/// recipient. Unless the funder disputes it within the config's dispute
+/// window, anyone can then release it with `SettleEscrow`.
+pub fn fund_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    nonce: u64,
+    amount: u64,
+) -> ProgramResult {
+    debug_msg!("Funding escrow with {} lamports...", amount);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let FundEscrowAccounts {
+        funder,
+        recipient,
+        escrow_account,
+        escrow_account_bump,
+        config_account,
+        system_program,
+    } = FundEscrowAccounts::try_accounts(program_id, account_info_iter, nonce)?;
+
+    let config_data = load_config(program_id, config_account)?.ok_or_else(|| {
+        msg!("Config is not initialized");
+        ProgramError::from(ReviewError::UninitializedAccount)
+    })?;
+    if config_data.escrow_arbiter == Pubkey::default() {
+        msg!("No escrow arbiter has been set");
+        return Err(ReviewError::EscrowNotConfigured.into());
+    }
+
+    let dispute_deadline = deadline_after(
+        Clock::get()?.unix_timestamp,
+        config_data.escrow_dispute_window_secs,
+    )?;
+
+    create_pda_account(
+        funder,
+        escrow_account,
+        system_program,
+        EscrowState::SIZE,
+        program_id,
+        &[
+            b"escrow",
+            funder.key.as_ref(),
+            recipient.key.as_ref(),
+            &nonce.to_le_bytes(),
+            &[escrow_account_bump],
+        ],
+    )?;
+    debug_msg!("Escrow created: {}", escrow_account.key);
+
+    invoke(
+        &system_instruction::transfer(funder.key, escrow_account.key, amount),
+        &[
+            funder.clone(),
+            escrow_account.clone(),
+            system_program.clone(),
+        ],
+    )?;
+
+    let escrow_data = EscrowState {
+        discriminator: EscrowState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        funder: *funder.key,
+        recipient: *recipient.key,
+        nonce,
+        amount,
+        status: EscrowStatus::Funded,
+        dispute_deadline,
+        resolution_deadline: 0,
+        paid_to: Pubkey::default(),
+    };
+    escrow_data.serialize(&mut &mut escrow_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct DisputeEscrowAccounts<'a, 'info> {
+    #[account(signer)]
+    funder: &'a AccountInfo<'info>,
+    #[account(mut)]
+    escrow_account: &'a AccountInfo<'info>,
+    config_account: &'a AccountInfo<'info>,
+}
+
+/// Freezes a funded escrow until the arbiter resolves it. Only the funder can
+/// dispute, and only before the dispute deadline.
+pub fn dispute_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Disputing escrow...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let DisputeEscrowAccounts {
+        funder,
+        escrow_account,
+        config_account,
+    } = DisputeEscrowAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut escrow_data = load_escrow(program_id, escrow_account)?;
+    if escrow_data.funder != *funder.key {
+        msg!("Escrow does not belong to signer");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+    if escrow_data.status != EscrowStatus::Funded {
+        msg!("Escrow is not funded");
+        return Err(ReviewError::InvalidEscrowStatus.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if now >= escrow_data.dispute_deadline {
+        msg!("Dispute window closed at {}", escrow_data.dispute_deadline);
+        return Err(ReviewError::DisputeWindowClosed.into());
+    }
+
+    let config_data = load_config(program_id, config_account)?.ok_or_else(|| {
+        msg!("Config is not initialized");
+        ProgramError::from(ReviewError::UninitializedAccount)
+    })?;
+
+    escrow_data.status = EscrowStatus::Disputed;
+    escrow_data.resolution_deadline =
+        deadline_after(now, config_data.escrow_resolution_timeout_secs)?;
+    debug_msg!(
+        "Escrow frozen until resolved or {}",
+        escrow_data.resolution_deadline
+    );
+    escrow_data.save()?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct ResolveEscrowAccounts<'a, 'info> {
+    #[account(signer)]
+    arbiter: &'a AccountInfo<'info>,
+    config_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    escrow_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    destination: &'a AccountInfo<'info>,
+}
+
+/// Arbiter only: pays a disputed escrow to its recipient with `release`, or
+/// back to its funder otherwise.
+pub fn resolve_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    release: bool,
+) -> ProgramResult {
+    debug_msg!("Resolving escrow...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ResolveEscrowAccounts {
+        arbiter,
+        config_account,
+        escrow_account,
+        destination,
+    } = ResolveEscrowAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let config_data = load_config(program_id, config_account)?.ok_or_else(|| {
+        msg!("Config is not initialized");
+        ProgramError::from(ReviewError::UninitializedAccount)
+    })?;
+    if config_data.escrow_arbiter != *arbiter.key {
+        msg!("Signer is not the escrow arbiter");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    let mut escrow_data = load_escrow(program_id, escrow_account)?;
+    if escrow_data.status != EscrowStatus::Disputed {
+        msg!("Escrow is not disputed");
+        return Err(ReviewError::InvalidEscrowStatus.into());
+    }
+
+    let winner = if release {
+        escrow_data.recipient
+    } else {
+        escrow_data.funder
+    };
+    pay_out_escrow(&mut escrow_data, destination, winner)
+}
+
+#[derive(Accounts)]
+struct SettleEscrowAccounts<'a, 'info> {
+    #[account(mut)]
+    escrow_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    destination: &'a AccountInfo<'info>,
+}
+
+/// Permissionless timeouts: releases a funded escrow to its recipient once
+/// the dispute deadline passes, and refunds a disputed one to its funder once
+/// the arbiter lets the resolution deadline pass.
+pub fn settle_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Settling escrow...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let SettleEscrowAccounts {
+        escrow_account,
+        destination,
+    } = SettleEscrowAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut escrow_data = load_escrow(program_id, escrow_account)?;
+    let (winner, deadline) = match escrow_data.status {
+        EscrowStatus::Funded => (escrow_data.recipient, escrow_data.dispute_deadline),
+        EscrowStatus::Disputed => (escrow_data.funder, escrow_data.resolution_deadline),
+        EscrowStatus::Resolved => {
+            msg!("Escrow is already resolved");
+            return Err(ReviewError::InvalidEscrowStatus.into());
+        }
+    };
+
+    if Clock::get()?.unix_timestamp < deadline {
+        msg!("Escrow is locked until {}", deadline);
+        return Err(ReviewError::EscrowLocked.into());
+    }
+
+    pay_out_escrow(&mut escrow_data, destination, winner)
+}
+
+#[derive(Accounts)]
+struct CloseEscrowAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    funder: &'a AccountInfo<'info>,
+    #[account(mut)]
+    escrow_account: &'a AccountInfo<'info>,
+}
+
+/// Returns the rent of a resolved escrow to its funder.
+pub fn close_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Closing escrow...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let CloseEscrowAccounts {
+        funder,
+        escrow_account,
+    } = CloseEscrowAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let escrow_data = load_escrow(program_id, escrow_account)?;
+    if escrow_data.funder != *funder.key {
+        msg!("Escrow does not belong to signer");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+    if escrow_data.status != EscrowStatus::Resolved {
+        msg!("Escrow is not resolved");
+        return Err(ReviewError::InvalidEscrowStatus.into());
+    }
+
+    close_account(escrow_account, funder, true)
+}
+
+#[derive(Accounts)]
+#[instruction(movie_a: &str, movie_b: &str)]
+struct CreateVersusPollAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    creator: &'a AccountInfo<'info>,
+    #[account(
+        mut,
+        pda = [
+            b"versus",
+            state::review_title_seed(movie_a),
+            state::review_title_seed(movie_b),
+        ],
+        bump
+    )]
+    poll_account: &'a AccountInfo<'info>,
+    poll_account_bump: u8,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Opens a poll between the movies titled `movie_a` and `movie_b`, taking
+/// votes until `ends_at`. Each ordered pair of titles gets one poll. Titles
+/// are bounded like review titles.
+pub fn create_versus_poll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    movie_a: String,
+    movie_b: String,
+    ends_at: i64,
+) -> ProgramResult {
+    debug_msg!("Creating versus poll: {} vs {}", movie_a, movie_b);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let CreateVersusPollAccounts {
+        creator,
+        poll_account,
+        poll_account_bump,
+        system_program,
+    } = CreateVersusPollAccounts::try_accounts(program_id, account_info_iter, &movie_a, &movie_b)?;
+
+    if movie_a == movie_b {
+        msg!("Cannot poll a movie against itself");
+        return Err(ReviewError::InvalidVersusPair.into());
+    }
+    if movie_a.len().max(movie_b.len()) > MAX_TITLE_LEN {
+        msg!("Title is longer than {} bytes", MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    if ends_at <= Clock::get()?.unix_timestamp {
+        msg!("Poll would end in the past");
+        return Err(ReviewError::PollClosed.into());
+    }
+
+    create_pda_account(
+        creator,
+        poll_account,
+        system_program,
+        VersusPollState::get_account_size(&movie_a, &movie_b)?,
+        program_id,
+        &[
+            b"versus",
+            &state::review_title_seed(&movie_a),
+            &state::review_title_seed(&movie_b),
+            &[poll_account_bump],
+        ],
+    )?;
+    debug_msg!("Poll created: {}", poll_account.key);
+
+    let poll_data = VersusPollState {
+        discriminator: VersusPollState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        creator: *creator.key,
+        movie_a,
+        movie_b,
+        ends_at,
+        votes_a: 0,
+        votes_b: 0,
+        finalized: false,
+        winner: None,
+    };
+    poll_data.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+fn load_versus_poll<'a, 'info>(
+    program_id: &Pubkey,
+    poll_account: &'a AccountInfo<'info>,
+) -> Result<VersusPollAccount<'a, 'info>, ProgramError> {
+    let poll_data = VersusPollAccount::load_mut(program_id, poll_account)?;
+
+    let (poll_pda, _bump_seed) =
+        state::derive_versus_poll_pda(program_id, &poll_data.movie_a, &poll_data.movie_b);
+    if poll_pda != *poll_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    Ok(poll_data)
+}
+
+#[derive(Accounts)]
+struct VoteVersusAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    voter: &'a AccountInfo<'info>,
+    #[account(mut)]
+    poll_account: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"versus_vote", poll_account, voter], bump)]
+    receipt_account: &'a AccountInfo<'info>,
+    receipt_account_bump: u8,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Votes for one side of an open poll. The vote receipt already existing
+/// rejects a second vote from the same wallet.
+pub fn vote_versus(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    side: VersusSide,
+) -> ProgramResult {
+    debug_msg!("Voting for {:?}...", side);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let VoteVersusAccounts {
+        voter,
+        poll_account,
+        receipt_account,
+        receipt_account_bump,
+        system_program,
+    } = VoteVersusAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut poll_data = load_versus_poll(program_id, poll_account)?;
+    if poll_data.finalized || Clock::get()?.unix_timestamp >= poll_data.ends_at {
+        msg!("Poll ended at {}", poll_data.ends_at);
+        return Err(ReviewError::PollClosed.into());
+    }
+
+    create_pda_account(
+        voter,
+        receipt_account,
+        system_program,
+        VersusVoteReceipt::SIZE,
+        program_id,
+        &[
+            b"versus_vote",
+            poll_account.key.as_ref(),
+            voter.key.as_ref(),
+            &[receipt_account_bump],
+        ],
+    )?;
+
+    let receipt_data = VersusVoteReceipt {
+        discriminator: VersusVoteReceipt::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        poll: *poll_account.key,
+        voter: *voter.key,
+        side,
+    };
+    receipt_data.serialize(&mut &mut receipt_account.data.borrow_mut()[..])?;
+
+    let votes = match side {
+        VersusSide::A => &mut poll_data.votes_a,
+        VersusSide::B => &mut poll_data.votes_b,
+    };
+    *votes = votes
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    poll_data.save()
+}
+
+#[derive(Accounts)]
+struct VoteOnReviewAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    voter: &'a AccountInfo<'info>,
+    #[account(mut)]
+    review_account: &'a AccountInfo<'info>,
+    #[account(mut, pda = [review_account, voter, b"vote"], bump)]
+    vote_account: &'a AccountInfo<'info>,
+    vote_account_bump: u8,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Up- or downvotes a review. The vote receipt already existing rejects a
+/// second vote from the same wallet.
+pub fn vote_on_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    is_upvote: bool,
+) -> ProgramResult {
+    debug_msg!("Voting on review, upvote: {}", is_upvote);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let VoteOnReviewAccounts {
+        voter,
+        review_account,
+        vote_account,
+        vote_account_bump,
+        system_program,
+    } = VoteOnReviewAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut review_data = load_review(program_id, review_account)?;
+    require_not_archived(&review_data)?;
+
+    create_pda_account(
+        voter,
+        vote_account,
+        system_program,
+        ReviewVote::SIZE,
+        program_id,
+        &[
+            review_account.key.as_ref(),
+            voter.key.as_ref(),
+            b"vote",
+            &[vote_account_bump],
+        ],
+    )?;
+
+    let vote_data = ReviewVote {
+        discriminator: ReviewVote::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        review: *review_account.key,
+        voter: *voter.key,
+        is_upvote,
+    };
+    vote_data.serialize(&mut &mut vote_account.data.borrow_mut()[..])?;
+
+    let votes = if is_upvote {
+        &mut review_data.upvotes
+    } else {
+        &mut review_data.downvotes
+    };
+    *votes = votes
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct TipReviewerAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    tipper: &'a AccountInfo<'info>,
+    #[account(mut)]
+    review_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    reviewer: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Sends `lamports` from the tipper to the review's reviewer, adding them to
+/// the review's `tips_received`.
+pub fn tip_reviewer(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    debug_msg!("Tipping reviewer {} lamports...", lamports);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let TipReviewerAccounts {
+        tipper,
+        review_account,
+        reviewer,
+        system_program,
+    } = TipReviewerAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut review_data = load_review(program_id, review_account)?;
+    require_not_archived(&review_data)?;
+    if review_data.reviewer != *reviewer.key {
+        msg!("Incorrect reviewer account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    invoke(
+        &system_instruction::transfer(tipper.key, reviewer.key, lamports),
+        &[tipper.clone(), reviewer.clone(), system_program.clone()],
+    )?;
+
+    review_data.tips_received = review_data
+        .tips_received
+        .checked_add(lamports)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct TipReviewerTokenAccounts<'a, 'info> {
+    #[account(signer)]
+    tipper: &'a AccountInfo<'info>,
+    review_account: &'a AccountInfo<'info>,
+    #[account(pda = [b"token_mint"])]
+    token_mint: &'a AccountInfo<'info>,
+    #[account(mut)]
+    tipper_token_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    reviewer_token_account: &'a AccountInfo<'info>,
+    #[account(address = spl_token::ID)]
+    token_program: &'a AccountInfo<'info>,
+}
+
+/// Transfers `amount` reward tokens from the tipper's associated token
+/// account to the reviewer's, which must already exist.
+pub fn tip_reviewer_token(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    debug_msg!("Tipping reviewer {} reward tokens...", amount);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let TipReviewerTokenAccounts {
+        tipper,
+        review_account,
+        token_mint,
+        tipper_token_account,
+        reviewer_token_account,
+        token_program,
+    } = TipReviewerTokenAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let review_data = load_review(program_id, review_account)?;
+    require_not_archived(&review_data)?;
+
+    if *tipper_token_account.key != get_associated_token_address(tipper.key, token_mint.key)
+        || *reviewer_token_account.key
+            != get_associated_token_address(&review_data.reviewer, token_mint.key)
+    {
+        msg!("Incorrect token account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            tipper_token_account.key,
+            reviewer_token_account.key,
+            tipper.key,
+            &[],
+            amount,
+        )?,
+        &[
+            tipper_token_account.clone(),
+            reviewer_token_account.clone(),
+            tipper.clone(),
+        ],
+    )
+}
+
+#[derive(Accounts)]
+struct FinalizeVersusPollAccounts<'a, 'info> {
+    #[account(mut)]
+    poll_account: &'a AccountInfo<'info>,
+    #[account(signer, mut)]
+    payer: &'a AccountInfo<'info>,
+    #[account(mut)]
+    summary_a_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    summary_b_account: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Permissionless once a poll ends: records the movie with more votes as the
+/// winner, or no winner on a tie, and adds the result to both movies'
+/// summaries. `payer` pays for a summary the movie doesn't have yet.
+pub fn finalize_versus_poll(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Finalizing versus poll...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let FinalizeVersusPollAccounts {
+        poll_account,
+        payer,
+        summary_a_account,
+        summary_b_account,
+        system_program,
+    } = FinalizeVersusPollAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut poll_data = load_versus_poll(program_id, poll_account)?;
+    if poll_data.finalized {
+        msg!("Poll is already finalized");
+        return Err(ReviewError::PollClosed.into());
+    }
+    if Clock::get()?.unix_timestamp < poll_data.ends_at {
+        msg!("Poll is open until {}", poll_data.ends_at);
+        return Err(ReviewError::PollOpen.into());
+    }
+
+    poll_data.winner = match poll_data.votes_a.cmp(&poll_data.votes_b) {
+        Ordering::Greater => Some(VersusSide::A),
+        Ordering::Less => Some(VersusSide::B),
+        Ordering::Equal => None,
+    };
+    poll_data.finalized = true;
+    debug_msg!("Winner: {:?}", poll_data.winner);
+
+    let mut summary_a = open_summary(
+        program_id,
+        payer,
+        summary_a_account,
+        system_program,
+        EntityKind::Movie,
+        &poll_data.movie_a,
+    )?;
+    let mut summary_b = open_summary(
+        program_id,
+        payer,
+        summary_b_account,
+        system_program,
+        EntityKind::Movie,
+        &poll_data.movie_b,
+    )?;
+    if let Some(side) = poll_data.winner {
+        let (winner, loser) = match side {
+            VersusSide::A => (&mut summary_a, &mut summary_b),
+            VersusSide::B => (&mut summary_b, &mut summary_a),
+        };
+        winner.versus_wins = winner
+            .versus_wins
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        loser.versus_losses = loser
+            .versus_losses
+            .checked_add(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+    summary_a.serialize(&mut &mut summary_a_account.data.borrow_mut()[..])?;
+    summary_b.serialize(&mut &mut summary_b_account.data.borrow_mut()[..])?;
+
+    poll_data.save()
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+struct AddToWatchlistAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    owner: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"watchlist", owner, page.to_le_bytes()], bump)]
+    watchlist_account: &'a AccountInfo<'info>,
+    watchlist_account_bump: u8,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Adds `movie_id` to a page of the signer's watchlist, creating the page if
+/// needed. A movie already on the page is left as is.
+pub fn add_to_watchlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    page: u32,
+    movie_id: String,
+) -> ProgramResult {
+    debug_msg!("Adding {} to watchlist page {}", movie_id, page);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let AddToWatchlistAccounts {
+        owner,
+        watchlist_account,
+        watchlist_account_bump,
+        system_program,
+    } = AddToWatchlistAccounts::try_accounts(program_id, account_info_iter, page)?;
+
+    if movie_id.is_empty() || movie_id.len() > MAX_WATCHLIST_MOVIE_ID_LEN {
+        msg!("Movie id must be 1 to {} bytes", MAX_WATCHLIST_MOVIE_ID_LEN);
+        return Err(ReviewError::InvalidDataLength.into());
+    }
+
+    if watchlist_account.owner != program_id {
+        create_pda_account(
+            owner,
+            watchlist_account,
+            system_program,
+            WatchlistState::SIZE,
+            program_id,
+            &[
+                b"watchlist",
+                owner.key.as_ref(),
+                &page.to_le_bytes(),
+                &[watchlist_account_bump],
+            ],
+        )?;
+        debug_msg!("Watchlist page created: {}", watchlist_account.key);
+
+        let watchlist_data = WatchlistState {
+            discriminator: WatchlistState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            owner: *owner.key,
+            page,
+            movie_ids: Vec::new(),
+        };
+        watchlist_data.serialize(&mut &mut watchlist_account.data.borrow_mut()[..])?;
+    }
+
+    let mut watchlist_data = WatchlistAccount::load_mut(program_id, watchlist_account)?;
+    if watchlist_data.movie_ids.contains(&movie_id) {
+        debug_msg!("Movie is already on the watchlist");
+        return Ok(());
+    }
+    if watchlist_data.movie_ids.len() >= MAX_WATCHLIST_ENTRIES {
+        msg!(
+            "Watchlist page holds at most {} movies",
+            MAX_WATCHLIST_ENTRIES
+        );
+        return Err(ReviewError::WatchlistPageFull.into());
+    }
+
+    watchlist_data.movie_ids.push(movie_id);
+    watchlist_data.save()
+}
+
+/// Loads a watchlist page for modification, checking it belongs to `owner`
+/// and lives at the address derived from its own owner and page.
+fn load_watchlist<'a, 'info>(
+    program_id: &Pubkey,
+    watchlist_account: &'a AccountInfo<'info>,
+    owner: &Pubkey,
+) -> Result<WatchlistAccount<'a, 'info>, ProgramError> {
+    let watchlist_data = WatchlistAccount::load_mut(program_id, watchlist_account)?;
+
+    let (watchlist_pda, _bump_seed) = Pubkey::find_program_address(
+        &[
+            b"watchlist",
+            watchlist_data.owner.as_ref(),
+            &watchlist_data.page.to_le_bytes(),
+        ],
+        program_id,
+    );
+    if watchlist_pda != *watchlist_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if watchlist_data.owner != *owner {
+        msg!("Watchlist does not belong to signer");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    Ok(watchlist_data)
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+struct RemoveFromWatchlistAccounts<'a, 'info> {
+    #[account(signer)]
+    owner: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"watchlist", owner, page.to_le_bytes()])]
+    watchlist_account: &'a AccountInfo<'info>,
+}
+
+/// Removes `movie_id` from a page of the signer's watchlist.
+pub fn remove_from_watchlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    page: u32,
+    movie_id: String,
+) -> ProgramResult {
+    debug_msg!("Removing {} from watchlist page {}", movie_id, page);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let RemoveFromWatchlistAccounts {
+        owner,
+        watchlist_account,
+    } = RemoveFromWatchlistAccounts::try_accounts(program_id, account_info_iter, page)?;
+
+    let mut watchlist_data = load_watchlist(program_id, watchlist_account, owner.key)?;
+
+    let Some(index) = watchlist_data
+        .movie_ids
+        .iter()
+        .position(|entry| *entry == movie_id)
+    else {
+        return Err(ReviewError::NotOnWatchlist.into());
+    };
+    watchlist_data.movie_ids.remove(index);
+    watchlist_data.save()
+}
+
+/// Splits off the last of `accounts` if it is a program-owned watchlist page.
+/// Review instructions take the page to clear after all their other
+/// accounts, optional ones included.
+fn split_watchlist<'a, 'info>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> (&'a [AccountInfo<'info>], Option<&'a AccountInfo<'info>>) {
+    match accounts.split_last() {
+        Some((last, rest))
+            if last.owner == program_id
+                && matches!(parse_any(&last.data.borrow()), Ok(AnyAccount::Watchlist(_))) =>
+        {
+            (rest, Some(last))
+        }
+        _ => (accounts, None),
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+struct IndexReviewsAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    reviewer: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"review_index", reviewer, page.to_le_bytes()], bump)]
+    index_account: &'a AccountInfo<'info>,
+    index_account_bump: u8,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Adds the signer's reviews passed after the fixed accounts to a page of
+/// their review index, creating the page if needed. Reviews already on the
+/// page are left as they are.
+pub fn index_reviews(program_id: &Pubkey, accounts: &[AccountInfo], page: u32) -> ProgramResult {
+    debug_msg!("Indexing reviews on page {}", page);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let IndexReviewsAccounts {
+        reviewer,
+        index_account,
+        index_account_bump,
+        system_program,
+    } = IndexReviewsAccounts::try_accounts(program_id, account_info_iter, page)?;
+
+    if index_account.owner != program_id {
+        create_pda_account(
+            reviewer,
+            index_account,
+            system_program,
+            ReviewIndexState::SIZE,
+            program_id,
+            &[
+                b"review_index",
+                reviewer.key.as_ref(),
+                &page.to_le_bytes(),
+                &[index_account_bump],
+            ],
+        )?;
+        debug_msg!("Review index page created: {}", index_account.key);
+
+        let index_data = ReviewIndexState {
+            discriminator: ReviewIndexState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            reviewer: *reviewer.key,
+            page,
+            reviews: Vec::new(),
+        };
+        index_data.serialize(&mut &mut index_account.data.borrow_mut()[..])?;
+    }
+
+    let mut index_data = ReviewIndexAccount::load_mut(program_id, index_account)?;
+    for review_account in account_info_iter {
+        load_own_review(program_id, reviewer, review_account)?;
+        if index_data.reviews.contains(review_account.key) {
+            debug_msg!("Review {} is already indexed", review_account.key);
+            continue;
+        }
+        if index_data.reviews.len() >= REVIEW_INDEX_PAGE_LEN {
+            msg!(
+                "Review index page holds at most {} reviews",
+                REVIEW_INDEX_PAGE_LEN
+            );
+            return Err(ReviewError::ReviewIndexPageFull.into());
+        }
+        index_data.reviews.push(*review_account.key);
+    }
+    index_data.save()
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+struct ExportMyReviewsAccounts<'a, 'info> {
+    reviewer: &'a AccountInfo<'info>,
+    #[account(pda = [b"review_index", reviewer, page.to_le_bytes()])]
+    index_account: &'a AccountInfo<'info>,
+}
+
+/// Sets the return data to a `Vec<ReviewRecord>` of the reviews on a page of
+/// `reviewer`'s review index, which must follow the fixed accounts in index
+/// order. Reviews deleted since they were indexed are left out. Meant to be
+/// simulated; it changes nothing.
+pub fn export_my_reviews(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    page: u32,
+) -> ProgramResult {
+    debug_msg!("Exporting review index page {}", page);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ExportMyReviewsAccounts {
+        reviewer,
+        index_account,
+    } = ExportMyReviewsAccounts::try_accounts(program_id, account_info_iter, page)?;
+
+    let index_data = ReviewIndexAccount::load(program_id, index_account)?;
+    let mut records = Vec::with_capacity(index_data.reviews.len());
+    for review in &index_data.reviews {
+        let review_account = next_account_info(account_info_iter)?;
+        if review_account.key != review {
+            msg!("Expected indexed review {}", review);
+            return Err(ReviewError::IncorrectAccountError.into());
+        }
+        if review_account.owner != program_id || state::is_closed(&review_account.data.borrow()) {
+            continue;
+        }
+        let review_data = load_own_review(program_id, reviewer, review_account)?;
+        records.push(ReviewRecord {
+            title_hash: state::title_hash(&review_data.title),
+            rating: review_data.rating_tenths(),
+            created_at: review_data.created_at,
+        });
+    }
+
+    set_return_data(&records.try_to_vec()?);
+
+    Ok(())
+}
+
+/// Loads an initialized review, checking it lives at the address derived from
+/// its own kind, reviewer and title.
+fn load_review(
+    program_id: &Pubkey,
+    review_account: &AccountInfo,
+) -> Result<MovieAccountState, ProgramError> {
+    let review_data = ReviewAccount::load(program_id, review_account)?.into_inner();
+
+    if review_data.address(program_id) != Some(*review_account.key) {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    Ok(review_data)
+}
+
+/// Loads a review and checks that it belongs to `reviewer`.
+fn load_own_review(
+    program_id: &Pubkey,
+    reviewer: &AccountInfo,
+    review_account: &AccountInfo,
+) -> Result<MovieAccountState, ProgramError> {
+    let review_data = load_review(program_id, review_account)?;
+    if review_data.reviewer != *reviewer.key {
+        msg!("Review does not belong to signer");
+        return Err(ReviewError::UnauthorizedReviewer.into());
+    }
+
+    Ok(review_data)
+}
+
+/// Loads the config, returning `None` until it has been initialized.
+fn load_config(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<Option<ConfigState>, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if config_account.owner != program_id {
+        return Ok(None);
+    }
+
+    let config_data = load_account::<ConfigState>(config_account)?;
+    Ok(config_data.is_initialized().then_some(config_data))
+}
+
+/// Loads the config for an admin-only instruction, checking `admin` signed.
+fn load_config_as_admin(
+    program_id: &Pubkey,
+    admin: &AccountInfo,
+    config_account: &AccountInfo,
+) -> Result<ConfigState, ProgramError> {
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config_data = load_config(program_id, config_account)?.ok_or_else(|| {
+        msg!("Config is not initialized");
+        ProgramError::from(ReviewError::UninitializedAccount)
+    })?;
+
+    if config_data.admin != *admin.key {
+        msg!("Signer is not the config admin");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    Ok(config_data)
+}
+
+pub fn set_premium_content(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    scheme: EncryptionScheme,
+    plaintext_hash: [u8; 32],
+) -> ProgramResult {
+    debug_msg!("Setting premium content...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+    let premium_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    load_own_review(program_id, reviewer, review_account)?;
+
+    let (premium_pda, premium_bump) =
+        Pubkey::find_program_address(&[b"premium", review_account.key.as_ref()], program_id);
+    if premium_pda != *premium_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if premium_account.owner != program_id {
+        create_pda_account(
+            reviewer,
+            premium_account,
+            system_program,
+            PremiumContentState::SIZE,
+            program_id,
+            &[b"premium", review_account.key.as_ref(), &[premium_bump]],
+        )?;
+        debug_msg!("Premium content created: {}", premium_pda);
+    }
+
+    let premium_data = PremiumContentState {
+        discriminator: PremiumContentState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        review: *review_account.key,
+        scheme,
+        plaintext_hash,
+    };
+    premium_data.serialize(&mut &mut premium_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn grant_access_key_hint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key_hint: Vec<u8>,
+) -> ProgramResult {
+    debug_msg!("Granting access key hint...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+    let premium_account = next_account_info(account_info_iter)?;
+    let subscriber = next_account_info(account_info_iter)?;
+    let subscription_account = next_account_info(account_info_iter)?;
+    let key_hint_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if key_hint.len() > MAX_KEY_HINT_LEN {
+        msg!("Key hint is longer than {} bytes", MAX_KEY_HINT_LEN);
+        return Err(ReviewError::InvalidDataLength.into());
+    }
+
+    load_own_review(program_id, reviewer, review_account)?;
+
+    let (premium_pda, _premium_bump) =
+        Pubkey::find_program_address(&[b"premium", review_account.key.as_ref()], program_id);
+    if premium_pda != *premium_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if premium_account.owner != program_id
+        || !load_account::<PremiumContentState>(premium_account)?.is_initialized()
+    {
+        msg!("Review is not premium content");
+        return Err(ReviewError::NotPremiumContent.into());
+    }
+
+    require_subscription(
+        program_id,
+        subscription_account,
+        subscriber.key,
+        reviewer.key,
+    )?;
+
+    let (key_hint_pda, key_hint_bump) = Pubkey::find_program_address(
+        &[
+            b"key_hint",
+            review_account.key.as_ref(),
+            subscriber.key.as_ref(),
+        ],
+        program_id,
+    );
+    if key_hint_pda != *key_hint_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if key_hint_account.owner != program_id {
+        create_pda_account(
+            reviewer,
+            key_hint_account,
+            system_program,
+            AccessKeyHintState::SIZE,
+            program_id,
+            &[
+                b"key_hint",
+                review_account.key.as_ref(),
+                subscriber.key.as_ref(),
+                &[key_hint_bump],
+            ],
+        )?;
+        debug_msg!("Key hint created: {}", key_hint_pda);
+    }
+
+    let key_hint_data = AccessKeyHintState {
+        discriminator: AccessKeyHintState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        review: *review_account.key,
+        subscriber: *subscriber.key,
+        key_hint,
+    };
+    key_hint_data.serialize(&mut &mut key_hint_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_visibility(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    visibility: Visibility,
+) -> ProgramResult {
+    debug_msg!("Setting visibility to {:?}...", visibility);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut review_data = load_own_review(program_id, reviewer, review_account)?;
+    let counted_before = review_data.counted_rating();
+    review_data.visibility = visibility;
+    recount_review(
+        program_id,
+        accounts,
+        &review_data,
+        counted_before,
+        review_data.counted_rating(),
+    )?;
+
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn grant_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    member: Pubkey,
+) -> ProgramResult {
+    debug_msg!("Granting access to {}...", member);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+    let acl_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    load_own_review(program_id, reviewer, review_account)?;
+
+    let (acl_pda, acl_bump) =
+        Pubkey::find_program_address(&[b"acl", review_account.key.as_ref()], program_id);
+    if acl_pda != *acl_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let mut acl_data = if acl_account.owner == program_id {
+        load_account::<AclState>(acl_account)?
+    } else {
+        create_pda_account(
+            reviewer,
+            acl_account,
+            system_program,
+            AclState::SIZE,
+            program_id,
+            &[b"acl", review_account.key.as_ref(), &[acl_bump]],
+        )?;
+        debug_msg!("ACL created: {}", acl_pda);
+
+        AclState {
+            discriminator: AclState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            review: *review_account.key,
+            members: Vec::new(),
+        }
+    };
+
+    if !acl_data.contains(&member) {
+        if acl_data.members.len() >= MAX_ACL_MEMBERS {
+            msg!("ACL already holds {} members", MAX_ACL_MEMBERS);
+            return Err(ReviewError::AclFull.into());
+        }
+        acl_data.members.push(member);
+    }
+
+    acl_data.serialize(&mut &mut acl_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn revoke_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    member: Pubkey,
+) -> ProgramResult {
+    debug_msg!("Revoking access from {}...", member);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+    let acl_account = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    load_own_review(program_id, reviewer, review_account)?;
+
+    let (acl_pda, _acl_bump) =
+        Pubkey::find_program_address(&[b"acl", review_account.key.as_ref()], program_id);
+    if acl_pda != *acl_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if acl_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut acl_data = load_account::<AclState>(acl_account)?;
+    acl_data.members.retain(|existing| *existing != member);
+
+    acl_data.serialize(&mut &mut acl_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Makes a draft review live. Published reviews count towards indexes,
+/// aggregates and rewards from `published_at` onwards.
+///
+/// The reviewer can publish at any time. Once a scheduled draft's
+/// `publish_at` has passed anyone can publish it, so keepers can release
+/// embargoed reviews on time. Whoever publishes, the publication reward
+/// accrues to the reviewer's profile, never the caller's; a keeper only pays
+/// to create the profile if the reviewer has none.
+pub fn publish_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Publishing review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+    // Found by address when the review is counted in, but taken here so the
+    // reward accounts follow it.
+    let _summary_account = next_account_info(account_info_iter)?;
+
+    if !caller.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut review_data = load_review(program_id, review_account)?;
+    if review_data.status == ReviewStatus::Published {
+        msg!("Review is already published");
+        return Err(ReviewError::AlreadyPublished.into());
+    }
+    require_not_archived(&review_data)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    if review_data.is_expired(now) {
+        msg!("Review has expired");
+        return Err(ReviewError::ReviewExpired.into());
+    }
+
+    if *caller.key != review_data.reviewer {
+        match review_data.publish_at {
+            Some(publish_at) if now >= publish_at => {}
+            Some(publish_at) => {
+                msg!("Review is embargoed until {}", publish_at);
+                return Err(ReviewError::PublishTimeNotReached.into());
+            }
+            None => {
+                msg!("Only the reviewer can publish an unscheduled draft");
+                return Err(ReviewError::UnauthorizedReviewer.into());
+            }
+        }
+    }
+
+    let counted_before = review_data.counted_rating();
+    review_data.status = ReviewStatus::Published;
+    review_data.published_at = now;
+    review_data.publish_at = None;
+    debug_msg!("Published at: {}", review_data.published_at);
+    recount_review(
+        program_id,
+        accounts,
+        &review_data,
+        counted_before,
+        review_data.counted_rating(),
+    )?;
+
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    // The caller may pass the config and the reviewer's profile to accrue
+    // the publication reward, which goes to the reviewer whoever publishes.
+    if let Some(system_program) = account_info_iter.next() {
+        let config_account = next_account_info(account_info_iter)?;
+        let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+        if config_pda != *config_account.key {
+            msg!("Invalid seeds for config PDA");
+            return Err(ReviewError::InvalidPDA.into());
+        }
+        reward_review(
+            program_id,
+            config_account,
+            caller,
+            &review_data.reviewer,
+            system_program,
+            account_info_iter,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Sets or clears the time after which anyone may publish a draft.
+pub fn schedule_publish(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    publish_at: Option<i64>,
+) -> ProgramResult {
+    debug_msg!("Scheduling publication at {:?}...", publish_at);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut review_data = load_own_review(program_id, reviewer, review_account)?;
+    if review_data.status == ReviewStatus::Published {
+        msg!("Review is already published");
+        return Err(ReviewError::AlreadyPublished.into());
+    }
+
+    review_data.publish_at = publish_at;
+
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Sets or clears the time after which anyone may expire a review.
+pub fn set_review_expiry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    valid_until: Option<i64>,
+) -> ProgramResult {
+    debug_msg!("Setting review expiry at {:?}...", valid_until);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reviewer = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut review_data = load_own_review(program_id, reviewer, review_account)?;
+    if review_data.status == ReviewStatus::Expired {
+        msg!("Review has expired");
+        return Err(ReviewError::ReviewExpired.into());
+    }
+
+    review_data.valid_until = valid_until;
+
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct ExpireReviewAccounts<'a, 'info> {
+    #[account(mut)]
+    review_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    reviewer: &'a AccountInfo<'info>,
+}
+
+/// Permissionless once a review's `valid_until` has passed: flips it to
+/// `Expired`, or with `purge` closes it and refunds its rent to the reviewer.
+/// An already expired review can still be purged.
+pub fn expire_review(program_id: &Pubkey, accounts: &[AccountInfo], purge: bool) -> ProgramResult {
+    debug_msg!("Expiring review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ExpireReviewAccounts {
+        review_account,
+        reviewer,
+    } = ExpireReviewAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut review_data = load_review(program_id, review_account)?;
+    if review_data.reviewer != *reviewer.key {
+        msg!("Incorrect reviewer account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+    require_not_archived(&review_data)?;
+
+    if !review_data.is_expired(Clock::get()?.unix_timestamp) {
+        msg!("Review is valid until {:?}", review_data.valid_until);
+        return Err(ReviewError::ReviewNotExpired.into());
+    }
+
+    let counted_before = review_data.counted_rating();
+    if purge {
+        recount_review(program_id, accounts, &review_data, counted_before, None)?;
+        debug_msg!("Purging review: {}", review_data.title);
+        return close_account(review_account, reviewer, false);
+    }
+
+    if review_data.status == ReviewStatus::Expired {
+        msg!("Review has already expired");
+        return Err(ReviewError::ReviewExpired.into());
+    }
+
+    review_data.status = ReviewStatus::Expired;
+    recount_review(program_id, accounts, &review_data, counted_before, None)?;
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct ArchiveReviewAccounts<'a, 'info> {
+    #[account(signer)]
+    admin: &'a AccountInfo<'info>,
+    config_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    review_account: &'a AccountInfo<'info>,
+}
+
+/// Config admin only: archives a review, keeping its data on-chain.
+pub fn archive_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Archiving review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ArchiveReviewAccounts {
+        admin,
+        config_account,
+        review_account,
+    } = ArchiveReviewAccounts::try_accounts(program_id, account_info_iter)?;
+
+    load_config_as_admin(program_id, admin, config_account)?;
+
+    let mut review_data = load_review(program_id, review_account)?;
+    require_not_archived(&review_data)?;
+
+    debug_msg!("Archiving review: {}", review_data.title);
+    let counted_before = review_data.counted_rating();
+    review_data.status = ReviewStatus::Archived;
+    recount_review(program_id, accounts, &review_data, counted_before, None)?;
+    review_data.serialize(&mut &mut review_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Fails if the config admin archived `review`.
+fn require_not_archived(review: &MovieAccountState) -> ProgramResult {
+    if review.status == ReviewStatus::Archived {
+        msg!("Review has been archived");
+        return Err(ReviewError::ReviewArchived.into());
+    }
+    Ok(())
+}
+
+pub fn attest_watched(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    kind: EntityKind,
+    movie_id: String,
+    watched_at: i64,
+) -> ProgramResult {
+    debug_msg!("Attesting watched: {}", movie_id);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let viewer = next_account_info(account_info_iter)?;
+    let attestation_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !viewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if movie_id.len() > MAX_TITLE_LEN {
+        msg!("Title is longer than {} bytes", MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+
+    if watched_at > Clock::get()?.unix_timestamp {
+        msg!("Cannot attest to watching in the future");
+        return Err(ReviewError::InvalidTimestamp.into());
+    }
+
+    let (attestation_pda, attestation_bump) =
+        state::derive_watch_attestation_pda(program_id, kind, viewer.key, &movie_id);
+    if attestation_pda != *attestation_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if attestation_account.owner != program_id {
+        create_pda_account(
+            viewer,
+            attestation_account,
+            system_program,
+            WatchAttestation::get_account_size(&movie_id)?,
+            program_id,
+            &[
+                b"watched",
+                kind.seed(),
+                viewer.key.as_ref(),
+                &state::review_title_seed(&movie_id),
+                &[attestation_bump],
+            ],
+        )?;
+        debug_msg!("Attestation created: {}", attestation_pda);
+    }
+
+    let attestation_data = WatchAttestation {
+        discriminator: WatchAttestation::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        viewer: *viewer.key,
+        movie_id,
+        watched_at,
+    };
+    attestation_data.serialize(&mut &mut attestation_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Fails with `WatchAttestationRequired` unless `attestation_account` holds
+/// `viewer`'s attestation for the `kind` entity `movie_id`.
+pub fn require_watch_attestation(
+    program_id: &Pubkey,
+    attestation_account: &AccountInfo,
+    viewer: &Pubkey,
+    kind: EntityKind,
+    movie_id: &str,
+) -> ProgramResult {
+    let (attestation_pda, _bump_seed) =
+        state::derive_watch_attestation_pda(program_id, kind, viewer, movie_id);
+    if attestation_pda != *attestation_account.key {
+        msg!("Invalid seeds for attestation PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if attestation_account.owner != program_id
+        || !load_account::<WatchAttestation>(attestation_account)?.is_initialized()
+    {
+        msg!("No watch attestation found");
+        return Err(ReviewError::WatchAttestationRequired.into());
+    }
+
+    Ok(())
+}
+
+pub fn set_watch_requirement(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    required: bool,
+) -> ProgramResult {
+    debug_msg!("Setting watch requirement: {}", required);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.require_watch_attestation = required;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_comment_limit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_comments_per_review: u64,
+) -> ProgramResult {
+    debug_msg!("Setting comment limit: {}", max_comments_per_review);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.max_comments_per_review = max_comments_per_review;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Creates the summary of the `kind` entity `title` on first use, paid for by
+/// `payer`. Returns the current summary contents.
+fn open_summary<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    summary_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    kind: EntityKind,
+    title: &str,
+) -> Result<MovieSummary, ProgramError> {
+    let (summary_pda, summary_bump) = state::derive_summary_pda(program_id, kind, title);
+    if summary_pda != *summary_account.key {
+        msg!("Invalid seeds for summary PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if summary_account.owner == program_id {
+        return Ok(SummaryAccount::load(program_id, summary_account)?.into_inner());
+    }
+
+    let title_hash = state::title_hash(title);
+    create_pda_account(
+        payer,
+        summary_account,
+        system_program,
+        MovieSummary::SIZE,
+        program_id,
+        &[b"summary", kind.seed(), &title_hash, &[summary_bump]],
+    )?;
+    debug_msg!("Summary created: {}", summary_pda);
+
+    Ok(MovieSummary {
+        discriminator: MovieSummary::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        kind,
+        title_hash,
+        review_count: 0,
+        rating_sum: 0,
+        rating_histogram: [0; 5],
+        versus_wins: 0,
+        versus_losses: 0,
+    })
+}
+
+/// The summary of `review_data`'s title among `accounts`, wherever it sits.
+fn find_summary_account<'a, 'info>(
+    program_id: &Pubkey,
+    review_data: &MovieAccountState,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a AccountInfo<'info>, ProgramError> {
+    let (summary_pda, _summary_bump) =
+        state::derive_summary_pda(program_id, review_data.kind, &review_data.title);
+    accounts
+        .iter()
+        .find(|account| *account.key == summary_pda)
+        .ok_or_else(|| {
+            msg!("Missing summary account");
+            ProgramError::NotEnoughAccountKeys
+        })
+}
+
+/// Moves `review_data` in its title's summary from the rating counted for it
+/// before a change, if any, to the one counted after, if any. The summary is
+/// only looked for when the two differ.
+fn recount_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    review_data: &MovieAccountState,
+    counted_before: Option<u8>,
+    counted_after: Option<u8>,
+) -> ProgramResult {
+    if counted_before == counted_after {
+        return Ok(());
+    }
+
+    let summary_account = find_summary_account(program_id, review_data, accounts)?;
+    let mut summary_data = SummaryAccount::load_mut(program_id, summary_account)?;
+    if let Some(rating) = counted_before {
+        summary_data.review_count = summary_data
+            .review_count
+            .checked_sub(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        summary_data.rating_sum = summary_data
+            .rating_sum
+            .checked_sub(rating.into())
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        let stars = &mut summary_data.rating_histogram[MovieSummary::histogram_bucket(rating)];
+        *stars = stars
+            .checked_sub(1)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+    if let Some(rating) = counted_after {
+        count_in_summary(&mut summary_data, rating)?;
+    }
+    summary_data.save()
+}
+
+/// Adds a review rated `rating` to `summary_data`'s totals.
+fn count_in_summary(summary_data: &mut MovieSummary, rating: u8) -> ProgramResult {
+    summary_data.review_count = summary_data
+        .review_count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    summary_data.rating_sum = summary_data
+        .rating_sum
+        .checked_add(rating.into())
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    let stars = &mut summary_data.rating_histogram[MovieSummary::histogram_bucket(rating)];
+    *stars = stars
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct SnapshotAggregateAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    payer: &'a AccountInfo<'info>,
+    config_account: &'a AccountInfo<'info>,
+    summary_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    snapshot_account: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Copies a title's summary into its snapshot for the current epoch, paid for
+/// by `payer`. Anyone can send it; the epoch's snapshot already existing
+/// rejects a second one.
+pub fn snapshot_aggregate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Snapshotting summary...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let SnapshotAggregateAccounts {
+        payer,
+        config_account,
+        summary_account,
+        snapshot_account,
+        system_program,
+    } = SnapshotAggregateAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let Some(epoch) = load_config(program_id, config_account)?
+        .and_then(|config_data| config_data.snapshot_epoch(now))
+    else {
+        msg!("Snapshot interval is not set");
+        return Err(ReviewError::SnapshotsNotConfigured.into());
+    };
+
+    let summary_data = SummaryAccount::load(program_id, summary_account)?;
+    let (snapshot_pda, snapshot_bump) =
+        state::derive_snapshot_pda(program_id, summary_account.key, epoch);
+    if snapshot_pda != *snapshot_account.key {
+        msg!("Invalid seeds for snapshot PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        payer,
+        snapshot_account,
+        system_program,
+        AggregateSnapshot::SIZE,
+        program_id,
+        &[
+            b"agg_hist",
+            summary_account.key.as_ref(),
+            &epoch.to_le_bytes(),
+            &[snapshot_bump],
+        ],
+    )?;
+    debug_msg!("Snapshot created: {}", snapshot_pda);
+
+    let snapshot_data = AggregateSnapshot {
+        discriminator: AggregateSnapshot::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        summary: *summary_account.key,
+        epoch,
+        taken_at: now,
+        review_count: summary_data.review_count,
+        rating_sum: summary_data.rating_sum,
+        rating_histogram: summary_data.rating_histogram,
+    };
+    snapshot_data.serialize(&mut &mut snapshot_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Creates the `[review, b"comment"]` counter on first use, paid for by
+/// `payer`. Returns the current counter contents.
+fn open_comment_counter<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    review_account: &AccountInfo<'a>,
+    counter_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<MovieCommentCounter, ProgramError> {
+    let (counter_pda, counter_bump) =
+        Pubkey::find_program_address(&[review_account.key.as_ref(), b"comment"], program_id);
+    if counter_pda != *counter_account.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if counter_account.owner == program_id {
+        return load_account::<MovieCommentCounter>(counter_account);
+    }
+
+    create_pda_account(
+        payer,
+        counter_account,
+        system_program,
+        MovieCommentCounter::SIZE,
+        program_id,
+        &[review_account.key.as_ref(), b"comment", &[counter_bump]],
+    )?;
+    debug_msg!("Comment counter created: {}", counter_pda);
+
+    Ok(MovieCommentCounter {
+        discriminator: MovieCommentCounter::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        counter: 0,
+        uncapped: false,
+    })
+}
+
+/// Creates the `[b"profile", owner]` account on first use, paid for by
+/// `payer`. Returns the current profile contents.
+fn open_profile<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    profile_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    owner: &Pubkey,
+) -> Result<ReviewerProfile, ProgramError> {
+    let (profile_pda, profile_bump) =
+        Pubkey::find_program_address(&[b"profile", owner.as_ref()], program_id);
+    if profile_pda != *profile_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if profile_account.owner == program_id {
+        let profile_data = load_account::<ReviewerProfile>(profile_account)?;
+        if !profile_data.is_initialized() {
+            msg!("Account is not initialized");
+            return Err(ReviewError::UninitializedAccount.into());
+        }
+        return Ok(profile_data);
+    }
+
+    create_pda_account(
+        payer,
+        profile_account,
+        system_program,
+        ReviewerProfile::SIZE,
+        program_id,
+        &[b"profile", owner.as_ref(), &[profile_bump]],
+    )?;
+    debug_msg!("Profile created: {}", profile_pda);
+
+    Ok(ReviewerProfile {
+        discriminator: ReviewerProfile::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        owner: *owner,
+        comments_created: 0,
+        handle: String::new(),
+        pending_rewards: 0,
+        rewards_claimed: 0,
+        reviews_published: 0,
+        referred_by: None,
+    })
+}
+
+#[derive(Accounts)]
+struct InitializeReviewerProfileAccounts<'a, 'info> {
+    #[account(signer)]
+    owner: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"profile", owner])]
+    profile_account: &'a AccountInfo<'info>,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Creates the signer's profile up front rather than on their first review,
+/// comment or handle.
+pub fn initialize_reviewer_profile(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Initializing reviewer profile...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let InitializeReviewerProfileAccounts {
+        owner,
+        profile_account,
+        system_program,
+    } = InitializeReviewerProfileAccounts::try_accounts(program_id, account_info_iter)?;
+
+    if profile_account.owner == program_id {
+        msg!("Profile already exists");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let profile_data = open_profile(
+        program_id,
+        owner,
+        profile_account,
+        system_program,
+        owner.key,
+    )?;
+    profile_data.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// `InitializeReviewerProfile` that succeeds without changes when the owner
+/// already has a profile.
+pub fn initialize_reviewer_profile_idempotent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let InitializeReviewerProfileAccounts {
+        owner,
+        profile_account,
+        system_program,
+    } = InitializeReviewerProfileAccounts::try_accounts(program_id, account_info_iter)?;
+
+    if profile_account.owner == program_id {
+        if !load_account::<ReviewerProfile>(profile_account)?.is_initialized() {
+            msg!("Account is not initialized");
+            return Err(ReviewError::UninitializedAccount.into());
+        }
+        debug_msg!("Profile already exists");
+        return Ok(());
+    }
+
+    let profile_data = open_profile(
+        program_id,
+        owner,
+        profile_account,
+        system_program,
+        owner.key,
+    )?;
+    profile_data.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(handle: &str)]
+struct RegisterHandleAccounts<'a, 'info> {
+    #[account(signer)]
+    owner: &'a AccountInfo<'info>,
+    #[account(mut)]
+    profile_account: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"handle", handle], bump)]
+    handle_account: &'a AccountInfo<'info>,
+    handle_account_bump: u8,
+    #[account(address = system_program::ID)]
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Claims `handle` for the signer, recording it in their profile. Each owner
+/// holds at most one handle and must release it before registering another.
+pub fn register_handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    handle: String,
+) -> ProgramResult {
+    debug_msg!("Registering handle: {}", handle);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let handle = mention::normalize_handle(&handle)?;
+    let RegisterHandleAccounts {
+        owner,
+        profile_account,
+        handle_account,
+        handle_account_bump,
+        system_program,
+    } = RegisterHandleAccounts::try_accounts(program_id, account_info_iter, &handle)?;
+
+    if handle_account.owner == program_id {
+        msg!("Handle {} is already registered", handle);
+        return Err(ReviewError::HandleTaken.into());
+    }
+
+    let mut profile_data = open_profile(
+        program_id,
+        owner,
+        profile_account,
+        system_program,
+        owner.key,
+    )?;
+    if !profile_data.handle.is_empty() {
+        msg!("Profile already has handle {}", profile_data.handle);
+        return Err(ReviewError::HandleAlreadySet.into());
+    }
+
+    create_pda_account(
+        owner,
+        handle_account,
+        system_program,
+        HandleState::get_account_size(&handle)?,
+        program_id,
+        &[b"handle", handle.as_bytes(), &[handle_account_bump]],
+    )?;
+
+    let handle_data = HandleState {
+        discriminator: HandleState::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        handle: handle.clone(),
+        owner: *owner.key,
+    };
+    handle_data.serialize(&mut &mut handle_account.data.borrow_mut()[..])?;
+
+    profile_data.handle = handle;
+    profile_data.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct ReleaseHandleAccounts<'a, 'info> {
+    #[account(signer)]
+    owner: &'a AccountInfo<'info>,
+    #[account(mut, pda = [b"profile", owner])]
+    profile_account: &'a AccountInfo<'info>,
+    #[account(mut)]
+    handle_account: &'a AccountInfo<'info>,
+}
+
+/// Gives up the signer's handle, refunding its rent and making it available
+/// to register again.
+pub fn release_handle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Releasing handle...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let ReleaseHandleAccounts {
+        owner,
+        profile_account,
+        handle_account,
+    } = ReleaseHandleAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let mut profile_data = ProfileAccount::load_mut(program_id, profile_account)?;
+    let handle_data = HandleAccount::load(program_id, handle_account)?;
+
+    let (handle_pda, _bump_seed) =
+        Pubkey::find_program_address(&[b"handle", handle_data.handle.as_bytes()], program_id);
+    if handle_pda != *handle_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if handle_data.owner != *owner.key || profile_data.handle != handle_data.handle {
+        msg!("Handle does not belong to signer");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    close_account(handle_account, owner, true)?;
+
+    profile_data.handle = String::new();
+    profile_data.save()?;
+
+    Ok(())
+}
+
+/// Exempts a review's comment thread from `max_comments_per_review`, or puts
+/// it back under the cap.
+pub fn set_comment_cap_override(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    uncapped: bool,
+) -> ProgramResult {
+    debug_msg!("Setting comment cap override: {}", uncapped);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+    let counter_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    load_config_as_admin(program_id, admin, config_account)?;
+    load_review(program_id, review_account)?;
+
+    let mut counter_data = open_comment_counter(
+        program_id,
+        admin,
+        review_account,
+        counter_account,
+        system_program,
+    )?;
+    counter_data.uncapped = uncapped;
+
+    counter_data.serialize(&mut &mut counter_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Adds a comment to a review and accrues the config's
+/// `comment_reward_amount` to the commenter's profile. The first comment
+/// creates the review's `[review, b"comment"]` counter, so the first
+/// commenter pays its rent rather than the reviewer. Like referral bonuses,
+/// comment rewards are minted by `ClaimRewards`, which checks the claimer's
+/// associated token account and signs with the `token_auth` PDA, so
+/// commenting needs no token accounts.
+pub fn add_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    comment: String,
+) -> ProgramResult {
+    debug_msg!("Adding comment...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commenter = next_account_info(account_info_iter)?;
+    let review_account = next_account_info(account_info_iter)?;
+    let counter_account = next_account_info(account_info_iter)?;
+    let comment_account = next_account_info(account_info_iter)?;
+    let profile_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    if !commenter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    require_not_empty("Comment", &comment)?;
+    if comment.len() > MAX_COMMENT_LEN {
+        msg!("Comment is longer than {} bytes", MAX_COMMENT_LEN);
+        return Err(ReviewError::CommentTooLong.into());
+    }
+
+    let review_data = load_review(program_id, review_account)?;
+    require_not_archived(&review_data)?;
+    let mut profile_data = open_profile(
+        program_id,
+        commenter,
+        profile_account,
+        system_program,
+        commenter.key,
+    )?;
+    let mut counter_data = open_comment_counter(
+        program_id,
+        commenter,
+        review_account,
+        counter_account,
+        system_program,
+    )?;
+
+    let config_data = load_config(program_id, config_account)?;
+    if let Some(config_data) = &config_data {
+        let limit = config_data.max_comments_per_review;
+        if limit > 0 && !counter_data.uncapped && counter_data.counter >= limit {
+            msg!("Review already has {} comments", counter_data.counter);
+            return Err(ReviewError::CommentLimitReached.into());
+        }
+    }
+
+    let count = counter_data.counter;
+    let (comment_pda, comment_bump) = Pubkey::find_program_address(
+        &[review_account.key.as_ref(), count.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if comment_pda != *comment_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        commenter,
+        comment_account,
+        system_program,
+        MovieComment::get_account_size(comment.clone())?,
+        program_id,
+        &[
+            review_account.key.as_ref(),
+            count.to_be_bytes().as_ref(),
+            &[comment_bump],
+        ],
+    )?;
+    debug_msg!("Created comment account: {}", comment_pda);
+
+    let comment_data = MovieComment {
+        discriminator: MovieComment::DISCRIMINATOR.to_string(),
+        version: state::STATE_VERSION,
+        is_initialized: true,
+        review: *review_account.key,
+        commenter: *commenter.key,
+        comment,
+        count,
+        created_at: Clock::get()?.unix_timestamp,
+        commenter_profile: *profile_account.key,
+    };
+    comment_data.serialize(&mut &mut comment_account.data.borrow_mut()[..])?;
+    Event::CommentAdded(CommentAdded {
+        review: *review_account.key,
+        comment_account: *comment_account.key,
+        commenter: *commenter.key,
+        count,
+        comment: comment_data.comment.clone(),
+    })
+    .emit()?;
+
+    profile_data.comments_created = profile_data
+        .comments_created
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    let reward = config_data.map_or(0, |config| config.comment_reward_amount);
+    if reward > 0 {
+        debug_msg!("Reward: {} tokens to {}", reward, commenter.key);
+        profile_data.pending_rewards = profile_data
+            .pending_rewards
+            .checked_add(reward)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+    profile_data.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    counter_data.counter = count
+        .checked_add(1)
+        .ok_or(ReviewError::ArithmeticOverflow)?;
+    debug_msg!("Comment count: {}", counter_data.counter);
+    counter_data.serialize(&mut &mut counter_account.data.borrow_mut()[..])?;
+
+    // Any remaining accounts are inboxes and handles in ascending key order,
+    // matched by address.
+    let remaining_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+    require_ascending_keys(&remaining_accounts)?;
+    let find_remaining = |seeds: &[&[u8]]| {
+        let (pda, _bump_seed) = Pubkey::find_program_address(seeds, program_id);
+        remaining_accounts
+            .iter()
+            .find(|account| *account.key == pda)
+            .copied()
+    };
+    let find_inbox = |owner: &Pubkey| find_remaining(&[b"inbox", owner.as_ref()]);
+
+    // Let the reviewer know about the reply when their inbox is passed.
+    if review_data.reviewer != *commenter.key {
+        if let Some(inbox_account) = find_inbox(&review_data.reviewer) {
+            notify(
+                program_id,
+                commenter,
+                inbox_account,
+                system_program,
+                &review_data.reviewer,
+                NotificationKind::Reply,
+                comment_account.key,
+            )?;
+        }
+    }
+
+    // Every mentioned user must be notified, so their inboxes are required.
+    // Handles only count as mentions when their account is passed and
+    // registered; otherwise they are plain text.
+    let mut mentioned_users: Vec<Pubkey> = Vec::new();
+    for mention in mention::parse_mentions(&comment_data.comment) {
+        let mentioned = match mention {
+            Mention::Key(key) => key,
+            Mention::Handle(handle) => match find_remaining(&[b"handle", handle.as_bytes()]) {
+                Some(handle_account) if handle_account.owner == program_id => {
+                    load_account::<HandleState>(handle_account)?.owner
+                }
+                _ => continue,
+            },
+        };
+        if mentioned == *commenter.key || mentioned_users.contains(&mentioned) {
+            continue;
+        }
+        mentioned_users.push(mentioned);
+        let Some(inbox_account) = find_inbox(&mentioned) else {
+            msg!("Missing inbox for mentioned user {}", mentioned);
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        notify(
+            program_id,
+            commenter,
+            inbox_account,
+            system_program,
+            &mentioned,
+            NotificationKind::Mention,
+            comment_account.key,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Config admin only: sets the tokens each new comment accrues to its author.
+/// Zero disables comment rewards.
+pub fn set_comment_reward(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    debug_msg!("Setting comment reward: {}", amount);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.comment_reward_amount = amount;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_comment_edit_window(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    comment_edit_window_secs: u64,
+) -> ProgramResult {
+    debug_msg!("Setting comment edit window: {}", comment_edit_window_secs);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config_as_admin(program_id, admin, config_account)?;
+    config_data.comment_edit_window_secs = comment_edit_window_secs;
+
+    config_data.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Resizes a program-owned account to `new_len`, topping its rent up from
+/// `payer` or refunding the excess to it.
+fn resize_account<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_len: usize,
+) -> ProgramResult {
+    let rent_exempt = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+
+    if rent_exempt <= lamports {
+        return shrink_account(account, payer, new_len);
+    }
+
+    invoke(
+        &system_instruction::transfer(
+            payer.key,
+            account.key,
+            rent_exempt
+                .checked_sub(lamports)
+                .ok_or(ReviewError::ArithmeticOverflow)?,
+        ),
+        &[payer.clone(), account.clone(), system_program.clone()],
+    )?;
+    account.realloc(new_len, false)
+}
+
+/// Reallocates `account` to `new_len` bytes it can already pay rent for,
+/// refunding any lamports above the new minimum balance to `recipient`.
+fn shrink_account(account: &AccountInfo, recipient: &AccountInfo, new_len: usize) -> ProgramResult {
+    let rent_exempt = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+
+    if lamports > rent_exempt {
+        let excess = lamports
+            .checked_sub(rent_exempt)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+        **account.try_borrow_mut_lamports()? = rent_exempt;
+        **recipient.try_borrow_mut_lamports()? = recipient
+            .lamports()
+            .checked_add(excess)
+            .ok_or(ReviewError::ArithmeticOverflow)?;
+    }
+
+    account.realloc(new_len, false)
+}
+
+pub fn update_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    comment: String,
+) -> ProgramResult {
+    debug_msg!("Updating comment...");
+    debug_msg!("Comment: {}", comment);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commenter = next_account_info(account_info_iter)?;
+    let comment_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    if !commenter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *system_program.key != system_program::ID {
+        msg!("Incorrect system program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut comment_data = CommentAccount::load_mut(program_id, comment_account)?;
+
+    require_not_empty("Comment", &comment)?;
+    if comment.len() > MAX_COMMENT_LEN {
+        msg!("Comment is longer than {} bytes", MAX_COMMENT_LEN);
+        return Err(ReviewError::CommentTooLong.into());
+    }
+
+    let (comment_pda, _bump_seed) = Pubkey::find_program_address(
+        &[
+            comment_data.review.as_ref(),
+            comment_data.count.to_be_bytes().as_ref(),
+        ],
+        program_id,
+    );
+    if comment_pda != *comment_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if comment_data.commenter != *commenter.key {
+        msg!("Comment does not belong to signer");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    if let Some(config_data) = load_config(program_id, config_account)? {
+        let window = config_data.comment_edit_window_secs;
+        let age = Clock::get()?
+            .unix_timestamp
+            .saturating_sub(comment_data.created_at);
+        if window > 0 && age > window as i64 {
+            msg!("Comment is older than the {} second edit window", window);
+            return Err(ReviewError::CommentLocked.into());
+        }
+    }
+
+    resize_account(
+        comment_account,
+        commenter,
+        system_program,
+        MovieComment::get_account_size(comment.clone())?,
+    )?;
+
+    comment_data.comment = comment;
+    comment_data.save()?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+struct DeleteCommentAccounts<'a, 'info> {
+    #[account(signer, mut)]
+    commenter: &'a AccountInfo<'info>,
+    #[account(mut)]
+    comment_account: &'a AccountInfo<'info>,
+}
+
+/// Closes one of the signer's comments and refunds its rent. The review's
+/// comment counter is left alone, since it numbers comment addresses.
+pub fn delete_comment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    debug_msg!("Deleting comment...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let DeleteCommentAccounts {
+        commenter,
+        comment_account,
+    } = DeleteCommentAccounts::try_accounts(program_id, account_info_iter)?;
+
+    let comment_data = CommentAccount::load_mut(program_id, comment_account)?;
+
+    let (comment_pda, _bump_seed) = Pubkey::find_program_address(
+        &[
+            comment_data.review.as_ref(),
+            comment_data.count.to_be_bytes().as_ref(),
+        ],
+        program_id,
+    );
+    if comment_pda != *comment_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if comment_data.commenter != *commenter.key {
+        msg!("Comment does not belong to signer");
+        return Err(ReviewError::InvalidAuthority.into());
+    }
+
+    close_account(comment_account, commenter, true)
+}
+
+// Inside processor.rs
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    assert_matches::*,
+    num_traits::FromPrimitive,
+  };
+
+    #[test]
+    fn test_reward_amount_halves() {
+        let mut config: ConfigState = try_from_slice_unchecked(&[0; ConfigState::SIZE]).unwrap();
+        config.reward_base_amount = 100;
+        assert_eq!(config.reward_amount(1_000), 100);
+
+        config.reward_halving_interval = 10;
+        config.rewarded_reviews = 25;
+        assert_eq!(config.reward_amount(0), 25);
+
+        config.reward_halving_unit = RewardHalvingUnit::Slots;
+        config.reward_start_slot = 100;
+        assert_eq!(config.reward_amount(109), 100);
+        assert_eq!(config.reward_amount(110), 50);
+        assert_eq!(config.reward_amount(u64::MAX), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_json_round_trip() {
+        let reviewer = Pubkey::new_unique();
+        let review = MovieAccountState {
+            discriminator: MovieAccountState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            reviewer,
+            rating: 4,
+            title: "Iron Man".to_string(),
+            description: "Liked it".to_string(),
+            visibility: Visibility::Unlisted,
+            status: ReviewStatus::Published,
+            published_at: 1_700_000_000,
+            publish_at: None,
+            kind: EntityKind::Movie,
+            valid_until: None,
+            upvotes: 0,
+            downvotes: 0,
+            tips_received: 0,
+            created_at: 0,
+            updated_at: 0,
+            bump: 0,
+        };
+
+        let json = serde_json::to_value(&review).unwrap();
+        assert_eq!(json["reviewer"], reviewer.to_string());
+        assert_eq!(json["visibility"], "Unlisted");
+
+        let decoded: MovieAccountState = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.try_to_vec().unwrap(), review.try_to_vec().unwrap());
+
+        let mut profile: ReviewerProfile =
+            try_from_slice_unchecked(&[0; ReviewerProfile::SIZE]).unwrap();
+        profile.referred_by = Some(reviewer);
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(json["referred_by"], reviewer.to_string());
+        let decoded: ReviewerProfile = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.referred_by, Some(reviewer));
+    }
+
+    #[test]
+    fn test_review_display_elides_long_descriptions() {
+        let reviewer = Pubkey::new_unique();
+        let mut review = MovieAccountState {
+            discriminator: MovieAccountState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            reviewer,
+            rating: 4,
+            title: "Iron Man".to_string(),
+            description: "é".repeat(100),
+            visibility: Visibility::Public,
+            status: ReviewStatus::Published,
+            published_at: 0,
+            publish_at: None,
+            kind: EntityKind::Movie,
+            valid_until: None,
+            upvotes: 0,
+            downvotes: 0,
+            tips_received: 0,
+            created_at: 0,
+            updated_at: 0,
+            bump: 0,
+        };
+
+        assert_eq!(
+            review.to_string(),
+            format!(
+                "\"Iron Man\" rated 4.0/5 by {} (Published, Public): {}…",
+                reviewer,
+                "é".repeat(60)
+            )
+        );
+
+        review.description = "Liked it".to_string();
+        assert!(review.to_string().ends_with(": Liked it"));
+    }
+
+    #[test]
+    fn test_vesting_unlocks_linearly() {
+        let mut vesting: VestingState = try_from_slice_unchecked(&[0; VestingState::SIZE]).unwrap();
+        vesting.total = 1_000;
+        vesting.start = 100;
+        vesting.duration_secs = 400;
+        assert_eq!(vesting.unlocked_amount(0), 0);
+        assert_eq!(vesting.unlocked_amount(100), 0);
+        assert_eq!(vesting.unlocked_amount(200), 250);
+        assert_eq!(vesting.unlocked_amount(500), 1_000);
+        assert_eq!(vesting.unlocked_amount(i64::MAX), 1_000);
+
+        vesting.duration_secs = 0;
+        assert_eq!(vesting.unlocked_amount(100), 1_000);
+    }
+
+    #[test]
+    fn test_checked_size_rejects_overflow() {
+        assert_eq!(state::checked_size(&[1, 1, 4, 10, 4, 20]).unwrap(), 40);
+        assert_matches!(
+            state::checked_size(&[1, usize::MAX]),
+            Err(ReviewError::ArithmeticOverflow)
+        );
+        assert_matches!(
+            state::checked_size(&[4, usize::MAX - 4, 1]),
+            Err(ReviewError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn test_account_sizes_at_boundaries() {
+        assert_eq!(
+            AirdropClaimStatus::get_account_size(u32::MAX as u64).unwrap(),
+            4 + AirdropClaimStatus::DISCRIMINATOR.len() + 1 + 1 + 8 + 4 + (1 << 29)
+        );
+        assert_eq!(
+            AirdropClaimStatus::bitmap_len(u64::MAX).unwrap(),
+            (u64::MAX / 8 + 1) as usize
+        );
+        assert_matches!(
+            MovieAccountState::get_account_size(String::new(), String::new()),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn test_purge_bounty_at_u64_boundary() {
+        assert_eq!(purge_bounty(0).unwrap(), 0);
+        assert_eq!(purge_bounty(10_000).unwrap(), PURGE_BOUNTY_BPS);
+        assert_eq!(
+            purge_bounty(u64::MAX).unwrap(),
+            ((u64::MAX as u128) * (PURGE_BOUNTY_BPS as u128) / 10_000) as u64
+        );
+        assert_eq!(share_bps(u64::MAX, 10_000).unwrap(), u64::MAX);
+        assert_eq!(
+            share_bps(u64::MAX, 10_001).unwrap_err(),
+            ReviewError::ArithmeticOverflow.into()
+        );
+    }
+
+    #[test]
+    fn test_inbox_ring_overwrites_oldest() {
+        let mut inbox = InboxState {
+            discriminator: InboxState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            owner: Pubkey::new_unique(),
+            next_seq: 0,
+            read_seq: 0,
+            notifications: Vec::new(),
+        };
+        let total = state::INBOX_CAPACITY as u64 + 5;
+        for slot in 0..total {
+            let seq = inbox
+                .push(NotificationKind::Reply, Pubkey::new_unique(), slot)
+                .unwrap();
+            assert_eq!(seq, slot);
+        }
+
+        assert_eq!(inbox.notifications.len(), state::INBOX_CAPACITY);
+        assert!(inbox.try_to_vec().unwrap().len() <= InboxState::SIZE);
+        let unread = inbox.unread();
+        assert_eq!(unread.len(), state::INBOX_CAPACITY);
+        assert_eq!(unread[0].seq, 5);
+        assert_eq!(unread.last().unwrap().seq, total - 1);
+
+        inbox.read_seq = total - 2;
+        assert_eq!(inbox.unread().len(), 2);
+    }
+
+    #[test]
+    fn test_full_review_index_page_fits_return_data() {
+        let index = ReviewIndexState {
+            discriminator: ReviewIndexState::DISCRIMINATOR.to_string(),
+            version: state::STATE_VERSION,
+            is_initialized: true,
+            reviewer: Pubkey::new_unique(),
+            page: u32::MAX,
+            reviews: vec![Pubkey::new_unique(); REVIEW_INDEX_PAGE_LEN],
+        };
+        assert_eq!(index.try_to_vec().unwrap().len(), ReviewIndexState::SIZE);
+
+        let record = ReviewRecord {
+            title_hash: [0; 32],
+            rating: state::MAX_RATING,
+            created_at: i64::MAX,
+        };
+        let records = vec![record; REVIEW_INDEX_PAGE_LEN].try_to_vec().unwrap();
+        assert_eq!(
+            records.len(),
+            4 + REVIEW_INDEX_PAGE_LEN * ReviewRecord::SIZE
+        );
+        assert!(records.len() <= solana_program::program::MAX_RETURN_DATA);
+    }
+
+    #[test]
+    fn test_parse_mentions() {
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let text = format!(
+            "@{} agrees with @{}, right @{}? @notakey @",
+            alice, bob, alice
+        );
+        assert_eq!(
+            mention::parse_mentions(&text),
+            vec![
+                Mention::Key(alice),
+                Mention::Key(bob),
+                Mention::Handle("notakey".to_string())
+            ]
+        );
+        assert_eq!(
+            mention::parse_mentions("@Bob_1, @bob_1 @ab @no-dashes"),
+            vec![Mention::Handle("bob_1".to_string())]
+        );
+
+        let many: Vec<Pubkey> = (0..mention::MAX_MENTIONS + 2)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        let text = many
+            .iter()
+            .map(|key| format!("@{}", key))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            mention::parse_mentions(&text),
+            many[..mention::MAX_MENTIONS]
+                .iter()
+                .map(|key| Mention::Key(*key))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_usd_cents_to_lamports() {
+        let price = Price {
+            price: 2_000_000_000,
+            conf: 0,
+            expo: -8,
+            publish_time: 0,
+        };
+        assert_eq!(usd_cents_to_lamports(100, &price), Some(50_000_000));
+        assert_eq!(usd_cents_to_lamports(0, &price), Some(0));
+        assert_eq!(
+            usd_cents_to_lamports(
+                100,
+                &Price {
+                    price: 0,
+                    ..price
+                }
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_review_error_codes_are_stable() {
+        for (error, code) in [
+            (ReviewError::UninitializedAccount, 0),
+            (ReviewError::InvalidPDA, 1),
+            (ReviewError::InvalidDataLength, 2),
+            (ReviewError::InvalidRating, 3),
+            (ReviewError::InvalidAuthority, 5),
+            (ReviewError::ArithmeticOverflow, 12),
+            (ReviewError::TitleTooLong, 48),
+            (ReviewError::DescriptionTooLong, 49),
+            (ReviewError::EmptyField, 51),
+            (ReviewError::AlreadyCurrentVersion, 52),
+            (ReviewError::ReviewAlreadyExists, 53),
+            (ReviewError::UnauthorizedReviewer, 54),
+            (ReviewError::SnapshotsNotConfigured, 55),
+            (ReviewError::ReviewIndexPageFull, 56),
+        ] {
+            assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+        }
+        assert!(ReviewError::from_u32(57).is_none());
+    }
+}
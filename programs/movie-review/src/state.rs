@@ -0,0 +1,1525 @@
+use crate::error::ReviewError;
+use crate::mention::MAX_HANDLE_LEN;
+use borsh::{BorshDeserialize, BorshSerialize};
+use movie_review_derive::VersionedState;
+use solana_program::{
+    hash::hashv,
+    program_pack::{IsInitialized, Sealed},
+    pubkey::{Pubkey, MAX_SEED_LEN},
+};
+use std::{fmt, io};
+
+/// Share of a purged account's rent paid to the caller of `PurgeExpired`, in
+/// basis points.
+pub const PURGE_BOUNTY_BPS: u64 = 500;
+
+/// Layout version of the state accounts the program creates. Every state
+/// struct holds its version right after its discriminator, at a fixed offset
+/// for each kind.
+pub const STATE_VERSION: u8 = 2;
+
+/// Reads the version following `discriminator` for `VersionedState` decoding.
+/// Version 1 accounts have no version field and hold `is_initialized`, 0 or 1,
+/// in its place.
+pub(crate) fn read_version(discriminator: &str, buf: &mut &[u8]) -> io::Result<u8> {
+    match buf.first() {
+        // Blank accounts about to be filled in take the current layout.
+        _ if discriminator.is_empty() => {
+            u8::deserialize(buf)?;
+            Ok(STATE_VERSION)
+        }
+        Some(0 | 1) => Ok(1),
+        _ => u8::deserialize(buf),
+    }
+}
+
+/// Layout version of the state account holding `data`, read without decoding
+/// the rest. `None` if `data` is too short to hold state.
+pub fn state_version(data: &[u8]) -> Option<u8> {
+    let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    match *data.get(4 + len)? {
+        0 | 1 => Some(1),
+        version => Some(version),
+    }
+}
+
+/// Discriminator written into accounts by the close procedure.
+pub const CLOSED_ACCOUNT_DISCRIMINATOR: &str = "closed";
+
+fn closed_marker() -> Vec<u8> {
+    CLOSED_ACCOUNT_DISCRIMINATOR.to_string().try_to_vec().unwrap()
+}
+
+pub fn is_closed(data: &[u8]) -> bool {
+    data.starts_with(&closed_marker())
+}
+
+/// Writes the closed discriminator at the start of `data`, where every state
+/// struct keeps its own discriminator.
+pub fn mark_closed(data: &mut [u8]) {
+    let marker = closed_marker();
+    let len = marker.len().min(data.len());
+    data[..len].copy_from_slice(&marker[..len]);
+}
+
+/// Sums the byte sizes of an account's fields, failing instead of wrapping.
+pub fn checked_size(parts: &[usize]) -> Result<usize, ReviewError> {
+    parts.iter().try_fold(0usize, |total, part| {
+        total
+            .checked_add(*part)
+            .ok_or(ReviewError::ArithmeticOverflow)
+    })
+}
+
+/// Serde adapters writing pubkeys as base58 strings, the way Solana tooling
+/// prints them, rather than as byte arrays.
+#[cfg(feature = "serde")]
+pub mod serde_pubkey {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_program::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        Pubkey::from_str(&encoded).map_err(D::Error::custom)
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            pubkey: &Option<Pubkey>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match pubkey {
+                Some(pubkey) => serializer.collect_str(pubkey),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Pubkey>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|encoded| Pubkey::from_str(&encoded).map_err(D::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+/// Who can find and read a review. `Unlisted` reviews are readable by anyone
+/// holding the address; `Restricted` ones only by members of the review's ACL.
+/// Indexes and aggregates only count `Public` reviews.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Visibility {
+    #[default]
+    Public,
+    Unlisted,
+    Restricted,
+}
+
+/// Drafts are saved on-chain but do not count towards indexes, aggregates or
+/// rewards until published. Reviews past their `valid_until` are flipped to
+/// `Expired` by `ExpireReview` and drop out of indexes and aggregates again.
+/// The config admin can `ArchiveReview` any review, which keeps its data but
+/// unlists it and freezes it: it can no longer be updated, published, expired
+/// or commented on.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReviewStatus {
+    #[default]
+    Published,
+    Draft,
+    Expired,
+    Archived,
+}
+
+/// What a review is about. Reviews, and the watch attestations they may
+/// require, are addressed per kind.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntityKind {
+    #[default]
+    Movie,
+    Series,
+    Book,
+    Game,
+}
+
+impl EntityKind {
+    /// Seed prefix for this kind's accounts. Movies predate entity kinds, so
+    /// theirs is empty and their reviews keep the `[reviewer, title]` address.
+    pub fn seed(self) -> &'static [u8] {
+        match self {
+            EntityKind::Movie => b"",
+            EntityKind::Series => b"series",
+            EntityKind::Book => b"book",
+            EntityKind::Game => b"game",
+        }
+    }
+}
+
+/// A review's title as a PDA seed. Seeds are capped at `MAX_SEED_LEN` bytes,
+/// so longer titles are hashed; shorter ones are used as is and keep the
+/// addresses their reviews always had.
+pub fn review_title_seed(title: &str) -> Vec<u8> {
+    if title.len() <= MAX_SEED_LEN {
+        title.as_bytes().to_vec()
+    } else {
+        hashv(&[title.as_bytes()]).to_bytes().to_vec()
+    }
+}
+
+/// Address of `reviewer`'s review of the `kind` entity `title`, and its bump.
+pub fn derive_review_pda(
+    program_id: &Pubkey,
+    kind: EntityKind,
+    reviewer: &Pubkey,
+    title: &str,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[kind.seed(), reviewer.as_ref(), &review_title_seed(title)],
+        program_id,
+    )
+}
+
+/// Address of the versus poll between the movies titled `movie_a` and
+/// `movie_b`, and its bump. Titles are seeded like review titles.
+pub fn derive_versus_poll_pda(program_id: &Pubkey, movie_a: &str, movie_b: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"versus",
+            &review_title_seed(movie_a),
+            &review_title_seed(movie_b),
+        ],
+        program_id,
+    )
+}
+
+/// Address of `viewer`'s watch attestation for the `kind` entity
+/// `entity_id`, and its bump. Ids are seeded like review titles.
+pub fn derive_watch_attestation_pda(
+    program_id: &Pubkey,
+    kind: EntityKind,
+    viewer: &Pubkey,
+    entity_id: &str,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"watched",
+            kind.seed(),
+            viewer.as_ref(),
+            &review_title_seed(entity_id),
+        ],
+        program_id,
+    )
+}
+
+/// Hash of `title` that summaries are addressed by.
+pub fn title_hash(title: &str) -> [u8; 32] {
+    hashv(&[title.as_bytes()]).to_bytes()
+}
+
+/// Address of the rating summary of the `kind` entity `title`, and its bump.
+pub fn derive_summary_pda(program_id: &Pubkey, kind: EntityKind, title: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"summary", kind.seed(), &title_hash(title)], program_id)
+}
+
+/// Address of `page` of `reviewer`'s review index, and its bump.
+pub fn derive_review_index_pda(program_id: &Pubkey, reviewer: &Pubkey, page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"review_index", reviewer.as_ref(), &page.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Address of the snapshot of `summary` for `epoch`, and its bump.
+pub fn derive_snapshot_pda(program_id: &Pubkey, summary: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"agg_hist", summary.as_ref(), &epoch.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Lowest and highest ratings, in tenths of a star.
+pub const MIN_RATING: u8 = 10;
+pub const MAX_RATING: u8 = 50;
+
+/// `rating` in tenths of a star, `None` if it is out of range. Ratings of 1 to
+/// 5 are whole stars, as instructions and reviews held them before half stars,
+/// and are scaled up.
+pub fn scale_rating(rating: u8) -> Option<u8> {
+    match rating {
+        1..=5 => Some(rating * 10),
+        MIN_RATING..=MAX_RATING => Some(rating),
+        _ => None,
+    }
+}
+
+/// Longest review title accepted, in bytes.
+pub const MAX_TITLE_LEN: usize = 200;
+
+/// Longest review description accepted, in bytes. Together with the longest
+/// title this keeps a review's text within the 1000 bytes it was capped at.
+pub const MAX_DESCRIPTION_LEN: usize = 790;
+
+#[derive(VersionedState, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MovieAccountState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub reviewer: Pubkey,
+    /// In tenths of a star, or whole stars in reviews not updated since half
+    /// stars were added; read it with `rating_tenths`.
+    pub rating: u8,
+    pub title: String,
+    pub description: String,
+    pub visibility: Visibility,
+    pub status: ReviewStatus,
+    pub published_at: i64,
+    pub publish_at: Option<i64>,
+    pub kind: EntityKind,
+    /// After this anyone may expire the review, e.g. to embargo reviews of a
+    /// movie until its release.
+    pub valid_until: Option<i64>,
+    pub upvotes: u64,
+    pub downvotes: u64,
+    /// Lamports tipped to the reviewer through `TipReviewer`.
+    pub tips_received: u64,
+    pub created_at: i64,
+    /// When the review was last changed with `UpdateMovieReview`, or
+    /// `created_at` if it never was.
+    pub updated_at: i64,
+    /// Bump seed of the review's address. Version 1 reviews gain it through
+    /// `MigrateReview`.
+    #[state(since = 2)]
+    pub bump: u8,
+}
+
+impl MovieAccountState {
+    pub const DISCRIMINATOR: &'static str = "review";
+
+    pub fn get_account_size(title: String, description: String) -> Result<usize, ReviewError> {
+        checked_size(&[
+            4 + MovieAccountState::DISCRIMINATOR.len(),
+            1,
+            1,
+            32,
+            1,
+            4,
+            title.len(),
+            4,
+            description.len(),
+            1,
+            1,
+            8,
+            1 + 8,
+            1,
+            1 + 8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            1,
+        ])
+    }
+
+    /// The review's address, checked against its stored bump rather than
+    /// searched for like `derive_review_pda` does. Version 1 reviews store no
+    /// bump, so theirs is still searched for. `None` if the bump is not valid
+    /// for the review's seeds.
+    pub fn address(&self, program_id: &Pubkey) -> Option<Pubkey> {
+        if self.version < 2 {
+            let (address, _bump) =
+                derive_review_pda(program_id, self.kind, &self.reviewer, &self.title);
+            return Some(address);
+        }
+        Pubkey::create_program_address(
+            &[
+                self.kind.seed(),
+                self.reviewer.as_ref(),
+                &review_title_seed(&self.title),
+                &[self.bump],
+            ],
+            program_id,
+        )
+        .ok()
+    }
+
+    pub fn rating_tenths(&self) -> u8 {
+        scale_rating(self.rating).unwrap_or(self.rating)
+    }
+
+    /// Whether the review has expired at `now`, even if nobody has sent
+    /// `ExpireReview` for it yet.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.status == ReviewStatus::Expired
+            || self.valid_until.is_some_and(|until| now >= until)
+    }
+
+    /// Whether the review may appear in indexes and aggregates at `now`.
+    pub fn is_listed(&self, now: i64) -> bool {
+        self.visibility == Visibility::Public
+            && self.status == ReviewStatus::Published
+            && !self.is_expired(now)
+    }
+
+    /// The rating the review adds to its title's `MovieSummary`, `None` while
+    /// it is left out: counted reviews are published and public, and stay
+    /// counted past `valid_until` until `ExpireReview` flips them to expired.
+    /// Version 1 reviews are left out until `MigrateReview` counts them in.
+    pub fn counted_rating(&self) -> Option<u8> {
+        (self.version >= 2
+            && self.visibility == Visibility::Public
+            && self.status == ReviewStatus::Published)
+            .then(|| self.rating_tenths())
+    }
+
+    /// Whether `reader` may see the review, given its ACL if it has one.
+    pub fn is_readable_by(&self, reader: &Pubkey, acl: Option<&AclState>) -> bool {
+        match self.visibility {
+            Visibility::Public | Visibility::Unlisted => true,
+            Visibility::Restricted => {
+                *reader == self.reviewer || acl.is_some_and(|acl| acl.contains(reader))
+            }
+        }
+    }
+}
+
+#[derive(VersionedState, Debug)]
+pub struct MovieCommentCounter {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub counter: u64,
+    /// Set by the config admin to exempt a thread from the comment cap.
+    pub uncapped: bool,
+}
+
+impl MovieCommentCounter {
+    pub const DISCRIMINATOR: &'static str = "counter";
+    pub const SIZE: usize = (4 + MovieCommentCounter::DISCRIMINATOR.len()) + 1 + 1 + 8 + 1;
+}
+
+/// Longest comment accepted by `AddComment`, in bytes.
+pub const MAX_COMMENT_LEN: usize = 500;
+
+/// `comment_reward_amount` of a new config.
+pub const COMMENT_REWARD_AMOUNT: u64 = 5;
+
+#[derive(VersionedState, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MovieComment {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub review: Pubkey,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub commenter: Pubkey,
+    pub comment: String,
+    pub count: u64,
+    pub created_at: i64,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub commenter_profile: Pubkey,
+}
+
+impl MovieComment {
+    pub const DISCRIMINATOR: &'static str = "comment";
+
+    pub fn get_account_size(comment: String) -> Result<usize, ReviewError> {
+        checked_size(&[
+            4 + MovieComment::DISCRIMINATOR.len(),
+            1,
+            1,
+            32,
+            32,
+            4,
+            comment.len(),
+            8,
+            8,
+            32,
+        ])
+    }
+}
+
+#[derive(VersionedState, Debug)]
+pub struct AirdropState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub round: u64,
+    pub max_num_claims: u64,
+}
+
+impl AirdropState {
+    pub const DISCRIMINATOR: &'static str = "airdrop";
+    pub const SIZE: usize = (4 + AirdropState::DISCRIMINATOR.len()) + 1 + 1 + 32 + 32 + 8 + 8;
+}
+
+#[derive(VersionedState, Debug)]
+pub struct AirdropClaimStatus {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub round: u64,
+    pub claimed: Vec<u8>,
+}
+
+impl AirdropClaimStatus {
+    pub const DISCRIMINATOR: &'static str = "airdrop_claims";
+    // Bounded by the largest account a program can create through CPI.
+    pub const MAX_NUM_CLAIMS: u64 = 80_000;
+
+    pub fn get_account_size(max_num_claims: u64) -> Result<usize, ReviewError> {
+        checked_size(&[
+            4 + AirdropClaimStatus::DISCRIMINATOR.len(),
+            1,
+            1,
+            8,
+            4,
+            AirdropClaimStatus::bitmap_len(max_num_claims)?,
+        ])
+    }
+
+    pub fn bitmap_len(max_num_claims: u64) -> Result<usize, ReviewError> {
+        usize::try_from(max_num_claims.div_ceil(8)).map_err(|_| ReviewError::ArithmeticOverflow)
+    }
+
+    pub fn is_claimed(&self, index: u64) -> bool {
+        self.claimed[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u64) {
+        self.claimed[(index / 8) as usize] |= 1 << (index % 8);
+    }
+}
+
+/// What the reward schedule's halving interval is measured in.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RewardHalvingUnit {
+    /// Rewarded reviews since the schedule was set.
+    #[default]
+    Reviews,
+    /// Slots since the schedule was set.
+    Slots,
+}
+
+/// Instruction families the config admin can switch off without a redeploy.
+/// Each is a bit of `ConfigState::disabled_features`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Feature {
+    /// `AddComment` and `UpdateComment`.
+    Comments,
+    /// `Subscribe`.
+    Subscriptions,
+    /// `FundEscrow`. Existing escrows can still be disputed and settled.
+    Escrow,
+    /// `ClaimAirdrop`, `ClaimRewards` and `ClaimVested`.
+    Minting,
+    /// `CreateVersusPoll`, `VoteVersus` and `VoteOnReview`. Ended polls can
+    /// still be finalized.
+    Votes,
+    /// `TipReviewer` and `TipReviewerToken`.
+    Tips,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 6] = [
+        Feature::Comments,
+        Feature::Subscriptions,
+        Feature::Escrow,
+        Feature::Minting,
+        Feature::Votes,
+        Feature::Tips,
+    ];
+
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+#[derive(VersionedState, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub admin: Pubkey,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub treasury: Pubkey,
+    pub review_fee_lamports: u64,
+    pub review_fee_usd_cents: u64,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub price_feed: Pubkey,
+    pub max_price_age_secs: u64,
+    pub require_watch_attestation: bool,
+    /// Zero leaves the number of comments per review unbounded.
+    pub max_comments_per_review: u64,
+    /// How long after posting a comment can still be edited. Zero never locks
+    /// comments.
+    pub comment_edit_window_secs: u64,
+    /// Tokens minted per published review before any halving. Zero disables
+    /// review rewards.
+    pub reward_base_amount: u64,
+    /// Zero never halves the reward.
+    pub reward_halving_interval: u64,
+    pub reward_halving_unit: RewardHalvingUnit,
+    pub reward_start_slot: u64,
+    pub rewarded_reviews: u64,
+    /// Airdrop grants larger than this vest linearly instead of being minted
+    /// at once. Zero disables vesting.
+    pub vesting_threshold: u64,
+    pub vesting_duration_secs: u64,
+    /// Tokens accrued to both the referee and the referrer when a referred
+    /// reviewer publishes their first review.
+    pub referral_bonus: u64,
+    /// Resolves disputed escrows. The default key disables `FundEscrow`.
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub escrow_arbiter: Pubkey,
+    /// How long after funding an escrow its funder can dispute it.
+    pub escrow_dispute_window_secs: u64,
+    /// How long the arbiter has to resolve a dispute before the funder can
+    /// take the escrowed lamports back.
+    pub escrow_resolution_timeout_secs: u64,
+    /// `Feature` bits that are switched off. Zero enables everything.
+    pub disabled_features: u32,
+    /// Longest review title in bytes. Zero leaves titles bounded only by the
+    /// review account size.
+    pub max_title_len: u32,
+    /// Longest review description in bytes, zero for no limit of its own.
+    pub max_description_len: u32,
+    /// Stops adding and updating reviews, and everything a `Feature` covers,
+    /// until unpaused. Admin instructions keep working.
+    pub paused: bool,
+    /// Length of a `SnapshotAggregate` epoch. Zero disables snapshots.
+    pub snapshot_interval_secs: u64,
+    /// Tokens accrued to the commenter per comment, apart from the review
+    /// reward schedule. Zero disables comment rewards.
+    pub comment_reward_amount: u64,
+}
+
+impl ConfigState {
+    pub const DISCRIMINATOR: &'static str = "config";
+    pub const SIZE: usize = (4 + ConfigState::DISCRIMINATOR.len())
+        + 1
+        + 1
+        + 32
+        + 32
+        + 8
+        + 8
+        + 32
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 32
+        + 8
+        + 8
+        + 4
+        + 4
+        + 4
+        + 1
+        + 8
+        + 8;
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.disabled_features & feature.bit() == 0
+    }
+
+    /// The `SnapshotAggregate` epoch `now` falls in, `None` while snapshots
+    /// are disabled.
+    pub fn snapshot_epoch(&self, now: i64) -> Option<u64> {
+        (self.snapshot_interval_secs > 0).then(|| now.max(0) as u64 / self.snapshot_interval_secs)
+    }
+
+    /// Reward for the next published review, halved once per elapsed
+    /// `reward_halving_interval`.
+    pub fn reward_amount(&self, slot: u64) -> u64 {
+        if self.reward_halving_interval == 0 {
+            return self.reward_base_amount;
+        }
+        let elapsed = match self.reward_halving_unit {
+            RewardHalvingUnit::Reviews => self.rewarded_reviews,
+            RewardHalvingUnit::Slots => slot.saturating_sub(self.reward_start_slot),
+        };
+        let halvings = elapsed / self.reward_halving_interval;
+        self.reward_base_amount
+            .checked_shr(halvings.try_into().unwrap_or(u32::MAX))
+            .unwrap_or(0)
+    }
+}
+
+/// Program-owned account at `[b"treasury"]` that collects fees when the
+/// program is bootstrapped with `InitializeProgram`.
+#[derive(VersionedState, Debug)]
+pub struct TreasuryState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+}
+
+impl TreasuryState {
+    pub const DISCRIMINATOR: &'static str = "treasury";
+    pub const SIZE: usize = (4 + TreasuryState::DISCRIMINATOR.len()) + 1 + 1;
+}
+
+/// Tokens granted to `beneficiary` that unlock linearly over
+/// `duration_secs` from `start`, at `[b"vesting", claims_account, index]` for
+/// airdrop grants.
+#[derive(VersionedState, Debug)]
+pub struct VestingState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub beneficiary: Pubkey,
+    pub total: u64,
+    pub start: i64,
+    pub duration_secs: u64,
+    pub claimed: u64,
+}
+
+impl VestingState {
+    pub const DISCRIMINATOR: &'static str = "vesting";
+    pub const SIZE: usize = (4 + VestingState::DISCRIMINATOR.len()) + 1 + 1 + 32 + 8 + 8 + 8 + 8;
+
+    /// Amount unlocked at `now`, including what has already been claimed.
+    pub fn unlocked_amount(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start).max(0) as u64;
+        if self.duration_secs == 0 || elapsed >= self.duration_secs {
+            return self.total;
+        }
+        (self.total as u128 * elapsed as u128 / self.duration_secs as u128) as u64
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EscrowStatus {
+    /// Released to the recipient by `SettleEscrow` once the dispute window
+    /// closes.
+    Funded,
+    /// Frozen until the arbiter resolves it, or refunded to the funder by
+    /// `SettleEscrow` once the resolution timeout passes.
+    Disputed,
+    /// Paid out to `paid_to`. Only the account's rent is left.
+    Resolved,
+}
+
+/// Lamports held for `recipient` at `[b"escrow", funder, recipient, nonce]`
+/// until the dispute window closes or a dispute is resolved.
+#[derive(VersionedState, Debug)]
+pub struct EscrowState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub funder: Pubkey,
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub status: EscrowStatus,
+    pub dispute_deadline: i64,
+    /// Set when the escrow is disputed.
+    pub resolution_deadline: i64,
+    /// Set when the escrow is resolved.
+    pub paid_to: Pubkey,
+}
+
+impl EscrowState {
+    pub const DISCRIMINATOR: &'static str = "escrow";
+    pub const SIZE: usize =
+        (4 + EscrowState::DISCRIMINATOR.len()) + 1 + 1 + 32 + 32 + 8 + 8 + 1 + 8 + 8 + 32;
+}
+
+/// Deployed program and instruction versions at `[b"version"]`, written by
+/// `SyncProgramVersion` after each upgrade so clients can check compatibility
+/// before sending anything.
+#[derive(VersionedState, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProgramVersionState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    /// Instruction version this build sends and understands.
+    pub instruction_version: u16,
+    /// Oldest instruction version still accepted, though some instructions
+    /// require a later one. Unversioned instructions count as version 0.
+    pub min_instruction_version: u16,
+}
+
+impl ProgramVersionState {
+    pub const DISCRIMINATOR: &'static str = "version";
+    pub const SIZE: usize = (4 + ProgramVersionState::DISCRIMINATOR.len()) + 1 + 1 + 2 * 5;
+}
+
+/// A viewer's own statement that they watched `movie_id`, which is the title
+/// reviews of the movie are filed under.
+#[derive(VersionedState, Debug)]
+pub struct WatchAttestation {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub viewer: Pubkey,
+    pub movie_id: String,
+    pub watched_at: i64,
+}
+
+impl WatchAttestation {
+    pub const DISCRIMINATOR: &'static str = "watched";
+
+    pub fn get_account_size(movie_id: &str) -> Result<usize, ReviewError> {
+        checked_size(&[
+            4 + WatchAttestation::DISCRIMINATOR.len(),
+            1,
+            1,
+            32,
+            4,
+            movie_id.len(),
+            8,
+        ])
+    }
+}
+
+/// Per-user activity stats at `[b"profile", owner]`.
+#[derive(VersionedState, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReviewerProfile {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey"))]
+    pub owner: Pubkey,
+    pub comments_created: u64,
+    /// Registered handle, or empty if the owner hasn't claimed one.
+    pub handle: String,
+    /// Rewards earned but not yet minted with `ClaimRewards`.
+    pub pending_rewards: u64,
+    pub rewards_claimed: u64,
+    /// Published reviews this profile was passed along with.
+    pub reviews_published: u64,
+    /// Set once, on the first published review, to the user who referred the
+    /// owner.
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkey::option"))]
+    pub referred_by: Option<Pubkey>,
+}
+
+impl ReviewerProfile {
+    pub const DISCRIMINATOR: &'static str = "profile";
+    pub const SIZE: usize = (4 + ReviewerProfile::DISCRIMINATOR.len())
+        + 1
+        + 1
+        + 32
+        + 8
+        + (4 + MAX_HANDLE_LEN)
+        + 8
+        + 8
+        + 8
+        + (1 + 32);
+}
+
+/// Maps a normalized handle to its owner at `[b"handle", handle]`.
+#[derive(VersionedState, Debug)]
+pub struct HandleState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub handle: String,
+    pub owner: Pubkey,
+}
+
+impl HandleState {
+    pub const DISCRIMINATOR: &'static str = "handle";
+
+    pub fn get_account_size(handle: &str) -> Result<usize, ReviewError> {
+        checked_size(&[
+            4 + HandleState::DISCRIMINATOR.len(),
+            1,
+            1,
+            4,
+            handle.len(),
+            32,
+        ])
+    }
+}
+
+/// Number of notifications an inbox holds before the oldest are overwritten.
+pub const INBOX_CAPACITY: usize = 32;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum NotificationKind {
+    Mention,
+    Reply,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Notification {
+    pub seq: u64,
+    pub kind: NotificationKind,
+    pub source: Pubkey,
+    pub slot: u64,
+}
+
+impl Notification {
+    pub const SIZE: usize = 8 + 1 + 32 + 8;
+}
+
+/// Ring of the latest notifications for `owner`. The notification with
+/// sequence number `seq` lives at `notifications[seq % INBOX_CAPACITY]`.
+#[derive(VersionedState, Debug)]
+pub struct InboxState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub next_seq: u64,
+    pub read_seq: u64,
+    pub notifications: Vec<Notification>,
+}
+
+impl InboxState {
+    pub const DISCRIMINATOR: &'static str = "inbox";
+    pub const SIZE: usize = (4 + InboxState::DISCRIMINATOR.len())
+        + 1
+        + 1
+        + 32
+        + 8
+        + 8
+        + (4 + INBOX_CAPACITY * Notification::SIZE);
+
+    /// Appends a notification, overwriting the oldest one once the ring is
+    /// full, and returns its sequence number.
+    pub fn push(
+        &mut self,
+        kind: NotificationKind,
+        source: Pubkey,
+        slot: u64,
+    ) -> Result<u64, ReviewError> {
+        let seq = self.next_seq;
+        let notification = Notification {
+            seq,
+            kind,
+            source,
+            slot,
+        };
+        let slot_index = (seq % INBOX_CAPACITY as u64) as usize;
+        if slot_index < self.notifications.len() {
+            self.notifications[slot_index] = notification;
+        } else {
+            self.notifications.push(notification);
+        }
+        self.next_seq = seq.checked_add(1).ok_or(ReviewError::ArithmeticOverflow)?;
+        Ok(seq)
+    }
+
+    /// Notifications still held in the ring that have not been marked read,
+    /// oldest first.
+    pub fn unread(&self) -> Vec<Notification> {
+        let mut unread: Vec<Notification> = self
+            .notifications
+            .iter()
+            .filter(|notification| notification.seq >= self.read_seq)
+            .copied()
+            .collect();
+        unread.sort_by_key(|notification| notification.seq);
+        unread
+    }
+}
+
+/// Length of one subscription month, in seconds.
+pub const SUBSCRIPTION_MONTH_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Price of one month of a subscription to a reviewer, in lamports.
+pub const SUBSCRIPTION_PRICE_LAMPORTS: u64 = 100_000_000;
+
+/// Share of each subscription payment kept by the treasury, in basis points.
+pub const SUBSCRIPTION_PROTOCOL_CUT_BPS: u64 = 1_000;
+
+#[derive(VersionedState, Debug)]
+pub struct SubscriptionState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub subscriber: Pubkey,
+    pub reviewer: Pubkey,
+    pub expires_at: i64,
+}
+
+impl SubscriptionState {
+    pub const DISCRIMINATOR: &'static str = "sub";
+    pub const SIZE: usize = (4 + SubscriptionState::DISCRIMINATOR.len()) + 1 + 1 + 32 + 32 + 8;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.is_initialized && now < self.expires_at
+    }
+}
+
+/// Cipher used for a premium review's description. Key exchange happens
+/// off-chain; the program only records which scheme the ciphertext uses.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EncryptionScheme {
+    XChaCha20Poly1305,
+    Aes256Gcm,
+}
+
+/// Marks the description of `review` as ciphertext. `plaintext_hash` commits
+/// to the decrypted description so readers can check what they were given.
+#[derive(VersionedState, Debug)]
+pub struct PremiumContentState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub scheme: EncryptionScheme,
+    pub plaintext_hash: [u8; 32],
+}
+
+impl PremiumContentState {
+    pub const DISCRIMINATOR: &'static str = "premium";
+    pub const SIZE: usize = (4 + PremiumContentState::DISCRIMINATOR.len()) + 1 + 1 + 32 + 1 + 32;
+}
+
+/// Longest content key hint a reviewer can leave for a subscriber.
+pub const MAX_KEY_HINT_LEN: usize = 128;
+
+/// Content key for `review`, sealed by the reviewer to `subscriber`.
+#[derive(VersionedState, Debug)]
+pub struct AccessKeyHintState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub subscriber: Pubkey,
+    pub key_hint: Vec<u8>,
+}
+
+impl AccessKeyHintState {
+    pub const DISCRIMINATOR: &'static str = "key_hint";
+    pub const SIZE: usize =
+        (4 + AccessKeyHintState::DISCRIMINATOR.len()) + 1 + 1 + 32 + 32 + (4 + MAX_KEY_HINT_LEN);
+}
+
+/// Most members a restricted review's ACL can hold.
+pub const MAX_ACL_MEMBERS: usize = 32;
+
+/// Readers allowed to see a `Restricted` review, besides its reviewer.
+#[derive(VersionedState, Debug)]
+pub struct AclState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub members: Vec<Pubkey>,
+}
+
+impl AclState {
+    pub const DISCRIMINATOR: &'static str = "acl";
+    pub const SIZE: usize =
+        (4 + AclState::DISCRIMINATOR.len()) + 1 + 1 + 32 + (4 + MAX_ACL_MEMBERS * 32);
+
+    pub fn contains(&self, member: &Pubkey) -> bool {
+        self.members.contains(member)
+    }
+}
+
+/// One of the two movies in a versus poll.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum VersusSide {
+    A,
+    B,
+}
+
+/// Head-to-head poll between the movies titled `movie_a` and `movie_b`, at
+/// `[b"versus", movie_a, movie_b]`. Votes are taken until `ends_at`;
+/// `FinalizeVersusPoll` then records the winner, here and in both movies'
+/// `MovieSummary`.
+#[derive(VersionedState, Debug)]
+pub struct VersusPollState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub creator: Pubkey,
+    pub movie_a: String,
+    pub movie_b: String,
+    pub ends_at: i64,
+    pub votes_a: u64,
+    pub votes_b: u64,
+    pub finalized: bool,
+    /// Set when finalized, `None` for a tie.
+    pub winner: Option<VersusSide>,
+}
+
+impl VersusPollState {
+    pub const DISCRIMINATOR: &'static str = "versus";
+
+    pub fn get_account_size(movie_a: &str, movie_b: &str) -> Result<usize, ReviewError> {
+        checked_size(&[
+            4 + VersusPollState::DISCRIMINATOR.len(),
+            1,
+            1,
+            32,
+            4,
+            movie_a.len(),
+            4,
+            movie_b.len(),
+            8,
+            8,
+            8,
+            1,
+            1 + 1,
+        ])
+    }
+}
+
+/// Receipt at `[b"versus_vote", poll, voter]` for `voter`'s vote, so each
+/// wallet votes once per poll.
+#[derive(VersionedState, Debug)]
+pub struct VersusVoteReceipt {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub poll: Pubkey,
+    pub voter: Pubkey,
+    pub side: VersusSide,
+}
+
+impl VersusVoteReceipt {
+    pub const DISCRIMINATOR: &'static str = "versus_vote";
+    pub const SIZE: usize = (4 + VersusVoteReceipt::DISCRIMINATOR.len()) + 1 + 1 + 32 + 32 + 1;
+}
+
+/// Receipt at `[review, voter, b"vote"]` for `voter`'s vote on a review, so
+/// each wallet votes once per review.
+#[derive(VersionedState, Debug)]
+pub struct ReviewVote {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub voter: Pubkey,
+    pub is_upvote: bool,
+}
+
+impl ReviewVote {
+    pub const DISCRIMINATOR: &'static str = "review_vote";
+    pub const SIZE: usize = (4 + ReviewVote::DISCRIMINATOR.len()) + 1 + 1 + 32 + 32 + 1;
+}
+
+/// Movies a watchlist page holds.
+pub const MAX_WATCHLIST_ENTRIES: usize = 16;
+
+/// Longest movie id a watchlist takes, so pages have a fixed size. Ids are
+/// review titles; movies with longer titles can't be watchlisted.
+pub const MAX_WATCHLIST_MOVIE_ID_LEN: usize = 32;
+
+/// Running rating totals for one title, at `derive_summary_pda` and created by
+/// its first review. It counts the reviews `MovieAccountState::counted_rating`
+/// lets in, adjusted whenever one is published, hidden or removed.
+#[derive(VersionedState, Debug)]
+pub struct MovieSummary {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub kind: EntityKind,
+    /// `title_hash` of the title, which the address is derived from.
+    pub title_hash: [u8; 32],
+    pub review_count: u64,
+    /// Sum of the counted reviews' ratings, in tenths of a star.
+    pub rating_sum: u64,
+    /// Counted reviews by whole stars, one star first. Half stars round down.
+    pub rating_histogram: [u64; 5],
+    /// Finalized versus polls the title won and lost. Ties count in neither.
+    pub versus_wins: u64,
+    pub versus_losses: u64,
+}
+
+impl MovieSummary {
+    pub const DISCRIMINATOR: &'static str = "movie_summary";
+    pub const SIZE: usize =
+        (4 + MovieSummary::DISCRIMINATOR.len()) + 1 + 1 + 1 + 32 + 8 + 8 + 5 * 8 + 8 + 8;
+
+    /// Mean rating of the counted reviews in stars, `None` while there are
+    /// none.
+    pub fn average_rating(&self) -> Option<f64> {
+        (self.review_count > 0).then(|| self.rating_sum as f64 / self.review_count as f64 / 10.0)
+    }
+
+    /// The `rating_histogram` entry a rating in tenths of a star falls in.
+    pub fn histogram_bucket(rating: u8) -> usize {
+        usize::from(rating.clamp(MIN_RATING, MAX_RATING) / 10 - 1)
+    }
+}
+
+/// A title's `MovieSummary` as it stood in one snapshot epoch, at
+/// `[b"agg_hist", summary, epoch]`. Epochs are the config's
+/// `snapshot_interval_secs` long, counted from the Unix epoch, and each holds
+/// at most one snapshot, so clients can chart a rating over time.
+#[derive(VersionedState, Debug)]
+pub struct AggregateSnapshot {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub summary: Pubkey,
+    pub epoch: u64,
+    pub taken_at: i64,
+    pub review_count: u64,
+    pub rating_sum: u64,
+    pub rating_histogram: [u64; 5],
+}
+
+impl AggregateSnapshot {
+    pub const DISCRIMINATOR: &'static str = "agg_hist";
+    pub const SIZE: usize =
+        (4 + AggregateSnapshot::DISCRIMINATOR.len()) + 1 + 1 + 32 + 8 + 8 + 8 + 8 + 5 * 8;
+
+    /// Mean rating of the snapshotted reviews in stars, `None` if there were
+    /// none.
+    pub fn average_rating(&self) -> Option<f64> {
+        (self.review_count > 0).then(|| self.rating_sum as f64 / self.review_count as f64 / 10.0)
+    }
+}
+
+/// One page of the movies `owner` wants to watch, at
+/// `[b"watchlist", owner, page]`. Clients choose which page to add to.
+#[derive(VersionedState, Debug)]
+pub struct WatchlistState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub page: u32,
+    pub movie_ids: Vec<String>,
+}
+
+impl WatchlistState {
+    pub const DISCRIMINATOR: &'static str = "watchlist";
+    pub const SIZE: usize = (4 + WatchlistState::DISCRIMINATOR.len())
+        + 1
+        + 1
+        + 32
+        + 4
+        + (4 + MAX_WATCHLIST_ENTRIES * (4 + MAX_WATCHLIST_MOVIE_ID_LEN));
+}
+
+/// Reviews a review index page holds, so `ExportMyReviews` fits a whole page
+/// of `ReviewRecord`s in its return data.
+pub const REVIEW_INDEX_PAGE_LEN: usize = 24;
+
+/// One page of the reviews `reviewer` has indexed with `IndexReviews`, at
+/// `[b"review_index", reviewer, page]`, so wallets can list a reviewer's
+/// history without scanning program accounts. Clients choose which page to
+/// add to.
+#[derive(VersionedState, Debug)]
+pub struct ReviewIndexState {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub reviewer: Pubkey,
+    pub page: u32,
+    /// Review addresses in the order they were indexed.
+    pub reviews: Vec<Pubkey>,
+}
+
+impl ReviewIndexState {
+    pub const DISCRIMINATOR: &'static str = "review_index";
+    pub const SIZE: usize = (4 + ReviewIndexState::DISCRIMINATOR.len())
+        + 1
+        + 1
+        + 32
+        + 4
+        + (4 + REVIEW_INDEX_PAGE_LEN * 32);
+}
+
+/// An indexed review as `ExportMyReviews` returns it.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReviewRecord {
+    pub title_hash: [u8; 32],
+    /// In tenths of a star.
+    pub rating: u8,
+    pub created_at: i64,
+}
+
+impl ReviewRecord {
+    pub const SIZE: usize = 32 + 1 + 8;
+}
+
+/// Characters of free text `Display` shows before eliding the rest.
+const DISPLAY_TEXT_LEN: usize = 60;
+
+/// Shortens `text` to `DISPLAY_TEXT_LEN` characters, marking any cut with an
+/// ellipsis.
+fn elide(text: &str) -> String {
+    match text.char_indices().nth(DISPLAY_TEXT_LEN) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+impl fmt::Display for MovieAccountState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" rated {}.{}/5 by {} ({:?}, {:?}): {}",
+            self.title,
+            self.rating_tenths() / 10,
+            self.rating_tenths() % 10,
+            self.reviewer,
+            self.status,
+            self.visibility,
+            elide(&self.description)
+        )
+    }
+}
+
+impl fmt::Display for MovieComment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Comment #{} on {} by {}: {}",
+            self.count,
+            self.review,
+            self.commenter,
+            elide(&self.comment)
+        )
+    }
+}
+
+impl fmt::Display for ConfigState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Initialized: {}", self.is_initialized)?;
+        writeln!(f, "Admin: {}", self.admin)?;
+        writeln!(f, "Treasury: {}", self.treasury)?;
+        writeln!(f, "Review fee (lamports): {}", self.review_fee_lamports)?;
+        writeln!(f, "Review fee (USD cents): {}", self.review_fee_usd_cents)?;
+        writeln!(f, "Price feed: {}", self.price_feed)?;
+        writeln!(f, "Max price age (seconds): {}", self.max_price_age_secs)?;
+        writeln!(
+            f,
+            "Require watch attestation: {}",
+            self.require_watch_attestation
+        )?;
+        writeln!(
+            f,
+            "Max comments per review: {}",
+            self.max_comments_per_review
+        )?;
+        writeln!(
+            f,
+            "Comment edit window (seconds): {}",
+            self.comment_edit_window_secs
+        )?;
+        writeln!(f, "Reward base amount: {}", self.reward_base_amount)?;
+        writeln!(
+            f,
+            "Reward halving interval: {} {:?}",
+            self.reward_halving_interval, self.reward_halving_unit
+        )?;
+        writeln!(f, "Reward start slot: {}", self.reward_start_slot)?;
+        writeln!(f, "Rewarded reviews: {}", self.rewarded_reviews)?;
+        writeln!(
+            f,
+            "Vesting: above {} over {} seconds",
+            self.vesting_threshold, self.vesting_duration_secs
+        )?;
+        writeln!(f, "Referral bonus: {}", self.referral_bonus)?;
+        writeln!(f, "Escrow arbiter: {}", self.escrow_arbiter)?;
+        writeln!(
+            f,
+            "Escrow dispute window (seconds): {}",
+            self.escrow_dispute_window_secs
+        )?;
+        writeln!(
+            f,
+            "Escrow resolution timeout (seconds): {}",
+            self.escrow_resolution_timeout_secs
+        )?;
+        let disabled: Vec<Feature> = Feature::ALL
+            .into_iter()
+            .filter(|feature| !self.is_enabled(*feature))
+            .collect();
+        writeln!(f, "Disabled features: {:?}", disabled)?;
+        writeln!(f, "Max title length: {}", self.max_title_len)?;
+        writeln!(f, "Max description length: {}", self.max_description_len)?;
+        writeln!(f, "Paused: {}", self.paused)?;
+        writeln!(
+            f,
+            "Snapshot interval (seconds): {}",
+            self.snapshot_interval_secs
+        )?;
+        write!(f, "Comment reward amount: {}", self.comment_reward_amount)
+    }
+}
+
+impl Sealed for MovieAccountState {}
+
+impl IsInitialized for MovieAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for MovieCommentCounter {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for MovieComment {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AirdropState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AirdropClaimStatus {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ConfigState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for TreasuryState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for VestingState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for EscrowState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ProgramVersionState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for WatchAttestation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReviewerProfile {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for HandleState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for SubscriptionState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for PremiumContentState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AccessKeyHintState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AclState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for InboxState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for VersusPollState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for VersusVoteReceipt {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReviewVote {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for MovieSummary {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AggregateSnapshot {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for WatchlistState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReviewIndexState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
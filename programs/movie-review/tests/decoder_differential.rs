@@ -0,0 +1,885 @@
+//! Differential test between the production instruction decoder and a frozen
+//! copy of the original hand-written decoder. Any change to
+//! `MovieInstruction::unpack` must keep accepting and rejecting exactly the
+//! same byte strings, and decode accepted ones to the same values.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    movie_review::{
+        instruction::MovieInstruction,
+        state::{EncryptionScheme, EntityKind, RewardHalvingUnit, VersusSide, Visibility},
+    },
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+};
+
+#[derive(BorshDeserialize)]
+struct LegacyMovieReviewPayload {
+    title: String,
+    rating: u8,
+    description: String,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyCommentPayload {
+    comment: String,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetAirdropRootPayload {
+    merkle_root: [u8; 32],
+    max_num_claims: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyClaimAirdropPayload {
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyInitializeConfigPayload {
+    treasury: Pubkey,
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: Pubkey,
+    max_price_age_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyMarkNotificationsReadPayload {
+    upto_seq: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySubscribePayload {
+    months: u16,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetPremiumContentPayload {
+    scheme: EncryptionScheme,
+    plaintext_hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyGrantAccessKeyHintPayload {
+    key_hint: Vec<u8>,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetVisibilityPayload {
+    visibility: Visibility,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyAccessPayload {
+    member: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySchedulePublishPayload {
+    publish_at: Option<i64>,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyAttestWatchedPayload {
+    movie_id: String,
+    watched_at: i64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetWatchRequirementPayload {
+    required: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetCommentLimitPayload {
+    max_comments_per_review: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetCommentCapOverridePayload {
+    uncapped: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetCommentEditWindowPayload {
+    comment_edit_window_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyRegisterHandlePayload {
+    handle: String,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyInitializeProgramPayload {
+    review_fee_lamports: u64,
+    review_fee_usd_cents: u64,
+    price_feed: Pubkey,
+    max_price_age_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetRewardSchedulePayload {
+    base_amount: u64,
+    halving_interval: u64,
+    halving_unit: RewardHalvingUnit,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetVestingPolicyPayload {
+    threshold: u64,
+    duration_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetReferralBonusPayload {
+    referral_bonus: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyEntityReviewPayload {
+    kind: EntityKind,
+    title: String,
+    rating: u8,
+    description: String,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyAttestEntityWatchedPayload {
+    kind: EntityKind,
+    entity_id: String,
+    watched_at: i64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetEscrowPolicyPayload {
+    arbiter: Pubkey,
+    dispute_window_secs: u64,
+    resolution_timeout_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyFundEscrowPayload {
+    nonce: u64,
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyResolveEscrowPayload {
+    release: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetReviewExpiryPayload {
+    valid_until: Option<i64>,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyExpireReviewPayload {
+    purge: bool,
+}
+
+#[cfg(feature = "test-utils")]
+#[derive(BorshDeserialize)]
+struct LegacyMintTestTokensPayload {
+    amount: u64,
+}
+
+#[cfg(feature = "test-utils")]
+#[derive(BorshDeserialize)]
+struct LegacySetTestProfilePayload {
+    owner: Pubkey,
+    comments_created: u64,
+    reviews_published: u64,
+    pending_rewards: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetDisabledFeaturesPayload {
+    disabled_features: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyCreateVersusPollPayload {
+    movie_a: String,
+    movie_b: String,
+    ends_at: i64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyVoteVersusPayload {
+    side: VersusSide,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyWatchlistPayload {
+    page: u32,
+    movie_id: String,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetReviewLengthLimitsPayload {
+    max_title_len: u32,
+    max_description_len: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetPausedPayload {
+    paused: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyVoteOnReviewPayload {
+    is_upvote: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyTipReviewerPayload {
+    lamports: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyTipReviewerTokenPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetSnapshotIntervalPayload {
+    snapshot_interval_secs: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacySetCommentRewardPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct LegacyReviewIndexPagePayload {
+    page: u32,
+}
+
+// Reference decoder. Do not change this when the wire format changes on
+// purpose; add a new variant arm instead. The original review arms unwrapped
+// the payload, and a panic rejects the instruction just like an error.
+fn legacy_unpack(input: &[u8]) -> Result<MovieInstruction, ProgramError> {
+    let (&variant, rest) = input
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(match variant {
+        0 => {
+            let payload = LegacyMovieReviewPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AddMovieReview {
+                title: payload.title,
+                rating: payload.rating,
+                description: payload.description,
+            }
+        }
+        1 => {
+            let payload = LegacyMovieReviewPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::UpdateMovieReview {
+                title: payload.title,
+                rating: payload.rating,
+                description: payload.description,
+            }
+        }
+        2 => {
+            let payload = LegacyCommentPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AddComment {
+                comment: payload.comment,
+            }
+        }
+        3 => MovieInstruction::InitializeTokenMint,
+        4 => {
+            let payload = LegacySetAirdropRootPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetAirdropRoot {
+                merkle_root: payload.merkle_root,
+                max_num_claims: payload.max_num_claims,
+            }
+        }
+        5 => {
+            let payload = LegacyClaimAirdropPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::ClaimAirdrop {
+                index: payload.index,
+                amount: payload.amount,
+                proof: payload.proof,
+            }
+        }
+        6 => {
+            let payload = LegacyInitializeConfigPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::InitializeConfig {
+                treasury: payload.treasury,
+                review_fee_lamports: payload.review_fee_lamports,
+                review_fee_usd_cents: payload.review_fee_usd_cents,
+                price_feed: payload.price_feed,
+                max_price_age_secs: payload.max_price_age_secs,
+            }
+        }
+        7 => MovieInstruction::PurgeExpired,
+        8 => MovieInstruction::DeleteMovieReview,
+        9 => {
+            let payload = LegacyMarkNotificationsReadPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::MarkNotificationsRead {
+                upto_seq: payload.upto_seq,
+            }
+        }
+        10 => {
+            let payload = LegacySubscribePayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::Subscribe {
+                months: payload.months,
+            }
+        }
+        11 => {
+            let payload = LegacySetPremiumContentPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetPremiumContent {
+                scheme: payload.scheme,
+                plaintext_hash: payload.plaintext_hash,
+            }
+        }
+        12 => {
+            let payload = LegacyGrantAccessKeyHintPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::GrantAccessKeyHint {
+                key_hint: payload.key_hint,
+            }
+        }
+        13 => {
+            let payload = LegacySetVisibilityPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetVisibility {
+                visibility: payload.visibility,
+            }
+        }
+        14 => {
+            let payload = LegacyAccessPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::GrantAccess {
+                member: payload.member,
+            }
+        }
+        15 => {
+            let payload = LegacyAccessPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::RevokeAccess {
+                member: payload.member,
+            }
+        }
+        16 => {
+            let payload = LegacyMovieReviewPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AddDraftReview {
+                title: payload.title,
+                rating: payload.rating,
+                description: payload.description,
+            }
+        }
+        17 => MovieInstruction::PublishReview,
+        18 => {
+            let payload = LegacySchedulePublishPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SchedulePublish {
+                publish_at: payload.publish_at,
+            }
+        }
+        19 => {
+            let payload = LegacyAttestWatchedPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AttestWatched {
+                movie_id: payload.movie_id,
+                watched_at: payload.watched_at,
+            }
+        }
+        20 => {
+            let payload = LegacySetWatchRequirementPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetWatchRequirement {
+                required: payload.required,
+            }
+        }
+        21 => {
+            let payload = LegacySetCommentLimitPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetCommentLimit {
+                max_comments_per_review: payload.max_comments_per_review,
+            }
+        }
+        22 => {
+            let payload = LegacySetCommentCapOverridePayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetCommentCapOverride {
+                uncapped: payload.uncapped,
+            }
+        }
+        23 => {
+            let payload = LegacyCommentPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::UpdateComment {
+                comment: payload.comment,
+            }
+        }
+        24 => {
+            let payload = LegacySetCommentEditWindowPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetCommentEditWindow {
+                comment_edit_window_secs: payload.comment_edit_window_secs,
+            }
+        }
+        25 => {
+            let payload = LegacyRegisterHandlePayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::RegisterHandle {
+                handle: payload.handle,
+            }
+        }
+        26 => MovieInstruction::ReleaseHandle,
+        27 => {
+            let payload = LegacyInitializeProgramPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::InitializeProgram {
+                review_fee_lamports: payload.review_fee_lamports,
+                review_fee_usd_cents: payload.review_fee_usd_cents,
+                price_feed: payload.price_feed,
+                max_price_age_secs: payload.max_price_age_secs,
+            }
+        }
+        28 => {
+            let payload = LegacySetRewardSchedulePayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetRewardSchedule {
+                base_amount: payload.base_amount,
+                halving_interval: payload.halving_interval,
+                halving_unit: payload.halving_unit,
+            }
+        }
+        29 => MovieInstruction::ClaimRewards,
+        30 => MovieInstruction::ClaimVested,
+        31 => {
+            let payload = LegacySetVestingPolicyPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetVestingPolicy {
+                threshold: payload.threshold,
+                duration_secs: payload.duration_secs,
+            }
+        }
+        32 => {
+            let payload = LegacySetReferralBonusPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetReferralBonus {
+                referral_bonus: payload.referral_bonus,
+            }
+        }
+        #[cfg(feature = "test-utils")]
+        33 => {
+            let payload = LegacyMintTestTokensPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::MintTestTokens {
+                amount: payload.amount,
+            }
+        }
+        #[cfg(feature = "test-utils")]
+        34 => {
+            let payload = LegacySetTestProfilePayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetTestProfile {
+                owner: payload.owner,
+                comments_created: payload.comments_created,
+                reviews_published: payload.reviews_published,
+                pending_rewards: payload.pending_rewards,
+            }
+        }
+        35 => MovieInstruction::InitializeReviewerProfile,
+        36 => {
+            let payload = LegacyEntityReviewPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AddEntityReview {
+                kind: payload.kind,
+                title: payload.title,
+                rating: payload.rating,
+                description: payload.description,
+            }
+        }
+        37 => {
+            let payload = LegacyAttestEntityWatchedPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AttestEntityWatched {
+                kind: payload.kind,
+                entity_id: payload.entity_id,
+                watched_at: payload.watched_at,
+            }
+        }
+        38 => MovieInstruction::SyncProgramVersion,
+        39 => MovieInstruction::MigrateLegacyReview,
+        40 => MovieInstruction::ReclaimOrphans,
+        41 => {
+            let payload = LegacySetEscrowPolicyPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetEscrowPolicy {
+                arbiter: payload.arbiter,
+                dispute_window_secs: payload.dispute_window_secs,
+                resolution_timeout_secs: payload.resolution_timeout_secs,
+            }
+        }
+        42 => {
+            let payload = LegacyFundEscrowPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::FundEscrow {
+                nonce: payload.nonce,
+                amount: payload.amount,
+            }
+        }
+        43 => MovieInstruction::DisputeEscrow,
+        44 => {
+            let payload = LegacyResolveEscrowPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::ResolveEscrow {
+                release: payload.release,
+            }
+        }
+        45 => MovieInstruction::SettleEscrow,
+        46 => MovieInstruction::CloseEscrow,
+        47 => {
+            let payload = LegacySetReviewExpiryPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetReviewExpiry {
+                valid_until: payload.valid_until,
+            }
+        }
+        48 => {
+            let payload = LegacyExpireReviewPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::ExpireReview {
+                purge: payload.purge,
+            }
+        }
+        49 => {
+            let payload = LegacyMovieReviewPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AddMovieReviewIdempotent {
+                title: payload.title,
+                rating: payload.rating,
+                description: payload.description,
+            }
+        }
+        50 => MovieInstruction::InitializeReviewerProfileIdempotent,
+        51 => {
+            let payload = LegacyInitializeConfigPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::InitializeConfigIdempotent {
+                treasury: payload.treasury,
+                review_fee_lamports: payload.review_fee_lamports,
+                review_fee_usd_cents: payload.review_fee_usd_cents,
+                price_feed: payload.price_feed,
+                max_price_age_secs: payload.max_price_age_secs,
+            }
+        }
+        52 => {
+            let payload = LegacySetDisabledFeaturesPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetDisabledFeatures {
+                disabled_features: payload.disabled_features,
+            }
+        }
+        53 => {
+            let payload = LegacyCreateVersusPollPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::CreateVersusPoll {
+                movie_a: payload.movie_a,
+                movie_b: payload.movie_b,
+                ends_at: payload.ends_at,
+            }
+        }
+        54 => {
+            let payload = LegacyVoteVersusPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::VoteVersus { side: payload.side }
+        }
+        55 => MovieInstruction::FinalizeVersusPoll,
+        56 => {
+            let payload = LegacyWatchlistPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::AddToWatchlist {
+                page: payload.page,
+                movie_id: payload.movie_id,
+            }
+        }
+        57 => {
+            let payload = LegacyWatchlistPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::RemoveFromWatchlist {
+                page: payload.page,
+                movie_id: payload.movie_id,
+            }
+        }
+        58 => MovieInstruction::DeleteComment,
+        59 => MovieInstruction::ArchiveReview,
+        60 => {
+            let payload = LegacySetReviewLengthLimitsPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetReviewLengthLimits {
+                max_title_len: payload.max_title_len,
+                max_description_len: payload.max_description_len,
+            }
+        }
+        61 => {
+            let payload = LegacySetPausedPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetPaused {
+                paused: payload.paused,
+            }
+        }
+        62 => {
+            let payload = LegacyVoteOnReviewPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::VoteOnReview {
+                is_upvote: payload.is_upvote,
+            }
+        }
+        63 => {
+            let payload = LegacyTipReviewerPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::TipReviewer {
+                lamports: payload.lamports,
+            }
+        }
+        64 => {
+            let payload = LegacyTipReviewerTokenPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::TipReviewerToken {
+                amount: payload.amount,
+            }
+        }
+        65 => MovieInstruction::MigrateReview,
+        66 => {
+            let payload = LegacySetSnapshotIntervalPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetSnapshotInterval {
+                snapshot_interval_secs: payload.snapshot_interval_secs,
+            }
+        }
+        67 => MovieInstruction::SnapshotAggregate,
+        68 => {
+            let payload = LegacySetCommentRewardPayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::SetCommentReward {
+                amount: payload.amount,
+            }
+        }
+        69 => {
+            let payload = LegacyReviewIndexPagePayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::IndexReviews { page: payload.page }
+        }
+        70 => {
+            let payload = LegacyReviewIndexPagePayload::try_from_slice(rest)
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            MovieInstruction::ExportMyReviews { page: payload.page }
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    })
+}
+
+fn decode(
+    decoder: fn(&[u8]) -> Result<MovieInstruction, ProgramError>,
+    input: &[u8],
+) -> Option<MovieInstruction> {
+    decoder(input).ok()
+}
+
+fn random_string(rng: &mut StdRng) -> String {
+    let len = rng.gen_range(0, 40);
+    (0..len)
+        .map(|_| rng.gen_range(b' ', b'~') as char)
+        .collect()
+}
+
+fn random_pubkey(rng: &mut StdRng) -> Pubkey {
+    Pubkey::new_from_array(rng.gen())
+}
+
+/// Every variant byte the program assigns.
+const VARIANTS: &[u8] = &[
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51,
+    52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70,
+];
+
+/// Well-formed encoding of a random instruction under the legacy layout.
+fn random_valid_instruction(rng: &mut StdRng) -> Vec<u8> {
+    let variant = VARIANTS[rng.gen_range(0, VARIANTS.len())];
+    let mut data = vec![variant];
+    let payload = match variant {
+        0 | 1 | 16 | 49 => (random_string(rng), rng.gen::<u8>(), random_string(rng))
+            .try_to_vec()
+            .unwrap(),
+        3 | 7 | 8 | 17 | 26 | 29 | 30 | 35 | 38 | 39 | 40 | 43 | 45 | 46 | 50 | 55 | 58 | 59
+        | 65 | 67 => vec![],
+        4 => (rng.gen::<[u8; 32]>(), rng.gen::<u64>())
+            .try_to_vec()
+            .unwrap(),
+        5 => {
+            let proof: Vec<[u8; 32]> = (0..rng.gen_range(0, 5)).map(|_| rng.gen()).collect();
+            (rng.gen::<u64>(), rng.gen::<u64>(), proof)
+                .try_to_vec()
+                .unwrap()
+        }
+        6 | 51 => (
+            random_pubkey(rng),
+            rng.gen::<u64>(),
+            rng.gen::<u64>(),
+            random_pubkey(rng),
+            rng.gen::<u64>(),
+        )
+            .try_to_vec()
+            .unwrap(),
+        9 => rng.gen::<u64>().try_to_vec().unwrap(),
+        10 => rng.gen::<u16>().try_to_vec().unwrap(),
+        11 => (rng.gen_range(0u8, 2), rng.gen::<[u8; 32]>())
+            .try_to_vec()
+            .unwrap(),
+        12 => {
+            let key_hint: Vec<u8> = (0..rng.gen_range(0, 80)).map(|_| rng.gen()).collect();
+            key_hint.try_to_vec().unwrap()
+        }
+        13 => rng.gen_range(0u8, 3).try_to_vec().unwrap(),
+        14 | 15 => random_pubkey(rng).try_to_vec().unwrap(),
+        18 | 47 => (rng.gen::<bool>().then(|| rng.gen::<i64>()))
+            .try_to_vec()
+            .unwrap(),
+        19 => (random_string(rng), rng.gen::<i64>()).try_to_vec().unwrap(),
+        53 => (random_string(rng), random_string(rng), rng.gen::<i64>())
+            .try_to_vec()
+            .unwrap(),
+        54 => rng.gen_range(0u8, 2).try_to_vec().unwrap(),
+        56 | 57 => (rng.gen::<u32>(), random_string(rng)).try_to_vec().unwrap(),
+        20 | 22 | 44 | 48 | 61 | 62 => rng.gen::<bool>().try_to_vec().unwrap(),
+        60 => (rng.gen::<u32>(), rng.gen::<u32>()).try_to_vec().unwrap(),
+        2 | 23 | 25 => random_string(rng).try_to_vec().unwrap(),
+        21 | 24 | 32 | 63 | 64 | 66 | 68 => rng.gen::<u64>().try_to_vec().unwrap(),
+        52 | 69 | 70 => rng.gen::<u32>().try_to_vec().unwrap(),
+        27 => (
+            rng.gen::<u64>(),
+            rng.gen::<u64>(),
+            random_pubkey(rng),
+            rng.gen::<u64>(),
+        )
+            .try_to_vec()
+            .unwrap(),
+        28 => (rng.gen::<u64>(), rng.gen::<u64>(), rng.gen_range(0u8, 2))
+            .try_to_vec()
+            .unwrap(),
+        31 | 42 => (rng.gen::<u64>(), rng.gen::<u64>()).try_to_vec().unwrap(),
+        41 => (random_pubkey(rng), rng.gen::<u64>(), rng.gen::<u64>())
+            .try_to_vec()
+            .unwrap(),
+        36 => (
+            rng.gen_range(0u8, 4),
+            random_string(rng),
+            rng.gen::<u8>(),
+            random_string(rng),
+        )
+            .try_to_vec()
+            .unwrap(),
+        37 => (rng.gen_range(0u8, 4), random_string(rng), rng.gen::<i64>())
+            .try_to_vec()
+            .unwrap(),
+        _ => unreachable!(),
+    };
+    data.extend(payload);
+    data
+}
+
+/// Applies one of a handful of corruptions that commonly trip decoders.
+fn mutate(rng: &mut StdRng, mut data: Vec<u8>) -> Vec<u8> {
+    match rng.gen_range(0, 5) {
+        0 => {
+            let len = rng.gen_range(0, data.len() + 1);
+            data.truncate(len);
+        }
+        1 => data.extend((0..rng.gen_range(1, 8)).map(|_| rng.gen::<u8>())),
+        2 => {
+            let index = rng.gen_range(0, data.len());
+            data[index] = rng.gen();
+        }
+        3 => data[0] = rng.gen(),
+        _ => {
+            // Blow up a length prefix
+            if data.len() >= 5 {
+                data[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+            }
+        }
+    }
+    data
+}
+
+fn assert_decoders_agree(input: &[u8]) {
+    assert_eq!(
+        decode(legacy_unpack, input),
+        decode(MovieInstruction::unpack, input),
+        "decoders disagree on {:?}",
+        input
+    );
+}
+
+#[test]
+fn test_decoders_agree_on_valid_instructions() {
+    let mut rng = StdRng::seed_from_u64(205);
+    for _ in 0..5_000 {
+        let input = random_valid_instruction(&mut rng);
+        assert!(decode(legacy_unpack, &input).is_some());
+        assert_decoders_agree(&input);
+    }
+}
+
+#[test]
+fn test_decoders_agree_on_mutated_instructions() {
+    let mut rng = StdRng::seed_from_u64(2050);
+    for _ in 0..20_000 {
+        let valid = random_valid_instruction(&mut rng);
+        let input = mutate(&mut rng, valid);
+        assert_decoders_agree(&input);
+    }
+}
+
+#[test]
+fn test_pack_inverts_unpack() {
+    let mut rng = StdRng::seed_from_u64(262);
+    for _ in 0..5_000 {
+        let input = random_valid_instruction(&mut rng);
+        let instruction = MovieInstruction::unpack(&input).unwrap();
+        assert_eq!(instruction.pack(), input, "{:?}", instruction);
+    }
+
+    // Instructions without a payload ignore trailing bytes, so a decodable
+    // input only has to pack to something that decodes the same.
+    for _ in 0..20_000 {
+        let valid = random_valid_instruction(&mut rng);
+        if let Ok(instruction) = MovieInstruction::unpack(&mutate(&mut rng, valid)) {
+            assert_eq!(
+                MovieInstruction::unpack(&instruction.pack()),
+                Ok(instruction)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_decoders_agree_on_random_bytes() {
+    let mut rng = StdRng::seed_from_u64(20500);
+    for _ in 0..20_000 {
+        let len = rng.gen_range(0, 128);
+        let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        assert_decoders_agree(&input);
+    }
+}
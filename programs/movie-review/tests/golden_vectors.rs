@@ -0,0 +1,136 @@
+//! Golden tests for the vectors in `tests/vectors`. The committed files must
+//! match what `gen-vectors` writes today, and must keep decoding the way they
+//! say they do, so a layout change shows up as a diff in review.
+
+use {
+    movie_review::{
+        accounts::{parse_any, AccountKind},
+        instruction::{split_instruction_version, MovieInstruction},
+        state::{state_version, STATE_VERSION},
+    },
+    serde_json::Value,
+    std::{collections::BTreeSet, fs, path::PathBuf, process::Command},
+};
+
+const VECTORS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors");
+const FILES: [&str; 2] = ["instructions.json", "accounts.json"];
+
+fn load(path: PathBuf) -> Vec<Value> {
+    let text = fs::read_to_string(&path).unwrap();
+    serde_json::from_str::<Value>(&text)
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .clone()
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn committed_vectors_are_up_to_date() {
+    let out_dir = std::env::temp_dir().join(format!("movie-review-vectors-{}", std::process::id()));
+    let status = Command::new(env!("CARGO_BIN_EXE_gen-vectors"))
+        .arg(&out_dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    for file in FILES {
+        let expected = fs::read_to_string(PathBuf::from(VECTORS).join(file)).unwrap();
+        let actual = fs::read_to_string(out_dir.join(file)).unwrap();
+        assert!(
+            expected == actual,
+            "tests/vectors/{} is stale; rerun `cargo run --bin gen-vectors`",
+            file
+        );
+    }
+    fs::remove_dir_all(out_dir).unwrap();
+}
+
+#[test]
+fn instruction_vectors_decode() {
+    let mut variants = BTreeSet::new();
+    for vector in load(PathBuf::from(VECTORS).join("instructions.json")) {
+        let data = decode_hex(vector["data"].as_str().unwrap());
+        let (_version, payload) = split_instruction_version(&data).unwrap();
+        let decoded = MovieInstruction::unpack(payload).unwrap();
+        assert_eq!(
+            format!("{:?}", decoded),
+            vector["decoded"].as_str().unwrap(),
+            "{}",
+            vector["name"]
+        );
+        assert_eq!(decoded.pack(), payload, "{}", vector["name"]);
+        variants.insert(payload[0]);
+    }
+
+    // Every instruction outside `test-utils` has a vector.
+    let expected: BTreeSet<u8> = (0..=70).filter(|v| ![33, 34].contains(v)).collect();
+    assert_eq!(variants, expected);
+}
+
+#[test]
+fn account_vectors_match_their_length() {
+    for vector in load(PathBuf::from(VECTORS).join("accounts.json")) {
+        let data = decode_hex(vector["data"].as_str().unwrap());
+        assert_eq!(
+            data.len() as u64,
+            vector["len"].as_u64().unwrap(),
+            "{}",
+            vector["name"]
+        );
+    }
+}
+
+#[test]
+fn account_vectors_parse_as_their_kind() {
+    let mut kinds = Vec::new();
+    for vector in load(PathBuf::from(VECTORS).join("accounts.json")) {
+        let data = decode_hex(vector["data"].as_str().unwrap());
+        let account = parse_any(&data).unwrap();
+        assert_eq!(
+            format!("{:?}", account),
+            format!(
+                "{:?}({})",
+                account.kind(),
+                vector["decoded"].as_str().unwrap()
+            ),
+            "{}",
+            vector["name"]
+        );
+        kinds.push(account.kind());
+    }
+
+    // Every account kind has a vector.
+    for kind in AccountKind::ALL {
+        assert!(kinds.contains(kind), "no vector for {:?}", kind);
+    }
+}
+
+#[test]
+fn account_vectors_cover_every_layout_version() {
+    let mut layouts = Vec::new();
+    for vector in load(PathBuf::from(VECTORS).join("accounts.json")) {
+        let data = decode_hex(vector["data"].as_str().unwrap());
+        let version = vector["version"].as_u64().unwrap() as u8;
+        assert_eq!(state_version(&data), Some(version), "{}", vector["name"]);
+        layouts.push((parse_any(&data).unwrap().kind(), version));
+    }
+
+    // Clients reading older accounts need every version of every kind.
+    for kind in AccountKind::ALL {
+        for version in 1..=STATE_VERSION {
+            assert!(
+                layouts.contains(&(*kind, version)),
+                "no version {} vector for {:?}",
+                version,
+                kind
+            );
+        }
+    }
+}
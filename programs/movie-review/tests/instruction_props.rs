@@ -0,0 +1,121 @@
+//! Property tests for instruction packing. Review instructions built from any
+//! title, rating and description must survive `pack` then `unpack`, and no
+//! byte string, however malformed, may make the decoder panic.
+
+use {
+    movie_review::{
+        instruction::{split_instruction_version, MovieInstruction},
+        state::EntityKind,
+    },
+    proptest::prelude::*,
+    solana_program::program_error::ProgramError,
+};
+
+fn entity_kind() -> impl Strategy<Value = EntityKind> {
+    prop_oneof![
+        Just(EntityKind::Movie),
+        Just(EntityKind::Series),
+        Just(EntityKind::Book),
+        Just(EntityKind::Game),
+    ]
+}
+
+/// Every instruction that carries a review's title, rating and description.
+fn review_instruction() -> impl Strategy<Value = MovieInstruction> {
+    (
+        0..5u8,
+        entity_kind(),
+        any::<String>(),
+        any::<u8>(),
+        any::<String>(),
+    )
+        .prop_map(
+            |(variant, kind, title, rating, description)| match variant {
+                0 => MovieInstruction::AddMovieReview {
+                    title,
+                    rating,
+                    description,
+                },
+                1 => MovieInstruction::UpdateMovieReview {
+                    title,
+                    rating,
+                    description,
+                },
+                2 => MovieInstruction::AddDraftReview {
+                    title,
+                    rating,
+                    description,
+                },
+                3 => MovieInstruction::AddMovieReviewIdempotent {
+                    title,
+                    rating,
+                    description,
+                },
+                _ => MovieInstruction::AddEntityReview {
+                    kind,
+                    title,
+                    rating,
+                    description,
+                },
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn review_instructions_round_trip(instruction in review_instruction()) {
+        let data = instruction.pack();
+        prop_assert_eq!(MovieInstruction::unpack(&data), Ok(instruction));
+    }
+
+    #[test]
+    fn truncated_review_instructions_are_rejected(
+        instruction in review_instruction(),
+        cut in any::<prop::sample::Index>(),
+    ) {
+        let data = instruction.pack();
+        let len = cut.index(data.len());
+        prop_assert_eq!(
+            MovieInstruction::unpack(&data[..len]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn review_instructions_with_trailing_bytes_are_rejected(
+        instruction in review_instruction(),
+        extra in prop::collection::vec(any::<u8>(), 1..16),
+    ) {
+        let mut data = instruction.pack();
+        data.extend(extra);
+        prop_assert_eq!(
+            MovieInstruction::unpack(&data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    /// Whatever arbitrary bytes decode to packs back to bytes that decode to
+    /// the same instruction.
+    #[test]
+    fn arbitrary_bytes_never_panic(data in prop::collection::vec(any::<u8>(), 0..512)) {
+        if let Ok((_version, payload)) = split_instruction_version(&data) {
+            if let Ok(instruction) = MovieInstruction::unpack(payload) {
+                prop_assert_eq!(MovieInstruction::unpack(&instruction.pack()), Ok(instruction));
+            }
+        }
+    }
+
+    /// Arbitrary payloads behind each valid variant byte, which reach the
+    /// payload decoders far more often than fully random bytes do.
+    #[test]
+    fn arbitrary_payloads_never_panic(
+        variant in any::<u8>(),
+        payload in prop::collection::vec(any::<u8>(), 0..256),
+    ) {
+        let mut data = vec![variant];
+        data.extend(payload);
+        if let Ok(instruction) = MovieInstruction::unpack(&data) {
+            prop_assert_eq!(MovieInstruction::unpack(&instruction.pack()), Ok(instruction));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Runs the program as built for the SBF VM rather than natively, to catch
+//! what only shows up there: stack frames over 4KB, the 32KB heap and real
+//! compute unit costs. Run with `cargo test-sbf`, which builds
+//! `movie_review.so`, points `ProgramTest` at it and enables `test-sbf`.
+
+#![cfg(feature = "test-sbf")]
+
+use {
+    movie_review::{
+        compute_units,
+        instruction::MovieInstruction,
+        state::{self, EntityKind, MovieAccountState, MAX_DESCRIPTION_LEN, MAX_TITLE_LEN},
+    },
+    solana_program::{
+        borsh::try_from_slice_unchecked,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+};
+
+/// `instruction` for `reviewer`'s review of `title`, requesting no more compute
+/// units than clients budget for it.
+fn review_transaction(
+    program_id: Pubkey,
+    reviewer: &Keypair,
+    recent_blockhash: Hash,
+    title: &str,
+    instruction: MovieInstruction,
+) -> Transaction {
+    let (review, _bump_seed) =
+        state::derive_review_pda(&program_id, EntityKind::Movie, &reviewer.pubkey(), title);
+    let (summary, _bump_seed) = state::derive_summary_pda(&program_id, EntityKind::Movie, title);
+    let (config, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let accounts = match &instruction {
+        MovieInstruction::DeleteMovieReview => vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(review, false),
+            AccountMeta::new(summary, false),
+        ],
+        _ => vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(summary, false),
+        ],
+    };
+
+    let units = compute_units::estimate(&instruction, 0);
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(units),
+            Instruction {
+                program_id,
+                accounts,
+                data: instruction.pack_versioned(),
+            },
+        ],
+        Some(&reviewer.pubkey()),
+    );
+    transaction.sign(&[reviewer], recent_blockhash);
+    transaction
+}
+
+async fn process_review_ix(
+    banks_client: &mut BanksClient,
+    program_id: Pubkey,
+    reviewer: &Keypair,
+    recent_blockhash: Hash,
+    title: &str,
+    instruction: MovieInstruction,
+) -> Result<(), BanksClientError> {
+    let transaction =
+        review_transaction(program_id, reviewer, recent_blockhash, title, instruction);
+    banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+async fn test_largest_reviews_fit_the_vm() {
+    let program_id = Pubkey::new_unique();
+    // No processor, so the compiled program is loaded instead.
+    let (mut banks_client, payer, recent_blockhash) =
+        ProgramTest::new("movie_review", program_id, None)
+            .start()
+            .await;
+
+    // The longest fields a transaction has room for, one review each.
+    let long_title = "t".repeat(MAX_TITLE_LEN);
+    let long_description = "d".repeat(MAX_DESCRIPTION_LEN);
+    for (title, description) in [
+        (long_title.as_str(), "Liked the movie"),
+        ("Captain America", long_description.as_str()),
+    ] {
+        process_review_ix(
+            &mut banks_client,
+            program_id,
+            &payer,
+            recent_blockhash,
+            title,
+            MovieInstruction::AddMovieReview {
+                title: title.to_string(),
+                rating: 45,
+                description: description.to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let (review_pda, _bump_seed) =
+            state::derive_review_pda(&program_id, EntityKind::Movie, &payer.pubkey(), title);
+        let account = banks_client.get_account(review_pda).await.unwrap().unwrap();
+        let review = try_from_slice_unchecked::<MovieAccountState>(&account.data).unwrap();
+        assert_eq!(review.title, title);
+        assert_eq!(review.description, description);
+
+        // Shrinking reallocates the account and refunds rent.
+        process_review_ix(
+            &mut banks_client,
+            program_id,
+            &payer,
+            recent_blockhash,
+            title,
+            MovieInstruction::UpdateMovieReview {
+                title: title.to_string(),
+                rating: 50,
+                description: "Loved it".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        process_review_ix(
+            &mut banks_client,
+            program_id,
+            &payer,
+            recent_blockhash,
+            title,
+            MovieInstruction::DeleteMovieReview,
+        )
+        .await
+        .unwrap();
+        assert!(banks_client
+            .get_account(review_pda)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
+
+/// Simulates each instruction before sending it and checks the units it used
+/// against its budget in `compute_units`, the one clients request. A handler
+/// that outgrows it fails here rather than on chain; raise the estimate there
+/// if the growth is intended.
+#[tokio::test]
+async fn test_review_instructions_stay_within_compute_budgets() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        ProgramTest::new("movie_review", program_id, None)
+            .start()
+            .await;
+
+    let title = "Captain America";
+    let long_description = "d".repeat(MAX_DESCRIPTION_LEN);
+    let instructions = [
+        (
+            "AddMovieReview",
+            MovieInstruction::AddMovieReview {
+                title: title.to_string(),
+                rating: 45,
+                description: "Liked the movie".to_string(),
+            },
+        ),
+        // Growing the account reallocates it and tops up its rent.
+        (
+            "UpdateMovieReview",
+            MovieInstruction::UpdateMovieReview {
+                title: title.to_string(),
+                rating: 50,
+                description: long_description,
+            },
+        ),
+    ];
+    for (name, instruction) in instructions {
+        let budget = compute_units::estimate(&instruction, 0);
+        let transaction =
+            review_transaction(program_id, &payer, recent_blockhash, title, instruction);
+        let simulation = banks_client
+            .simulate_transaction(transaction.clone())
+            .await
+            .unwrap();
+        assert_eq!(simulation.result, Some(Ok(())), "{}", name);
+        let units = simulation.simulation_details.unwrap().units_consumed;
+        assert!(
+            units <= budget as u64,
+            "{} used {} compute units, over its budget of {}",
+            name,
+            units,
+            budget
+        );
+
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+}
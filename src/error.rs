@@ -1,26 +0,0 @@
-use solana_program::program_error::ProgramError;
-use thiserror::Error;
-
-#[derive(Debug, Error)]
-pub enum ReviewError {
-    #[error("Account not initialized yet")]
-    UninitializedAccount,
-
-    #[error("PDA derived does not equal PDA passed in")]
-    InvalidPDA,
-
-    #[error("Input data exceeds max length")]
-    InvalidDataLength,
-
-    #[error("Rating greater than 5 or less than 1")]
-    InvalidRating,
-
-    #[error("Accounts do not match")]
-    IncorrectAccountError,
-}
-
-impl From<ReviewError> for ProgramError {
-    fn from(e: ReviewError) -> Self {
-        ProgramError::Custom(e as u32)
-    }
-}
\ No newline at end of file
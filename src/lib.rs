@@ -1,5 +0,0 @@
-pub mod entrypoint;
-pub mod error;
-pub mod instruction;
-pub mod processor;
-pub mod state;
\ No newline at end of file